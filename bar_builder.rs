@@ -0,0 +1,85 @@
+pub mod bar_builder {
+    use crate::models::models::{AggTrade, Kline};
+    use rust_decimal::Decimal;
+    use std::time::Duration;
+
+    // Buckets aggTrades into bars aligned to multiples of `duration` since the epoch, e.g. 5s
+    // bars that Binance doesn't offer. Intervals without trades produce no bar.
+    #[derive(Debug, Clone)]
+    pub struct BarBuilder {
+        duration_ms: i64,
+        current: Option<Kline>,
+    }
+
+    impl BarBuilder {
+        pub fn new(duration: Duration) -> Self {
+            assert!(
+                duration.as_millis() > 0,
+                "bar duration must be at least 1ms."
+            );
+            BarBuilder {
+                duration_ms: duration.as_millis() as i64,
+                current: None,
+            }
+        }
+
+        // Returns the previous bar once a trade lands in a later interval.
+        pub fn push(&mut self, trade: &AggTrade) -> Option<Kline> {
+            let open_time = trade.time - trade.time.rem_euclid(self.duration_ms);
+            let finished = match &self.current {
+                Some(bar) if bar.open_time != open_time => self.current.take(),
+                _ => None,
+            };
+            let trade_count = (trade.last_trade_id - trade.first_trade_id + 1).max(1) as u64;
+            let quote = trade.price * trade.qty;
+            let (taker_base, taker_quote) = if trade.is_buyer_maker {
+                (Decimal::ZERO, Decimal::ZERO)
+            } else {
+                (trade.qty, quote)
+            };
+            match &mut self.current {
+                Some(bar) => {
+                    bar.high = bar.high.max(trade.price);
+                    bar.low = bar.low.min(trade.price);
+                    bar.close = trade.price;
+                    bar.volume += trade.qty;
+                    bar.quote_volume += quote;
+                    bar.trade_count += trade_count;
+                    bar.taker_buy_base_volume += taker_base;
+                    bar.taker_buy_quote_volume += taker_quote;
+                }
+                None => {
+                    self.current = Some(Kline {
+                        open_time: open_time,
+                        open: trade.price,
+                        high: trade.price,
+                        low: trade.price,
+                        close: trade.price,
+                        volume: trade.qty,
+                        close_time: open_time + self.duration_ms - 1,
+                        quote_volume: quote,
+                        trade_count: trade_count,
+                        taker_buy_base_volume: taker_base,
+                        taker_buy_quote_volume: taker_quote,
+                    })
+                }
+            }
+            finished
+        }
+
+        // The bar still being built, e.g. to emit it on shutdown.
+        pub fn flush(&mut self) -> Option<Kline> {
+            self.current.take()
+        }
+
+        pub fn build_all(duration: Duration, trades: &[AggTrade]) -> Vec<Kline> {
+            let mut builder = BarBuilder::new(duration);
+            let mut bars = trades
+                .iter()
+                .filter_map(|a| builder.push(a))
+                .collect::<Vec<_>>();
+            bars.extend(builder.flush());
+            bars
+        }
+    }
+}
@@ -0,0 +1,246 @@
+pub mod basis {
+    #[cfg(feature = "websocket")]
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::MarkPriceEvent;
+    #[cfg(feature = "websocket")]
+    use futures::{stream, StreamExt};
+    use rust_decimal::Decimal;
+    use std::{collections::HashMap, fmt};
+    #[cfg(feature = "websocket")]
+    use std::{error::Error, sync::Mutex};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum BasisMetric {
+        // (perp - spot) / spot, in percent.
+        BasisPercent,
+        // `Basis::annualized_carry`, in percent.
+        AnnualizedCarry,
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct Basis {
+        pub symbol: String,
+        // Last spot trade.
+        pub spot_price: Decimal,
+        pub mark_price: Decimal,
+        pub funding_rate: Decimal,
+        pub next_funding_time: i64,
+        // In ms, of the later of the two prices.
+        pub updated: i64,
+    }
+
+    impl Basis {
+        // Both prices are in.
+        pub fn is_ready(&self) -> bool {
+            !self.spot_price.is_zero() && !self.mark_price.is_zero()
+        }
+
+        pub fn basis(&self) -> Decimal {
+            self.mark_price - self.spot_price
+        }
+
+        pub fn basis_percent(&self) -> Decimal {
+            if self.spot_price.is_zero() {
+                return Decimal::ZERO;
+            }
+            self.basis() / self.spot_price * Decimal::ONE_HUNDRED
+        }
+
+        // Yearly return of long spot and short perp at the current funding rate, settled every
+        // `funding_interval_hours`, in percent; negative when shorts pay.
+        pub fn annualized_carry(&self, funding_interval_hours: u32) -> Decimal {
+            let settlements =
+                Decimal::from(365 * 24) / Decimal::from(funding_interval_hours.max(1));
+            self.funding_rate * settlements * Decimal::ONE_HUNDRED
+        }
+    }
+
+    struct Threshold {
+        symbol: String,
+        metric: BasisMetric,
+        level: Decimal,
+        // Which side of `level` the metric was on last, None before the first value.
+        above: Option<bool>,
+        callback: Box<dyn FnMut(&Basis, bool) + Send>,
+    }
+
+    impl fmt::Debug for Threshold {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Threshold")
+                .field("symbol", &self.symbol)
+                .field("metric", &self.metric)
+                .field("level", &self.level)
+                .finish()
+        }
+    }
+
+    // Spot and perp prices per symbol, fed with spot trades and mark price events;
+    // `run_basis_monitor` does both. Symbols are the same on spot and futures (BTCUSDT).
+    #[derive(Debug)]
+    pub struct BasisMonitor {
+        bases: HashMap<String, Basis>,
+        // 8 on most perps; some settle every 4 hours.
+        pub funding_interval_hours: u32,
+        thresholds: Vec<Threshold>,
+    }
+
+    impl BasisMonitor {
+        pub fn new(symbols: &Vec<&str>) -> Self {
+            BasisMonitor {
+                bases: symbols
+                    .iter()
+                    .map(|a| {
+                        let symbol = a.to_uppercase();
+                        (
+                            symbol.clone(),
+                            Basis {
+                                symbol: symbol,
+                                ..Default::default()
+                            },
+                        )
+                    })
+                    .collect(),
+                funding_interval_hours: 8,
+                thresholds: vec![],
+            }
+        }
+
+        pub fn funding_interval_hours(mut self, funding_interval_hours: u32) -> Self {
+            self.funding_interval_hours = funding_interval_hours;
+            self
+        }
+
+        // Calls `callback` with the basis and true when `metric` of `symbol` crosses above
+        // `level`, false when it crosses back below. The first value only sets the side.
+        pub fn on_threshold(
+            mut self,
+            symbol: &str,
+            metric: BasisMetric,
+            level: Decimal,
+            callback: impl FnMut(&Basis, bool) + Send + 'static,
+        ) -> Self {
+            self.thresholds.push(Threshold {
+                symbol: symbol.to_uppercase(),
+                metric: metric,
+                level: level,
+                above: None,
+                callback: Box::new(callback),
+            });
+            self
+        }
+
+        pub fn symbols(&self) -> Vec<String> {
+            let mut symbols: Vec<_> = self.bases.keys().cloned().collect();
+            symbols.sort();
+            symbols
+        }
+
+        pub fn basis(&self, symbol: &str) -> Option<&Basis> {
+            self.bases.get(&symbol.to_uppercase())
+        }
+
+        pub fn metric(&self, basis: &Basis, metric: BasisMetric) -> Decimal {
+            match metric {
+                BasisMetric::BasisPercent => basis.basis_percent(),
+                BasisMetric::AnnualizedCarry => basis.annualized_carry(self.funding_interval_hours),
+            }
+        }
+
+        pub fn apply_spot_price(&mut self, symbol: &str, price: Decimal, time: i64) {
+            if let Some(basis) = self.bases.get_mut(symbol) {
+                basis.spot_price = price;
+                basis.updated = basis.updated.max(time);
+                self.check(symbol);
+            }
+        }
+
+        // Symbols not configured are skipped, so `!markPrice@arr` can be fed as is.
+        pub fn apply_mark_price(&mut self, event: &MarkPriceEvent) {
+            if let Some(basis) = self.bases.get_mut(&event.symbol) {
+                basis.mark_price = event.mark_price;
+                basis.funding_rate = event.funding_rate;
+                basis.next_funding_time = event.next_funding_time;
+                basis.updated = basis.updated.max(event.event_time);
+                self.check(&event.symbol);
+            }
+        }
+
+        fn check(&mut self, symbol: &str) {
+            let basis = match self.bases.get(symbol) {
+                Some(basis) if basis.is_ready() => basis.clone(),
+                _ => return,
+            };
+            let values: Vec<Decimal> = self
+                .thresholds
+                .iter()
+                .map(|a| self.metric(&basis, a.metric))
+                .collect();
+            for (threshold, value) in self.thresholds.iter_mut().zip(values) {
+                if threshold.symbol != symbol {
+                    continue;
+                }
+                let above = value > threshold.level;
+                if threshold.above.is_some_and(|a| a != above) {
+                    (threshold.callback)(&basis, above);
+                }
+                threshold.above = Some(above);
+            }
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    enum PriceUpdate {
+        Spot(String, Decimal),
+        Mark(MarkPriceEvent),
+    }
+
+    #[cfg(feature = "websocket")]
+    impl<'a> BinanceAPI<'a> {
+        // Keeps `monitor` up to date from this spot client's trades and `perp`'s mark prices
+        // until a stream ends. Read the monitor from another task through the mutex.
+        pub async fn run_basis_monitor(
+            &self,
+            perp: &BinanceAPI<'_>,
+            monitor: &Mutex<BasisMonitor>,
+        ) -> Result<(), Box<dyn Error>> {
+            assert!(
+                self.account_type == "spot" && perp.account_type == "swap",
+                "basis needs a `spot` client and a `swap` one."
+            );
+            let symbols = monitor.lock().unwrap().symbols();
+            let mut updates = vec![];
+            for symbol in &symbols {
+                let spot_symbol = symbol.clone();
+                updates.push(
+                    stream::unfold(self.stream_last_price(symbol), move |mut rx| {
+                        let symbol = spot_symbol.clone();
+                        async move {
+                            let price = rx.recv().await?;
+                            Some((PriceUpdate::Spot(symbol, price), rx))
+                        }
+                    })
+                    .boxed(),
+                );
+                updates.push(
+                    stream::unfold(perp.stream_mark_price(symbol), |mut rx| async move {
+                        Some((PriceUpdate::Mark(rx.recv().await?), rx))
+                    })
+                    .boxed(),
+                );
+            }
+            let mut updates = stream::select_all(updates);
+            while let Some(update) = updates.next().await {
+                let mut monitor = monitor.lock().unwrap();
+                match update {
+                    PriceUpdate::Spot(symbol, price) => monitor.apply_spot_price(
+                        &symbol,
+                        price,
+                        chrono::Utc::now().timestamp_millis(),
+                    ),
+                    PriceUpdate::Mark(event) => monitor.apply_mark_price(&event),
+                }
+            }
+            Err("Basis price stream ended.".into())
+        }
+    }
+}
@@ -1,23 +1,174 @@
 pub mod binance_api {
-    use chrono::{
-        prelude::{DateTime, TimeZone, Utc},
-        Duration, NaiveDateTime,
+    use crate::builder::builder::{BinanceAPIBuilder, RateLimitBehavior, TimeUnit};
+    #[cfg(feature = "metrics")]
+    use crate::metrics::metrics;
+    #[cfg(feature = "wallet")]
+    use crate::models::models::{
+        AccountApiTradingStatus, AccountStatus, ApiKeyPermissions, AssetTransfer, CoinInfo,
+        DustAssets, DustTransferResult, MarginInterest, MarginInterestRate, MaxBorrowable,
+        SystemStatus,
     };
-    use hmac::{Hmac, Mac};
-    use sha2::Sha256;
-    use std::{collections::HashMap, error::Error, net::TcpStream};
-    use tungstenite::{connect, protocol::WebSocket, stream::MaybeTlsStream, Message};
+    #[cfg(feature = "usdm-futures")]
+    use crate::models::models::{
+        AccountConfig, ApiTradingStatus, AssetIndex, ContractType, IndexInfo, MarginCallEvent,
+        Position, PositionMarginChange, SymbolConfig,
+    };
+    use crate::models::models::{
+        AggTrade, AssetBalance, BookTicker, DepthSnapshot, Kline, KlineQuery, NewOrder,
+        SymbolFilters, TimeInput, Trade,
+    };
+    #[cfg(feature = "spot")]
+    use crate::models::models::{AvgPrice, NewOcoOrder, OcoOrderReport};
+    use crate::paper::paper::PaperAccount;
+    use crate::rate_limiter::rate_limiter::{RateLimiter, RequestPriority};
+    use crate::risk::risk::RiskState;
+    use crate::signer::signer::{HmacSigner, Signer};
+    #[cfg(feature = "websocket")]
+    use crate::stream::stream::{StreamTasks, WsRequests};
+    use crate::symbol_registry::symbol_registry::{SymbolInfo, SymbolRegistry};
+    use crate::transport::transport::{
+        redact, HttpRequest, HttpResponse, QueryBuilder, Response, Transport, TransportHandle,
+    };
+    use chrono::{prelude::Utc, Duration};
+    use futures::{stream, StreamExt};
+    use rust_decimal::Decimal;
+    use serde::de::DeserializeOwned;
+    #[cfg(feature = "websocket")]
+    use std::net::TcpStream;
+    use std::{
+        borrow::Cow,
+        collections::HashMap,
+        error::Error,
+        fmt,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        time::Instant,
+    };
+    use tracing::Instrument;
+    #[cfg(feature = "websocket")]
+    use tungstenite::{connect, protocol::WebSocket, stream::MaybeTlsStream};
+    #[cfg(feature = "websocket")]
     use url::Url;
 
     type SendRequestRe = serde_json::Value;
-    #[derive(Default, Debug)]
+
+    // Lookups, 0.5s, 1s, 1.5s and 2s apart, before an idempotent order is sent again.
+    const NOT_FOUND_LOOKUPS: u64 = 4;
+
+    #[derive(Debug, Clone)]
+    pub struct BinanceError {
+        pub code: i64,
+        pub msg: String,
+    }
+
+    impl fmt::Display for BinanceError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Binance error {}: {}", self.code, self.msg)
+        }
+    }
+
+    impl Error for BinanceError {}
+
+    // What signs requests; `rotate_keys` swaps it as a whole, so a request never mixes keys.
+    #[derive(Default)]
+    pub(crate) struct ApiKeys {
+        pub(crate) api_key: String,
+        secret_key: String,
+        signer: Option<Arc<dyn Signer>>,
+    }
+
+    impl ApiKeys {
+        fn sign(&self, query: &str) -> String {
+            match &self.signer {
+                Some(signer) => signer.sign(query),
+                None => HmacSigner::new(&self.secret_key).sign(query),
+            }
+        }
+    }
+
+    impl fmt::Debug for ApiKeys {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ApiKeys")
+                .field("api_key", &redact(&self.api_key))
+                .field("secret_key", &redact(&self.secret_key))
+                .field("signer", &self.signer)
+                .finish()
+        }
+    }
+
+    #[derive(Default)]
     pub struct BinanceAPI<'a> {
-        api_key: &'a str,
-        secret_key: &'a str,
+        keys: RwLock<Arc<ApiKeys>>,
         pub account_type: &'a str,
-        base_url: &'a str,
-        wss_url: &'a str,
-        listen_key: String,
+        base_url: Cow<'a, str>,
+        pub(crate) wss_url: Cow<'a, str>,
+        pub(crate) sapi_url: Cow<'a, str>,
+        pub(crate) listen_key: String,
+        recv_window: u64,
+        rate_limit: RateLimitBehavior,
+        order_retries: Option<u32>,
+        order_ids: AtomicU64,
+        pub(crate) time_unit: TimeUnit,
+        pub rate_limiter: RateLimiter,
+        pub symbol_registry: SymbolRegistry,
+        pub(crate) paper: Mutex<Option<PaperAccount>>,
+        pub(crate) risk: Mutex<Option<RiskState>>,
+        pub(crate) transport: TransportHandle,
+        #[cfg(feature = "websocket")]
+        pub(crate) streams: StreamTasks,
+        #[cfg(feature = "websocket")]
+        pub(crate) ws_requests: WsRequests,
+    }
+
+    // Keys and the listen key never reach logs through `{:?}`.
+    impl<'a> fmt::Debug for BinanceAPI<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let mut debug = f.debug_struct("BinanceAPI");
+            debug
+                .field("keys", &self.api_keys())
+                .field("account_type", &self.account_type)
+                .field("base_url", &self.base_url)
+                .field("wss_url", &self.wss_url)
+                .field("sapi_url", &self.sapi_url)
+                .field("listen_key", &redact(&self.listen_key))
+                .field("recv_window", &self.recv_window)
+                .field("rate_limit", &self.rate_limit)
+                .field("order_retries", &self.order_retries)
+                .field("time_unit", &self.time_unit)
+                .field("rate_limiter", &self.rate_limiter)
+                .field("symbol_registry", &self.symbol_registry)
+                .field("paper", &self.paper)
+                .field("risk", &self.risk)
+                .field("transport", &self.transport);
+            #[cfg(feature = "websocket")]
+            debug
+                .field("streams", &self.streams)
+                .field("ws_requests", &self.ws_requests);
+            debug.finish()
+        }
+    }
+
+    fn trim_url(url: Cow<'_, str>) -> Cow<'_, str> {
+        match url {
+            Cow::Borrowed(url) => Cow::Borrowed(url.trim_end_matches('/')),
+            Cow::Owned(url) => Cow::Owned(url.trim_end_matches('/').to_string()),
+        }
+    }
+
+    // Length of a kline interval such as `15m` or `1w`, in ms; None for `1M`, whose months differ.
+    pub(crate) fn interval_ms(interval: &str) -> Option<i64> {
+        let (count, unit) = interval.split_at(interval.len().checked_sub(1)?);
+        let unit_ms = match unit {
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            "w" => 604_800_000,
+            _ => return None,
+        };
+        Some(count.parse::<i64>().ok()? * unit_ms)
     }
 
     impl<'a> BinanceAPI<'a> {
@@ -26,32 +177,154 @@ pub mod binance_api {
             secret_key: &'a str,
             account_type: &'a str,
         ) -> Result<Self, Box<dyn Error>> {
-            let (base_url, wss_url) = match account_type {
-                "spot" => ("https://api.binance.com", "wss://stream.binance.com/ws"),
-                "swap" => ("https://fapi.binance.com", "wss://fstream.binance.com/ws"),
-                _ => Self::panic_not_define("Account type", account_type, ("", "")),
+            Self::builder()
+                .keys(api_key, secret_key)
+                .account_type(account_type)
+                .auto_listen_key(true)
+                .build()
+                .await
+        }
+
+        pub async fn with_transport(
+            api_key: &'a str,
+            secret_key: &'a str,
+            account_type: &'a str,
+            transport: impl Transport + 'static,
+        ) -> Result<Self, Box<dyn Error>> {
+            Self::builder()
+                .keys(api_key, secret_key)
+                .account_type(account_type)
+                .transport(transport)
+                .auto_listen_key(true)
+                .build()
+                .await
+        }
+
+        pub fn builder() -> BinanceAPIBuilder<'a> {
+            BinanceAPIBuilder::new()
+        }
+
+        // Unit of the time fields in responses and stream events.
+        pub fn time_unit(&self) -> TimeUnit {
+            self.time_unit
+        }
+
+        pub(crate) async fn from_builder(
+            builder: BinanceAPIBuilder<'a>,
+            transport: Arc<dyn Transport>,
+        ) -> Result<Self, Box<dyn Error>> {
+            let (base_url, wss_url) = match (builder.account_type, builder.testnet) {
+                #[cfg(feature = "spot")]
+                ("spot", false) => ("https://api.binance.com", "wss://stream.binance.com/ws"),
+                #[cfg(feature = "usdm-futures")]
+                ("swap", false) => ("https://fapi.binance.com", "wss://fstream.binance.com/ws"),
+                #[cfg(feature = "spot")]
+                ("spot", true) => (
+                    "https://testnet.binance.vision",
+                    "wss://stream.testnet.binance.vision/ws",
+                ),
+                #[cfg(feature = "usdm-futures")]
+                ("swap", true) => (
+                    "https://testnet.binancefuture.com",
+                    "wss://stream.binancefuture.com/ws",
+                ),
+                _ => Self::panic_not_define("Account type", builder.account_type, ("", "")),
+            };
+            // Overrides (another api cluster, binance.us, a proxy) replace only the host; the
+            // spot/swap endpoint paths are still chosen by account type.
+            let sapi_url = match (&builder.sapi_url, &builder.base_url) {
+                (Some(sapi_url), _) => sapi_url.clone(),
+                (None, Some(base_url)) if builder.account_type == "spot" => base_url.clone(),
+                _ => Cow::Borrowed("https://api.binance.com"),
             };
             let mut bn_api = BinanceAPI {
-                api_key: api_key,
-                secret_key: secret_key,
-                account_type: account_type,
-                base_url: base_url,
-                wss_url: wss_url,
+                keys: RwLock::new(Arc::new(ApiKeys {
+                    api_key: builder.api_key.into_owned(),
+                    secret_key: builder.secret_key.into_owned(),
+                    signer: builder.signer,
+                })),
+                account_type: builder.account_type,
+                base_url: trim_url(builder.base_url.unwrap_or(Cow::Borrowed(base_url))),
+                wss_url: trim_url(builder.wss_url.unwrap_or(Cow::Borrowed(wss_url))),
+                sapi_url: trim_url(sapi_url),
                 listen_key: Default::default(),
+                recv_window: builder.recv_window,
+                rate_limit: builder.rate_limit,
+                order_retries: builder.order_retries,
+                order_ids: Default::default(),
+                time_unit: builder.time_unit,
+                rate_limiter: RateLimiter::new(if builder.account_type == "spot" {
+                    6000
+                } else {
+                    2400
+                }),
+                symbol_registry: Default::default(),
+                paper: Default::default(),
+                risk: Default::default(),
+                transport: TransportHandle(transport),
+                #[cfg(feature = "websocket")]
+                streams: StreamTasks::new(builder.ws_compression),
+                #[cfg(feature = "websocket")]
+                ws_requests: Default::default(),
             };
-            if api_key.is_empty() || secret_key.is_empty() {
-            } else {
-                bn_api.listen_key = bn_api.listen_key_manager("generate").await.unwrap();
+            #[cfg(feature = "websocket")]
+            if builder.auto_listen_key
+                && !bn_api.api_keys().api_key.is_empty()
+                && !bn_api.api_keys().secret_key.is_empty()
+            {
+                bn_api.create_listen_key().await?;
             };
             return Ok(bn_api);
         }
 
-        fn panic_not_define<T>(type_name: &str, type_content: &str, res: T) -> T {
+        #[cfg(feature = "websocket")]
+        pub async fn create_listen_key(&mut self) -> Result<(), Box<dyn Error>> {
+            self.listen_key = self.listen_key_manager("generate").await?;
+            Ok(())
+        }
+
+        // Swaps in new keys without a restart. With a listen key, a new one is created with the
+        // new keys first, so on error the old keys stay in use; running user streams then reopen
+        // on it (reported as a `Gap`) while market streams stay connected. The old listen key is
+        // left to expire. `signer` signs for the new key, e.g. an `Ed25519Signer`; None signs with
+        // HMAC and `secret_key`. Takes `&self`, so a client shared with running tasks can rotate.
+        pub async fn rotate_keys(
+            &self,
+            api_key: &str,
+            secret_key: &str,
+            signer: Option<Box<dyn Signer>>,
+        ) -> Result<(), Box<dyn Error>> {
+            let new_keys = Arc::new(ApiKeys {
+                api_key: api_key.to_string(),
+                secret_key: secret_key.to_string(),
+                signer: signer.map(Arc::from),
+            });
+            let old_keys = std::mem::replace(&mut *self.keys.write().unwrap(), new_keys);
+            #[cfg(feature = "websocket")]
+            if !self.current_listen_key().is_empty() {
+                let listen_key = match self.listen_key_manager("generate").await {
+                    Ok(listen_key) => listen_key,
+                    Err(e) => {
+                        *self.keys.write().unwrap() = old_keys;
+                        return Err(e);
+                    }
+                };
+                self.streams.rotate(api_key, Some(listen_key));
+            } else {
+                self.streams.rotate(api_key, None);
+            }
+            #[cfg(not(feature = "websocket"))]
+            drop(old_keys);
+            tracing::info!("api keys rotated");
+            Ok(())
+        }
+
+        pub(crate) fn panic_not_define<T>(type_name: &str, type_content: &str, res: T) -> T {
             assert!(false, "{type_name} `{type_content}` is not defined.");
             return res;
         }
 
-        fn generate_exchange_url(&self, spot_swap_url: (&str, &str)) -> String {
+        pub(crate) fn generate_exchange_url(&self, spot_swap_url: (&str, &str)) -> String {
             let current_type = self.account_type;
             let url = match current_type {
                 "spot" => spot_swap_url.0,
@@ -61,17 +334,14 @@ pub mod binance_api {
             self.base_url.to_string() + url
         }
 
-        fn generate_signature(&self, param_map: &HashMap<String, String>) -> String {
-            let mut query = String::new();
-            for (key, value) in param_map {
-                query.push_str(&format!("{}={}&", key, value));
-            }
-            query.pop();
-            type HmacSha256 = Hmac<Sha256>;
-            let mut mac = HmacSha256::new_from_slice(self.secret_key.as_bytes())
-                .expect("HMAC can take key of any size");
-            mac.update(query.as_bytes());
-            hex::encode(mac.finalize().into_bytes())
+        pub(crate) fn api_keys(&self) -> Arc<ApiKeys> {
+            self.keys.read().unwrap().clone()
+        }
+
+        // Appends the signature of `query` as sent, see `QueryBuilder`.
+        fn sign(keys: &ApiKeys, query: QueryBuilder) -> QueryBuilder {
+            let signature = keys.sign(&query.build());
+            query.push("signature", &signature)
         }
 
         pub async fn send_request(
@@ -81,40 +351,505 @@ pub mod binance_api {
             param_map: &mut HashMap<String, String>,
             signature: bool,
         ) -> Result<SendRequestRe, Box<dyn Error>> {
-            let client = reqwest::Client::new();
-            let mut headers_map = reqwest::header::HeaderMap::new();
-            headers_map.insert("Content-Type", "application/json".parse().unwrap());
-            headers_map.insert("X-MBX-APIKEY", self.api_key.parse().unwrap());
-            let mut signature_map = HashMap::new();
-            if signature {
-                param_map.insert(
-                    "timestamp".to_string(),
-                    Utc::now().timestamp_millis().to_string(),
+            let res = self
+                .send_request_raw(url, method, param_map, signature)
+                .await?;
+            Ok(serde_json::from_str(&res.body).expect("Can't parse data to JSON"))
+        }
+
+        // Same as `send_request` but keeps the status, headers and body text next to the parsed data.
+        pub async fn send_request_parsed<T: DeserializeOwned>(
+            &self,
+            url: &str,
+            method: &str,
+            param_map: &mut HashMap<String, String>,
+            signature: bool,
+        ) -> Result<Response<T>, Box<dyn Error>> {
+            let res = self
+                .send_request_raw(url, method, param_map, signature)
+                .await?;
+            let parsed = serde_json::from_str(&res.body)?;
+            Ok(Response {
+                status: res.status,
+                headers: res.headers,
+                body: res.body,
+                data: Self::parse_response(parsed)?,
+            })
+        }
+
+        pub async fn send_request_raw(
+            &self,
+            url: &str,
+            method: &str,
+            param_map: &mut HashMap<String, String>,
+            signature: bool,
+        ) -> Result<HttpResponse, Box<dyn Error>> {
+            if !["GET", "POST", "PUT", "DELETE"].contains(&method) {
+                Self::panic_not_define("Request method", method, ());
+            }
+            let path = url::Url::parse(url)
+                .map(|a| a.path().to_string())
+                .unwrap_or_default();
+            let priority = RequestPriority::for_request(method, &path);
+            let mut retries = 0;
+            let res = loop {
+                let queued = self.rate_limiter.queue(priority);
+                while let Some(wait) = self.rate_limiter.reserve_for(1, priority, &path) {
+                    tracing::info!(
+                        wait_ms = wait.as_millis() as u64,
+                        priority = ?priority,
+                        "request weight exhausted, waiting"
+                    );
+                    self.transport.0.sleep(wait).await;
+                }
+                drop(queued);
+                let keys = self.api_keys();
+                let query = if signature {
+                    param_map.insert(
+                        "timestamp".to_string(),
+                        Utc::now().timestamp_millis().to_string(),
+                    );
+                    if self.recv_window > 0 {
+                        param_map.insert("recvWindow".to_string(), self.recv_window.to_string());
+                    }
+                    Self::sign(&keys, QueryBuilder::from_map(param_map))
+                } else {
+                    QueryBuilder::from_map(param_map)
+                };
+                let mut headers = vec![
+                    ("Content-Type".to_string(), "application/json".to_string()),
+                    ("X-MBX-APIKEY".to_string(), keys.api_key.clone()),
+                ];
+                if self.time_unit != TimeUnit::Millisecond {
+                    headers.push((
+                        "X-MBX-TIME-UNIT".to_string(),
+                        self.time_unit.as_str().to_string(),
+                    ));
+                }
+                let request = HttpRequest {
+                    method: method.to_string(),
+                    url: url.to_string(),
+                    headers: headers,
+                    query: query.into_pairs(),
+                };
+                let endpoint = request.path();
+                let span = tracing::info_span!(
+                    "rest",
+                    endpoint = %endpoint,
+                    method = method,
+                    signed = signature
                 );
-                signature_map.insert("signature", self.generate_signature(&param_map));
-            }
-            let res = match method {
-                "GET" => client.get(url),
-                "POST" => client.post(url),
-                "PUT" => client.put(url),
-                "DELETE" => client.delete(url),
-                _ => Self::panic_not_define("Request method", method, client.get(url)),
-            };
-            let res = res.headers(headers_map);
-            let res = if param_map.is_empty() {
-                res
-            } else {
-                res.query(&param_map)
+                let started = Instant::now();
+                // Matched on directly so no error is kept across the awaits below.
+                let res = match self
+                    .transport
+                    .0
+                    .send(request)
+                    .instrument(span.clone())
+                    .await
+                {
+                    Ok(res) => {
+                        let latency_ms = started.elapsed().as_millis() as u64;
+                        if let Some(weight) = res.used_weight() {
+                            self.rate_limiter.record_for(&path, 1, weight);
+                        }
+                        tracing::debug!(
+                            parent: &span,
+                            status = res.status,
+                            weight = res.used_weight(),
+                            latency_ms = latency_ms,
+                            "response"
+                        );
+                        #[cfg(feature = "metrics")]
+                        {
+                            metrics::record_request(&endpoint, method, res.status, latency_ms);
+                            if let Some(weight) = res.used_weight() {
+                                metrics::record_used_weight(weight);
+                            }
+                        }
+                        res
+                    }
+                    Err(e) => {
+                        let latency_ms = started.elapsed().as_millis() as u64;
+                        tracing::warn!(parent: &span, latency_ms = latency_ms, error = %e, "request failed");
+                        #[cfg(feature = "metrics")]
+                        metrics::record_transport_error(&endpoint, method);
+                        return Err(e);
+                    }
+                };
+                match self.rate_limit {
+                    RateLimitBehavior::Wait { max_retries }
+                        if [418, 429].contains(&res.status) && retries < max_retries =>
+                    {
+                        let retry_after = res
+                            .header("Retry-After")
+                            .and_then(|a| a.parse().ok())
+                            .unwrap_or(1);
+                        tracing::warn!(parent: &span, retry_after = retry_after, "rate limited, waiting");
+                        self.transport
+                            .0
+                            .sleep(std::time::Duration::from_secs(retry_after))
+                            .await;
+                        retries += 1;
+                    }
+                    _ => break res,
+                }
             };
-            let res = if signature {
-                res.query(&signature_map)
+            Ok(res)
+        }
+
+        pub(crate) fn parse_response<T: DeserializeOwned>(
+            parsed: SendRequestRe,
+        ) -> Result<T, Box<dyn Error>> {
+            if let Some(code) = parsed["code"].as_i64() {
+                if code < 0 {
+                    #[cfg(feature = "metrics")]
+                    metrics::record_error_code(code);
+                    return Err(Box::new(BinanceError {
+                        code: code,
+                        msg: parsed["msg"].as_str().unwrap_or_default().to_string(),
+                    }));
+                }
+            }
+            Ok(serde_json::from_value(parsed)?)
+        }
+
+        fn parse_response_list<T: DeserializeOwned>(
+            parsed: SendRequestRe,
+        ) -> Result<Vec<T>, Box<dyn Error>> {
+            if parsed.is_array() {
+                Self::parse_response(parsed)
             } else {
-                res
-            };
-            let res = res.send().await?.text().await?;
-            Ok(serde_json::from_str(&res).expect("Can't parse data to JSON"))
+                Ok(vec![Self::parse_response(parsed)?])
+            }
+        }
+
+        pub async fn ping(&self) -> Result<(), Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/ping", "/fapi/v1/ping"));
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response::<SendRequestRe>(parsed)?;
+            Ok(())
+        }
+
+        pub async fn server_time(&self) -> Result<i64, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/time", "/fapi/v1/time"));
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            let parsed: SendRequestRe = Self::parse_response(parsed)?;
+            Ok(parsed["serverTime"]
+                .as_i64()
+                .ok_or("Can't get `serverTime` from response.")?)
+        }
+
+        #[cfg(feature = "wallet")]
+        pub async fn system_status(&self) -> Result<SystemStatus, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/system/status";
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "wallet")]
+        pub async fn get_api_key_permissions(&self) -> Result<ApiKeyPermissions, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/account/apiRestrictions";
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "wallet")]
+        pub async fn ensure_api_key_permissions(
+            &self,
+            required: &Vec<&str>,
+        ) -> Result<ApiKeyPermissions, Box<dyn Error>> {
+            let permissions = self.get_api_key_permissions().await?;
+            let missing = required
+                .iter()
+                .filter(|a| !match **a {
+                    "reading" => permissions.enable_reading,
+                    "spot" => permissions.enable_spot_and_margin_trading,
+                    "margin" => permissions.enable_margin,
+                    "futures" => permissions.enable_futures,
+                    "withdrawals" => permissions.enable_withdrawals,
+                    "internal_transfer" => permissions.enable_internal_transfer,
+                    "universal_transfer" => permissions.permits_universal_transfer,
+                    "vanilla_options" => permissions.enable_vanilla_options,
+                    _ => Self::panic_not_define("API key permission", a, false),
+                })
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                return Err(
+                    format!("API key is missing permissions: {}.", missing.join(", ")).into(),
+                );
+            }
+            Ok(permissions)
+        }
+
+        #[cfg(feature = "wallet")]
+        pub async fn get_account_status(&self) -> Result<AccountStatus, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/account/status";
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        // Whether the spot quantitative rules locked the API key, and the indicators behind it.
+        #[cfg(feature = "wallet")]
+        pub async fn get_account_api_trading_status(
+            &self,
+        ) -> Result<AccountApiTradingStatus, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/account/apiTradingStatus";
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            let parsed: SendRequestRe = Self::parse_response(parsed)?;
+            Ok(serde_json::from_value(parsed["data"].clone())?)
+        }
+
+        // Fails when the account isn't `Normal` or its API trading is locked, e.g. at startup.
+        #[cfg(feature = "wallet")]
+        pub async fn ensure_account_active(&self) -> Result<(), Box<dyn Error>> {
+            let status = self.get_account_status().await?;
+            if !status.is_normal() {
+                return Err(format!("Account status is `{}`.", status.status).into());
+            }
+            let trading = self.get_account_api_trading_status().await?;
+            if trading.is_locked {
+                return Err(format!(
+                    "API trading is locked until {}.",
+                    trading.planned_recover_time
+                )
+                .into());
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "wallet")]
+        pub async fn get_dust_assets(&self) -> Result<DustAssets, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/asset/dust-btc";
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "wallet")]
+        pub async fn dust_transfer(
+            &self,
+            assets: &Vec<&str>,
+        ) -> Result<DustTransferResult, Box<dyn Error>> {
+            // `asset` is repeated once per asset, which a HashMap of params can't hold,
+            // so the query is signed and attached to the url here.
+            let mut query = assets
+                .iter()
+                .fold(QueryBuilder::new(), |query, a| query.push("asset", a))
+                .push("timestamp", &Utc::now().timestamp_millis().to_string());
+            if self.recv_window > 0 {
+                query = query.push("recvWindow", &self.recv_window.to_string());
+            }
+            let url = format!(
+                "{}/sapi/v1/asset/dust?{}",
+                self.sapi_url,
+                Self::sign(&self.api_keys(), query).build()
+            );
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, false)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        // Deposit and withdrawal status, fees and minimums of every coin, per network.
+        #[cfg(feature = "wallet")]
+        pub async fn get_all_coins_info(&self) -> Result<Vec<CoinInfo>, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/capital/config/getall";
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "wallet")]
+        fn futures_transfer_type(direction: &str) -> &'static str {
+            match direction {
+                "to_futures" => "MAIN_UMFUTURE",
+                "to_spot" => "UMFUTURE_MAIN",
+                "to_coin_futures" => "MAIN_CMFUTURE",
+                "from_coin_futures" => "CMFUTURE_MAIN",
+                _ => Self::panic_not_define("Futures transfer direction", direction, ""),
+            }
+        }
+
+        // Moves `amount` of `asset` between the spot and futures wallets; `direction` is
+        // `to_futures`, `to_spot`, `to_coin_futures` or `from_coin_futures`. Returns the tranId.
+        #[cfg(feature = "wallet")]
+        pub async fn futures_transfer(
+            &self,
+            asset: &str,
+            amount: Decimal,
+            direction: &str,
+        ) -> Result<i64, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/asset/transfer";
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert(
+                "type".to_string(),
+                Self::futures_transfer_type(direction).to_string(),
+            );
+            param_map.insert("asset".to_string(), asset.to_string());
+            param_map.insert("amount".to_string(), amount.normalize().to_string());
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            let parsed: SendRequestRe = Self::parse_response(parsed)?;
+            Ok(parsed["tranId"]
+                .as_i64()
+                .ok_or("Can't get `tranId` from response.")?)
+        }
+
+        // Transfers in one `direction` (see `futures_transfer`), newest first. The exchange keeps
+        // six months of history and queries at most 30 days without a start time.
+        #[cfg(feature = "wallet")]
+        pub async fn get_futures_transfer_history(
+            &self,
+            direction: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<AssetTransfer>, Box<dyn Error>> {
+            self.get_transfer_history(
+                Self::futures_transfer_type(direction),
+                start_time_utc,
+                end_time_utc,
+            )
+            .await
+        }
+
+        // Transfers of one universal transfer `kind` between the account's wallets, e.g.
+        // MAIN_UMFUTURE, MAIN_MARGIN, MARGIN_MAIN, MAIN_FUNDING or FUNDING_UMFUTURE, newest
+        // first, every page. Same history limits as `get_futures_transfer_history`.
+        #[cfg(feature = "wallet")]
+        pub async fn get_transfer_history(
+            &self,
+            kind: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<AssetTransfer>, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/asset/transfer";
+            let (start_time, end_time) =
+                self.time_range_ms(start_time_utc.into(), end_time_utc.into())?;
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("type".to_string(), kind.to_uppercase());
+            if start_time > 0 {
+                param_map.insert("startTime".to_string(), start_time.to_string());
+            }
+            if end_time < i64::MAX {
+                param_map.insert("endTime".to_string(), end_time.to_string());
+            }
+            self.paginate_rows(url.as_str(), &mut param_map, true).await
+        }
+
+        // `get_transfer_history` over several `kinds`, merged newest first, to reconcile every
+        // wallet at once.
+        #[cfg(feature = "wallet")]
+        pub async fn get_transfer_history_all(
+            &self,
+            kinds: &Vec<&str>,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<AssetTransfer>, Box<dyn Error>> {
+            let start_time_utc = start_time_utc.into();
+            let end_time_utc = end_time_utc.into();
+            let mut transfers = vec![];
+            for kind in kinds {
+                transfers.extend(
+                    self.get_transfer_history(kind, &start_time_utc, &end_time_utc)
+                        .await?,
+                );
+            }
+            transfers.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            Ok(transfers)
+        }
+
+        // Interest charged on margin loans of `asset` ("" for every asset), cross margin unless
+        // `isolated_symbol` is set. The exchange keeps six months and returns the last 7 days
+        // without a start time.
+        #[cfg(feature = "wallet")]
+        pub async fn get_margin_interest_history(
+            &self,
+            asset: &str,
+            isolated_symbol: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<MarginInterest>, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/margin/interestHistory";
+            let (start_time, end_time) =
+                self.time_range_ms(start_time_utc.into(), end_time_utc.into())?;
+            let mut param_map = std::collections::HashMap::new();
+            if !asset.is_empty() {
+                param_map.insert("asset".to_string(), asset.to_string());
+            }
+            if !isolated_symbol.is_empty() {
+                param_map.insert("isolatedSymbol".to_string(), isolated_symbol.to_string());
+            }
+            if start_time > 0 {
+                param_map.insert("startTime".to_string(), start_time.to_string());
+            }
+            if end_time < i64::MAX {
+                param_map.insert("endTime".to_string(), end_time.to_string());
+            }
+            self.paginate_rows(url.as_str(), &mut param_map, true).await
+        }
+
+        // The hourly cross margin borrow rate of `assets` (at most 20) for the next hour.
+        #[cfg(feature = "wallet")]
+        pub async fn get_cross_margin_interest_rate(
+            &self,
+            assets: &Vec<&str>,
+        ) -> Result<Vec<MarginInterestRate>, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/margin/next-hourly-interest-rate";
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("assets".to_string(), assets.join(",").to_uppercase());
+            param_map.insert("isIsolated".to_string(), "false".to_string());
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        // How much of `asset` can be borrowed, on cross margin unless `isolated_symbol` is set.
+        #[cfg(feature = "wallet")]
+        pub async fn get_max_borrowable(
+            &self,
+            asset: &str,
+            isolated_symbol: &str,
+        ) -> Result<MaxBorrowable, Box<dyn Error>> {
+            let url = self.sapi_url.to_string() + "/sapi/v1/margin/maxBorrowable";
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("asset".to_string(), asset.to_uppercase());
+            if !isolated_symbol.is_empty() {
+                param_map.insert("isolatedSymbol".to_string(), isolated_symbol.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
         }
 
+        #[cfg(feature = "websocket")]
         pub async fn listen_key_manager(&self, method: &str) -> Result<String, Box<dyn Error>> {
             let url = self.generate_exchange_url(("/api/v3/userDataStream", "/fapi/v1/listenKey"));
             let method_request = match method {
@@ -125,33 +860,39 @@ pub mod binance_api {
             };
             let mut param_map = HashMap::new();
             if ["delay", "delete"].contains(&method) {
-                param_map.insert("listenKey".to_string(), self.listen_key.to_string());
+                param_map.insert("listenKey".to_string(), self.current_listen_key());
             }
 
             let parsed = self
                 .send_request(url.as_str(), method_request, &mut param_map, false)
                 .await?;
+            let parsed: SendRequestRe = Self::parse_response(parsed)?;
             if method == "generate" {
-                let listenkey = parsed["listenKey"].to_string();
-                let listenkey = listenkey.split("\"").collect::<Vec<_>>()[1];
+                let listenkey = parsed["listenKey"]
+                    .as_str()
+                    .ok_or_else(|| format!("No listenKey in `{parsed}`."))?;
                 return Ok(listenkey.to_string());
             } else {
                 return Ok("".to_string());
             }
         }
 
+        #[cfg(feature = "websocket")]
         pub fn generate_websocket(&self, type_ws: &str) -> WebSocket<MaybeTlsStream<TcpStream>> {
             let stream_url = self.wss_url.to_string();
             let stream_url = match type_ws {
-                "account" => stream_url + "/" + self.listen_key.as_str(),
+                "account" => stream_url + "/" + self.current_listen_key().as_str(),
                 "market" => stream_url,
                 _ => Self::panic_not_define("Websocket type", type_ws, stream_url),
             };
+            let stream_url = self.with_time_unit(stream_url);
+            tracing::info!(stream = type_ws, url = %self.wss_url, "websocket connect");
             let (mut websocket, _response) =
                 connect(Url::parse(&stream_url).unwrap()).expect("Can't connect.");
             return websocket;
         }
 
+        #[cfg(feature = "websocket")]
         pub async fn subscribe_websocket(
             &self,
             ws: &mut WebSocket<MaybeTlsStream<TcpStream>>,
@@ -160,17 +901,50 @@ pub mod binance_api {
         ) -> Result<(), Box<dyn Error>> {
             let subscribes = symbols
                 .iter()
-                .map(|a| format!(r#""{}@{}""#, a.to_lowercase(), sub_type))
-                .collect::<Vec<_>>()
-                .join(",");
-            let subscribes = format!(
-                r#"{{"method": "SUBSCRIBE", "params": [{}], "id": 1}}"#,
-                subscribes
-            );
-            ws.write_message(Message::Text((&subscribes).into()))?;
+                .map(|a| format!("{}@{}", a.to_lowercase(), sub_type))
+                .collect::<Vec<_>>();
+            tracing::debug!(symbols = ?symbols, sub_type = sub_type, "websocket subscribe");
+            self.send_ws_request(ws, "SUBSCRIBE", serde_json::json!(subscribes))?;
             return Ok(());
         }
 
+        // Stream type for `subscribe_websocket`: 5, 10 or 20 levels; `update_ms` 0 keeps the
+        // default speed (1000ms spot, 250ms futures).
+        #[cfg(feature = "websocket")]
+        pub fn partial_depth_type(&self, levels: u32, update_ms: u32) -> String {
+            if ![5, 10, 20].contains(&levels) {
+                Self::panic_not_define("Depth levels", &levels.to_string(), ());
+            }
+            let speeds: &[u32] = match self.account_type {
+                "spot" => &[100, 1000],
+                _ => &[100, 250, 500],
+            };
+            if update_ms == 0 {
+                format!("depth{}", levels)
+            } else if speeds.contains(&update_ms) {
+                format!("depth{}@{}ms", levels, update_ms)
+            } else {
+                Self::panic_not_define(
+                    "Depth update speed",
+                    &format!("{update_ms}ms"),
+                    String::new(),
+                )
+            }
+        }
+
+        #[cfg(feature = "websocket")]
+        pub async fn subscribe_partial_depth(
+            &self,
+            ws: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+            symbols: &Vec<&str>,
+            levels: u32,
+            update_ms: u32,
+        ) -> Result<(), Box<dyn Error>> {
+            let sub_type = self.partial_depth_type(levels, update_ms);
+            self.subscribe_websocket(ws, symbols, &sub_type).await
+        }
+
+        #[cfg(feature = "websocket")]
         pub async fn unsubscribe_websocket(
             &self,
             ws: &mut WebSocket<MaybeTlsStream<TcpStream>>,
@@ -179,75 +953,451 @@ pub mod binance_api {
         ) -> Result<(), Box<dyn Error>> {
             let subscribes = symbols
                 .iter()
-                .map(|a| format!(r#""{}@{}""#, a.to_lowercase(), sub_type))
-                .collect::<Vec<_>>()
-                .join(",");
-            let subscribes = format!(
-                r#"{{"method": "UNSUBSCRIBE", "params": [{}], "id": 312}}"#,
-                subscribes
-            );
-            ws.write_message(Message::Text((&subscribes).into()))?;
+                .map(|a| format!("{}@{}", a.to_lowercase(), sub_type))
+                .collect::<Vec<_>>();
+            tracing::debug!(symbols = ?symbols, sub_type = sub_type, "websocket unsubscribe");
+            self.send_ws_request(ws, "UNSUBSCRIBE", serde_json::json!(subscribes))?;
             return Ok(());
         }
 
+        // Binance knows the `combined` property: true wraps events as `{"stream", "data"}`.
+        #[cfg(feature = "websocket")]
+        pub async fn set_websocket_property(
+            &self,
+            ws: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+            property: &str,
+            value: SendRequestRe,
+        ) -> Result<(), Box<dyn Error>> {
+            self.send_ws_request(ws, "SET_PROPERTY", serde_json::json!([property, value]))?;
+            Ok(())
+        }
+
+        #[cfg(feature = "websocket")]
+        pub async fn get_websocket_property(
+            &self,
+            ws: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+            property: &str,
+        ) -> Result<SendRequestRe, Box<dyn Error>> {
+            self.send_ws_request(ws, "GET_PROPERTY", serde_json::json!([property]))
+        }
+
+        #[cfg(feature = "websocket")]
         pub fn websocket_read_once(&self, ws: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> String {
+            if let Some(message) = self.ws_requests.backlog.lock().unwrap().pop_front() {
+                return message;
+            }
             match ws.read_message().unwrap() {
-                tungstenite::Message::Text(message) => message,
+                tungstenite::Message::Text(message) => {
+                    #[cfg(feature = "metrics")]
+                    metrics::record_ws_message(&message);
+                    message
+                }
                 _ => "{\"Error\":\"Can't getting text from websocket.\"}".to_string(),
             }
         }
 
-        fn str2datetime(&self, utc_str: &str) -> DateTime<Utc> {
-            NaiveDateTime::parse_from_str(utc_str, "%Y-%m-%d %H:%M:%S")
-                .unwrap()
-                .and_utc()
-        }
-
-        pub async fn history_klines(
+        // Repeats the request until a page comes back empty or `next_page`, which moves the
+        // cursor in `param_map` for the following request, returns false.
+        pub(crate) async fn paginate<T, F>(
             &self,
-            symbol: &str,
-            interval: &str,
-            start_time_utc: &str,
-            end_time_utc: &str,
-        ) -> Result<Vec<SendRequestRe>, Box<dyn Error>> {
-            let url = self.generate_exchange_url(("/api/v3/klines", "/fapi/v1/klines"));
-            let mut param_map = std::collections::HashMap::new();
-            param_map.insert("symbol".to_string(), symbol.to_string());
-            param_map.insert("interval".to_string(), interval.to_string());
-            param_map.insert(
-                "startTime".to_string(),
-                (self.str2datetime(start_time_utc).timestamp() * 1000).to_string(),
-            );
-            if !end_time_utc.is_empty() {
-                param_map.insert(
-                    "endTime".to_string(),
-                    (self.str2datetime(end_time_utc).timestamp() * 1000).to_string(),
-                );
-            }
-            let mut kline_data = vec![];
+            url: &str,
+            param_map: &mut HashMap<String, String>,
+            signature: bool,
+            mut next_page: F,
+        ) -> Result<Vec<T>, Box<dyn Error>>
+        where
+            T: DeserializeOwned,
+            F: FnMut(&[T], &mut HashMap<String, String>) -> bool,
+        {
+            let mut records = vec![];
             loop {
-                let parsed = self
-                    .send_request(url.as_str(), "GET", &mut param_map, false)
-                    .await?;
-                let kdatai = parsed.as_array().unwrap().to_owned();
-                if kdatai.is_empty() {
+                let parsed = self.send_request(url, "GET", param_map, signature).await?;
+                let page: Vec<T> = Self::parse_response(parsed)?;
+                if page.is_empty() {
+                    break;
+                }
+                let more = next_page(&page, param_map);
+                records.extend(page);
+                if !more {
                     break;
-                } else {
-                    param_map.insert("startTime".to_string(),(kdatai[kdatai.len() - 1][0].as_i64().unwrap() + 1).to_string());
-                    kline_data.extend(kdatai);
                 }
             }
-            Ok(kline_data[..kline_data.len() - 1].to_vec())
-            // Ok(kline_data)
+            Ok(records)
         }
 
-        pub async fn get_exchange_info(&self) -> Result<SendRequestRe, Box<dyn Error>> {
-            let url = self.generate_exchange_url(("/api/v3/exchangeInfo", "/fapi/v1/exchangeInfo"));
-            let mut param_map = std::collections::HashMap::new();
-            let parsed = self
+        // For the `/sapi` pages of `{"rows": [...], "total": n}` numbered by `current` from 1:
+        // asks for 100 rows a page until a short page or `total` rows.
+        #[cfg(feature = "wallet")]
+        pub(crate) async fn paginate_rows<T: DeserializeOwned>(
+            &self,
+            url: &str,
+            param_map: &mut HashMap<String, String>,
+            signature: bool,
+        ) -> Result<Vec<T>, Box<dyn Error>> {
+            param_map.insert("size".to_string(), "100".to_string());
+            let mut records = vec![];
+            for current in 1.. {
+                param_map.insert("current".to_string(), current.to_string());
+                let parsed = self.send_request(url, "GET", param_map, signature).await?;
+                let parsed: SendRequestRe = Self::parse_response(parsed)?;
+                let rows: Vec<T> = match parsed.get("rows") {
+                    Some(rows) => serde_json::from_value(rows.clone())?,
+                    None => vec![],
+                };
+                let total = parsed["total"].as_u64().unwrap_or_default() as usize;
+                let done = rows.len() < 100;
+                records.extend(rows);
+                if done || records.len() >= total {
+                    break;
+                }
+            }
+            Ok(records)
+        }
+
+        // Unset times become 0 and i64::MAX.
+        fn time_range_ms(
+            &self,
+            start_time_utc: TimeInput,
+            end_time_utc: TimeInput,
+        ) -> Result<(i64, i64), Box<dyn Error>> {
+            let start_time = start_time_utc.millis()?.unwrap_or(0);
+            let end_time = end_time_utc.millis()?.unwrap_or(i64::MAX);
+            Ok((start_time, end_time))
+        }
+
+        async fn klines_loop(
+            &self,
+            url: &str,
+            param_map: &mut HashMap<String, String>,
+            start_time_utc: TimeInput,
+            end_time_utc: TimeInput,
+            query: &KlineQuery,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            // No start time pages from the first kline listed. Times are in the client's time unit
+            // from here on, like the open times of the klines returned.
+            let time_unit = self.time_unit;
+            let start_time = time_unit.from_millis(start_time_utc.millis()?.unwrap_or(0));
+            let end_time = end_time_utc
+                .millis()?
+                .map(|a| time_unit.from_millis(a))
+                .map(|a| if query.inclusive_end { a } else { a - 1 });
+            let interval = param_map
+                .get("interval")
+                .and_then(|a| interval_ms(a))
+                .map(|a| time_unit.from_millis(a));
+            match interval {
+                Some(interval) if query.concurrency > 1 => {
+                    self.klines_chunked(url, param_map, start_time, end_time, interval, query)
+                        .await
+                }
+                _ => {
+                    self.klines_range(url, param_map, start_time, end_time, query)
+                        .await
+                }
+            }
+        }
+
+        // Splits the range into chunks of whole pages and fetches `query.concurrency` of them at a
+        // time; the rate limiter still paces every request.
+        async fn klines_chunked(
+            &self,
+            url: &str,
+            param_map: &HashMap<String, String>,
+            start_time: i64,
+            end_time: Option<i64>,
+            interval: i64,
+            query: &KlineQuery,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            let end_time = end_time
+                .unwrap_or_else(|| self.time_unit.from_millis(Utc::now().timestamp_millis()));
+            let limit = if query.limit > 0 { query.limit } else { 500 } as i64;
+            let pages = ((end_time - start_time) / (interval * limit) + 1).max(1);
+            // Several chunks per task, so months before a listing (answered by one empty page)
+            // don't leave tasks idle.
+            let chunks = (query.concurrency as i64 * 4).min(pages);
+            let chunk_len = (pages + chunks - 1) / chunks * interval * limit;
+            let ranges: Vec<(i64, i64)> = (0..chunks)
+                .map(|i| start_time + i * chunk_len)
+                .filter(|a| *a <= end_time)
+                .map(|a| (a, (a + chunk_len - 1).min(end_time)))
+                .collect();
+            tracing::debug!(chunks = ranges.len(), "fetching klines in chunks");
+            // Errors are buffered as text, which unlike `Box<dyn Error>` is Send.
+            let mut requests = stream::iter(ranges.into_iter().map(|(start, end)| {
+                let mut param_map = param_map.clone();
+                async move {
+                    self.klines_range(url, &mut param_map, start, Some(end), query)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+            }))
+            .buffered(query.concurrency);
+            let mut kline_data: Vec<Kline> = vec![];
+            while let Some(chunk) = requests.next().await {
+                for kline in chunk? {
+                    if kline_data
+                        .last()
+                        .is_none_or(|a| a.open_time < kline.open_time)
+                    {
+                        kline_data.push(kline);
+                    }
+                }
+            }
+            Ok(kline_data)
+        }
+
+        async fn klines_range(
+            &self,
+            url: &str,
+            param_map: &mut HashMap<String, String>,
+            start_time: i64,
+            end_time: Option<i64>,
+            query: &KlineQuery,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            param_map.insert("startTime".to_string(), start_time.to_string());
+            if let Some(end_time) = end_time {
+                param_map.insert("endTime".to_string(), end_time.to_string());
+            }
+            if query.limit > 0 {
+                param_map.insert("limit".to_string(), query.limit.to_string());
+            }
+            let limit = query.limit as usize;
+            let mut kline_data = self
+                .paginate(url, param_map, false, |page: &[Kline], param_map| {
+                    let next_start = page[page.len() - 1].open_time + 1;
+                    param_map.insert("startTime".to_string(), next_start.to_string());
+                    (limit == 0 || page.len() >= limit) && end_time.is_none_or(|a| next_start <= a)
+                })
+                .await?;
+            if !query.include_open {
+                let now = self.time_unit.from_millis(Utc::now().timestamp_millis());
+                kline_data.retain(|a| a.close_time < now);
+            }
+            Ok(kline_data)
+        }
+
+        pub async fn history_klines(
+            &self,
+            symbol: &str,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            self.history_klines_with(
+                symbol,
+                interval,
+                start_time_utc,
+                end_time_utc,
+                &KlineQuery::default(),
+            )
+            .await
+        }
+
+        pub async fn history_klines_with(
+            &self,
+            symbol: &str,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+            query: &KlineQuery,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/klines", "/fapi/v1/klines"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("interval".to_string(), interval.to_string());
+            self.klines_loop(
+                url.as_str(),
+                &mut param_map,
+                start_time_utc.into(),
+                end_time_utc.into(),
+                query,
+            )
+            .await
+        }
+
+        pub async fn history_klines_many(
+            &self,
+            symbols: &Vec<&str>,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+            concurrency: usize,
+        ) -> Result<HashMap<String, Vec<Kline>>, Box<dyn Error>> {
+            let start_time_utc: &TimeInput = &start_time_utc.into();
+            let end_time_utc: &TimeInput = &end_time_utc.into();
+            let mut requests = stream::iter(symbols.iter().map(|symbol| async move {
+                let klines = self
+                    .history_klines(symbol, interval, start_time_utc, end_time_utc)
+                    .await
+                    .map_err(|e| e.to_string());
+                (symbol.to_string(), klines)
+            }))
+            .buffer_unordered(concurrency.max(1));
+            let mut klines_map = HashMap::new();
+            while let Some((symbol, klines)) = requests.next().await {
+                klines_map.insert(symbol, klines?);
+            }
+            Ok(klines_map)
+        }
+
+        #[cfg(feature = "spot")]
+        pub async fn get_ui_klines(
+            &self,
+            symbol: &str,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+            time_zone: &str,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "spot",
+                "only `spot` can get UI klines."
+            );
+            let url = self.generate_exchange_url(("/api/v3/uiKlines", ""));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("interval".to_string(), interval.to_string());
+            if !time_zone.is_empty() {
+                param_map.insert("timeZone".to_string(), time_zone.to_string());
+            }
+            self.klines_loop(
+                url.as_str(),
+                &mut param_map,
+                start_time_utc.into(),
+                end_time_utc.into(),
+                &KlineQuery::default(),
+            )
+            .await
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_mark_price_klines(
+            &self,
+            symbol: &str,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get mark price klines."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/markPriceKlines"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("interval".to_string(), interval.to_string());
+            self.klines_loop(
+                url.as_str(),
+                &mut param_map,
+                start_time_utc.into(),
+                end_time_utc.into(),
+                &KlineQuery::default(),
+            )
+            .await
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_index_price_klines(
+            &self,
+            pair: &str,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get index price klines."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/indexPriceKlines"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("pair".to_string(), pair.to_string());
+            param_map.insert("interval".to_string(), interval.to_string());
+            self.klines_loop(
+                url.as_str(),
+                &mut param_map,
+                start_time_utc.into(),
+                end_time_utc.into(),
+                &KlineQuery::default(),
+            )
+            .await
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_continuous_klines(
+            &self,
+            pair: &str,
+            contract_type: ContractType,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get continuous klines."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/continuousKlines"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("pair".to_string(), pair.to_string());
+            param_map.insert(
+                "contractType".to_string(),
+                contract_type.as_str().to_string(),
+            );
+            param_map.insert("interval".to_string(), interval.to_string());
+            self.klines_loop(
+                url.as_str(),
+                &mut param_map,
+                start_time_utc.into(),
+                end_time_utc.into(),
+                &KlineQuery::default(),
+            )
+            .await
+        }
+
+        pub async fn get_exchange_info(&self) -> Result<SendRequestRe, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/exchangeInfo", "/fapi/v1/exchangeInfo"));
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
                 .send_request(url.as_str(), "GET", &mut param_map, false)
                 .await?;
-            Ok(parsed)
+            Self::parse_response(parsed)
+        }
+
+        pub async fn refresh_symbols(&self) -> Result<(), Box<dyn Error>> {
+            let exchange_info = self.get_exchange_info().await?;
+            self.symbol_registry.load(&exchange_info)
+        }
+
+        pub async fn get_symbol_info(&self, symbol: &str) -> Result<SymbolInfo, Box<dyn Error>> {
+            if !self.symbol_registry.is_fresh() {
+                self.refresh_symbols().await?;
+            }
+            match self.symbol_registry.get(symbol) {
+                Some(info) => Ok(info),
+                None => Err(format!("Symbol `{symbol}` is not in exchangeInfo.").into()),
+            }
+        }
+
+        pub async fn get_symbol_filters(
+            &self,
+            symbol: &str,
+        ) -> Result<SymbolFilters, Box<dyn Error>> {
+            Ok(self.get_symbol_info(symbol).await?.filters)
+        }
+
+        pub async fn round_price(
+            &self,
+            symbol: &str,
+            price: Decimal,
+        ) -> Result<Decimal, Box<dyn Error>> {
+            Ok(self.get_symbol_filters(symbol).await?.round_price(price))
+        }
+
+        pub async fn round_qty(
+            &self,
+            symbol: &str,
+            qty: Decimal,
+        ) -> Result<Decimal, Box<dyn Error>> {
+            Ok(self.get_symbol_filters(symbol).await?.round_qty(qty))
         }
 
         pub async fn get_price(&self, symbol: &str) -> Result<SendRequestRe, Box<dyn Error>> {
@@ -263,6 +1413,39 @@ pub mod binance_api {
             Ok(parsed)
         }
 
+        #[cfg(feature = "spot")]
+        pub async fn get_avg_price(&self, symbol: &str) -> Result<AvgPrice, Box<dyn Error>> {
+            assert!(
+                self.account_type == "spot",
+                "only `spot` can get average price."
+            );
+            let url = self.generate_exchange_url(("/api/v3/avgPrice", ""));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_mark_price(&self, symbol: &str) -> Result<SendRequestRe, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get mark price."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/premiumIndex"));
+            let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
         pub async fn get_ticker(&self, symbol: &str) -> Result<SendRequestRe, Box<dyn Error>> {
             let url = self.generate_exchange_url(("/api/v3/ticker/24hr", "/fapi/v1/ticker/24hr"));
             let mut param_map = std::collections::HashMap::new();
@@ -276,6 +1459,162 @@ pub mod binance_api {
             Ok(parsed)
         }
 
+        pub async fn get_depth(
+            &self,
+            symbol: &str,
+            limit: u32,
+        ) -> Result<DepthSnapshot, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/depth", "/fapi/v1/depth"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("limit".to_string(), limit.to_string());
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        pub async fn get_recent_trades(
+            &self,
+            symbol: &str,
+            limit: usize,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/trades", "/fapi/v1/trades"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("limit".to_string(), limit.min(1000).to_string());
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            let recent: Vec<Trade> = Self::parse_response(parsed)?;
+            if limit <= 1000 || recent.is_empty() {
+                return Ok(recent);
+            }
+            let missing = limit - recent.len();
+            let from_id = (recent[0].id - missing as i64).max(0);
+            let mut trades = self
+                .get_historical_trades(symbol, from_id, (recent[0].id - from_id) as usize)
+                .await?;
+            trades.extend(recent);
+            Ok(trades)
+        }
+
+        pub async fn get_historical_trades(
+            &self,
+            symbol: &str,
+            from_id: i64,
+            limit: usize,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            let url = self
+                .generate_exchange_url(("/api/v3/historicalTrades", "/fapi/v1/historicalTrades"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("fromId".to_string(), from_id.to_string());
+            if limit == 0 {
+                return Ok(vec![]);
+            }
+            param_map.insert("limit".to_string(), limit.min(1000).to_string());
+            let mut fetched = 0;
+            self.paginate(
+                url.as_str(),
+                &mut param_map,
+                false,
+                |page: &[Trade], param_map| {
+                    fetched += page.len();
+                    param_map.insert(
+                        "fromId".to_string(),
+                        (page[page.len() - 1].id + 1).to_string(),
+                    );
+                    param_map.insert(
+                        "limit".to_string(),
+                        (limit.saturating_sub(fetched)).min(1000).to_string(),
+                    );
+                    fetched < limit
+                },
+            )
+            .await
+        }
+
+        pub async fn get_agg_trades(
+            &self,
+            symbol: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<AggTrade>, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/aggTrades", "/fapi/v1/aggTrades"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("limit".to_string(), "1000".to_string());
+            let (start_time, end_time) =
+                self.time_range_ms(start_time_utc.into(), end_time_utc.into())?;
+            param_map.insert("startTime".to_string(), start_time.to_string());
+            let end_time = self.time_unit.from_millis(end_time);
+            let mut trade_data = self
+                .paginate(
+                    url.as_str(),
+                    &mut param_map,
+                    false,
+                    |page: &[AggTrade], param_map| {
+                        let last = &page[page.len() - 1];
+                        param_map.remove("startTime");
+                        param_map.insert("fromId".to_string(), (last.agg_trade_id + 1).to_string());
+                        last.time <= end_time
+                    },
+                )
+                .await?;
+            trade_data.retain(|a| a.time <= end_time);
+            Ok(trade_data)
+        }
+
+        pub async fn get_book_ticker(
+            &self,
+            symbol: &str,
+        ) -> Result<Vec<BookTicker>, Box<dyn Error>> {
+            let url = self
+                .generate_exchange_url(("/api/v3/ticker/bookTicker", "/fapi/v1/ticker/bookTicker"));
+            let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response_list(parsed)
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_index_info(&self, symbol: &str) -> Result<Vec<IndexInfo>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get index info."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/indexInfo"));
+            let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response_list(parsed)
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_asset_index(&self) -> Result<Vec<AssetIndex>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get asset index."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/assetIndex"));
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, false)
+                .await?;
+            Self::parse_response_list(parsed)
+        }
+
         pub async fn new_order(
             &self,
             symbol: &str,
@@ -284,21 +1623,229 @@ pub mod binance_api {
             quantity: &str,
             price: &str,
             time_inforce: &str,
+            good_till_date: i64,
+        ) -> Result<SendRequestRe, Box<dyn Error>> {
+            let mut order = NewOrder::new(symbol, side, trade_type).quantity(quantity.parse()?);
+            if trade_type == "LIMIT" {
+                order = order.price(price.parse()?).time_in_force(time_inforce);
+            }
+            if time_inforce == "GTD" {
+                order = order
+                    .time_in_force(time_inforce)
+                    .good_till_date(good_till_date);
+            }
+            self.place_order(&order).await
+        }
+
+        pub async fn place_order(&self, order: &NewOrder) -> Result<SendRequestRe, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/order", "/fapi/v1/order"));
+            let order = self.prepare_order(order).await?;
+            if self.is_paper_trading() {
+                return self.paper_place_order(&order).await;
+            }
+            if let Some(max_retries) = self.order_retries {
+                return self.place_order_idempotent(&url, order, max_retries).await;
+            }
+            let mut param_map = order.to_param_map();
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        // See `BinanceAPIBuilder::idempotent_orders`.
+        async fn place_order_idempotent(
+            &self,
+            url: &str,
+            mut order: NewOrder,
+            max_retries: u32,
+        ) -> Result<SendRequestRe, Box<dyn Error>> {
+            if order.new_client_order_id.is_empty() {
+                let id = self.order_ids.fetch_add(1, Ordering::SeqCst) + 1;
+                order.new_client_order_id = format!("idm_{}_{}", Utc::now().timestamp_millis(), id);
+            }
+            let mut attempt = 0;
+            loop {
+                let mut param_map = order.to_param_map();
+                // A timeout (408, -1007 or no response) or a 5xx says nothing about whether the
+                // order reached the matching engine.
+                let error = match self
+                    .send_request_raw(url, "POST", &mut param_map, true)
+                    .await
+                {
+                    Ok(res) if res.status < 500 && res.status != 408 => {
+                        let parsed: SendRequestRe = serde_json::from_str(&res.body)?;
+                        if parsed["code"].as_i64() != Some(-1007) {
+                            return Self::parse_response(parsed);
+                        }
+                        res.body
+                    }
+                    Ok(res) => format!("status {}: {}", res.status, res.body),
+                    Err(e) => e.to_string(),
+                };
+                attempt += 1;
+                tracing::warn!(
+                    client_order_id = %order.new_client_order_id,
+                    attempt = attempt,
+                    error = %error,
+                    "order status unknown, looking it up"
+                );
+                // An order still on its way to the matching engine reads as unknown (-2013) for a
+                // while, so it only counts as never placed once every lookup of the window says so.
+                for lookup in 1..=NOT_FOUND_LOOKUPS {
+                    self.transport
+                        .0
+                        .sleep(std::time::Duration::from_millis(500 * lookup))
+                        .await;
+                    let found = self
+                        .get_order(&order.symbol, "", &order.new_client_order_id)
+                        .await;
+                    let not_found = matches!(
+                        &found,
+                        Err(e) if e.downcast_ref::<BinanceError>().map(|a| a.code) == Some(-2013)
+                    );
+                    if !not_found {
+                        return found;
+                    }
+                }
+                if attempt > max_retries {
+                    return Err(format!(
+                        "Order `{}` not placed after {} attempts: {}",
+                        order.new_client_order_id, attempt, error
+                    )
+                    .into());
+                }
+            }
+        }
+
+        // Signs and checks the order like `place_order` but nothing reaches the matching engine;
+        // with `compute_commission_rates` the response carries the commission the order would pay.
+        #[cfg(feature = "spot")]
+        pub async fn new_order_test(
+            &self,
+            order: &NewOrder,
+            compute_commission_rates: bool,
+        ) -> Result<SendRequestRe, Box<dyn Error>> {
+            if self.account_type != "spot" {
+                return Err("Only `spot` can use `new_order_test`.".into());
+            }
+            self.ensure_not_paper("new_order_test")?;
+            let url = self.generate_exchange_url(("/api/v3/order/test", ""));
+            let order = self.prepare_order(order).await?;
+            let mut param_map = order.to_param_map();
+            if compute_commission_rates {
+                param_map.insert("computeCommissionRates".to_string(), "true".to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        async fn prepare_order(&self, order: &NewOrder) -> Result<NewOrder, Box<dyn Error>> {
+            if order.time_in_force == "GTD" {
+                if self.account_type != "swap" {
+                    return Err("Only `swap` can use GTD.".into());
+                }
+                let earliest = Utc::now() + Duration::minutes(10);
+                if order.good_till_date <= earliest.timestamp_millis() {
+                    return Err(format!(
+                        "goodTillDate `{}` must be more than 10 minutes in the future.",
+                        order.good_till_date
+                    )
+                    .into());
+                }
+            }
+            let mut order = order.clone();
+            if order.auto_round {
+                let filters = self.get_symbol_filters(&order.symbol).await?;
+                order.quantity = order.quantity.map(|a| filters.round_qty(a));
+                order.price = order.price.map(|a| filters.round_price(a));
+                order.stop_price = order.stop_price.map(|a| filters.round_price(a));
+            }
+            if order.validate {
+                self.validate_order(&order).await?;
+            }
+            if !order.skip_risk {
+                self.check_risk(&order).await?;
+            }
+            Ok(order)
+        }
+
+        pub async fn validate_order(&self, order: &NewOrder) -> Result<(), Box<dyn Error>> {
+            let filters = self.get_symbol_filters(&order.symbol).await?;
+            let reference_price: Decimal = match self.account_type {
+                #[cfg(feature = "spot")]
+                "spot" => self.get_avg_price(&order.symbol).await?.price,
+                #[cfg(feature = "usdm-futures")]
+                "swap" => {
+                    let mark_price = self.get_mark_price(&order.symbol).await?;
+                    mark_price["markPrice"].as_str().unwrap_or("0").parse()?
+                }
+                _ => Self::panic_not_define("Account type", self.account_type, Decimal::ZERO),
+            };
+            let open_orders = self.get_open_orders(&order.symbol).await?;
+            let open_orders = open_orders.as_array().map(|a| a.len()).unwrap_or_default();
+            filters.check_order(order, reference_price, open_orders)?;
+            Ok(())
+        }
+
+        pub async fn get_open_orders(&self, symbol: &str) -> Result<SendRequestRe, Box<dyn Error>> {
+            if self.is_paper_trading() {
+                return Ok(self.paper_open_orders(symbol));
+            }
+            let url = self.generate_exchange_url(("/api/v3/openOrders", "/fapi/v1/openOrders"));
+            let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        // Looks an order up by `order_id` or, when that is empty, by `orig_client_order_id`.
+        pub async fn get_order(
+            &self,
+            symbol: &str,
+            order_id: &str,
+            orig_client_order_id: &str,
         ) -> Result<SendRequestRe, Box<dyn Error>> {
+            if self.is_paper_trading() {
+                let orders = self.paper_open_orders(symbol);
+                return orders
+                    .as_array()
+                    .and_then(|a| {
+                        a.iter().find(|a| {
+                            a["orderId"].to_string() == order_id
+                                || a["clientOrderId"].as_str() == Some(orig_client_order_id)
+                        })
+                    })
+                    .cloned()
+                    .ok_or_else(|| {
+                        Box::new(BinanceError {
+                            code: -2013,
+                            msg: "Order does not exist.".to_string(),
+                        }) as Box<dyn Error>
+                    });
+            }
             let url = self.generate_exchange_url(("/api/v3/order", "/fapi/v1/order"));
             let mut param_map = std::collections::HashMap::new();
             param_map.insert("symbol".to_string(), symbol.to_string());
-            param_map.insert("side".to_string(), side.to_string());
-            param_map.insert("type".to_string(), trade_type.to_string());
-            param_map.insert("quantity".to_string(), quantity.to_string());
-            if trade_type == "LIMIT" {
-                param_map.insert("price".to_string(), price.to_string());
-                param_map.insert("timeInForce".to_string(), time_inforce.to_string());
+            if order_id.is_empty() {
+                param_map.insert(
+                    "origClientOrderId".to_string(),
+                    orig_client_order_id.to_string(),
+                );
+            } else {
+                param_map.insert("orderId".to_string(), order_id.to_string());
             }
             let parsed = self
-                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .send_request(url.as_str(), "GET", &mut param_map, true)
                 .await?;
-            Ok(parsed)
+            Self::parse_response(parsed)
         }
 
         pub async fn cancel_order(
@@ -307,6 +1854,9 @@ pub mod binance_api {
             order_id: &str,
             all: bool,
         ) -> Result<SendRequestRe, Box<dyn Error>> {
+            if self.is_paper_trading() {
+                return self.paper_cancel_order(symbol, order_id, all);
+            }
             let url = if all {
                 self.generate_exchange_url(("/api/v3/openOrders", "/fapi/v1/allOpenOrders"))
             } else {
@@ -320,10 +1870,183 @@ pub mod binance_api {
             let parsed = self
                 .send_request(url.as_str(), "DELETE", &mut param_map, true)
                 .await?;
-            Ok(parsed)
+            Self::parse_response(parsed)
+        }
+
+        // Binance cancels every open order on `symbol` unless this is called again within
+        // `countdown_ms`; 0 switches the countdown off.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn auto_cancel_all(
+            &self,
+            symbol: &str,
+            countdown_ms: u64,
+        ) -> Result<SendRequestRe, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can use `auto_cancel_all`."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/countdownCancelAll"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("countdownTime".to_string(), countdown_ms.to_string());
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        // Re-arms the countdown every `refresh` until the future is dropped or the request fails,
+        // so the orders are cancelled once this process stops refreshing.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn keep_auto_cancel_all(
+            &self,
+            symbol: &str,
+            countdown_ms: u64,
+            refresh: std::time::Duration,
+        ) -> Result<(), Box<dyn Error>> {
+            if refresh.as_millis() as u64 >= countdown_ms {
+                return Err(format!(
+                    "refresh {:?} must be shorter than the countdown {}ms.",
+                    refresh, countdown_ms
+                )
+                .into());
+            }
+            loop {
+                self.auto_cancel_all(symbol, countdown_ms).await?;
+                tracing::debug!(
+                    symbol = symbol,
+                    countdown_ms = countdown_ms,
+                    "countdown refreshed"
+                );
+                self.transport.0.sleep(refresh).await;
+            }
+        }
+
+        pub async fn get_all_orders(
+            &self,
+            symbol: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<SendRequestRe>, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/allOrders", "/fapi/v1/allOrders"));
+            let (start_time, end_time) =
+                self.time_range_ms(start_time_utc.into(), end_time_utc.into())?;
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("limit".to_string(), "1000".to_string());
+            if start_time > 0 {
+                param_map.insert("startTime".to_string(), start_time.to_string());
+            } else {
+                param_map.insert("orderId".to_string(), "0".to_string());
+            }
+            let end_time = self.time_unit.from_millis(end_time);
+            let time = |a: &SendRequestRe| a["time"].as_i64().unwrap_or_default();
+            let mut orders = self
+                .paginate(
+                    url.as_str(),
+                    &mut param_map,
+                    true,
+                    |page: &[SendRequestRe], param_map| {
+                        let last = &page[page.len() - 1];
+                        param_map.remove("startTime");
+                        param_map.insert(
+                            "orderId".to_string(),
+                            (last["orderId"].as_i64().unwrap_or_default() + 1).to_string(),
+                        );
+                        page.len() == 1000 && time(last) <= end_time
+                    },
+                )
+                .await?;
+            orders.retain(|a| time(a) <= end_time);
+            Ok(orders)
+        }
+
+        pub async fn get_my_trades(
+            &self,
+            symbol: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<SendRequestRe>, Box<dyn Error>> {
+            let url = self.generate_exchange_url(("/api/v3/myTrades", "/fapi/v1/userTrades"));
+            let (start_time, end_time) =
+                self.time_range_ms(start_time_utc.into(), end_time_utc.into())?;
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("limit".to_string(), "1000".to_string());
+            if start_time > 0 {
+                param_map.insert("startTime".to_string(), start_time.to_string());
+            } else {
+                param_map.insert("fromId".to_string(), "0".to_string());
+            }
+            let end_time = self.time_unit.from_millis(end_time);
+            let time = |a: &SendRequestRe| a["time"].as_i64().unwrap_or_default();
+            let mut trades = self
+                .paginate(
+                    url.as_str(),
+                    &mut param_map,
+                    true,
+                    |page: &[SendRequestRe], param_map| {
+                        let last = &page[page.len() - 1];
+                        // fromId can't be combined with startTime.
+                        param_map.remove("startTime");
+                        param_map.insert(
+                            "fromId".to_string(),
+                            (last["id"].as_i64().unwrap_or_default() + 1).to_string(),
+                        );
+                        page.len() == 1000 && time(last) <= end_time
+                    },
+                )
+                .await?;
+            trades.retain(|a| time(a) <= end_time);
+            Ok(trades)
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_income_history(
+            &self,
+            symbol: &str,
+            income_type: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<SendRequestRe>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get income history."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/income"));
+            let (start_time, end_time) =
+                self.time_range_ms(start_time_utc.into(), end_time_utc.into())?;
+            let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
+            if income_type.is_empty() {
+            } else {
+                param_map.insert("incomeType".to_string(), income_type.to_string());
+            }
+            param_map.insert("limit".to_string(), "1000".to_string());
+            param_map.insert("startTime".to_string(), start_time.to_string());
+            if end_time < i64::MAX {
+                param_map.insert("endTime".to_string(), end_time.to_string());
+            }
+            self.paginate(
+                url.as_str(),
+                &mut param_map,
+                true,
+                |page: &[SendRequestRe], param_map| {
+                    let last = page[page.len() - 1]["time"].as_i64().unwrap_or_default();
+                    param_map.insert("startTime".to_string(), (last + 1).to_string());
+                    page.len() == 1000
+                },
+            )
+            .await
         }
 
         pub async fn pull_account(&self) -> Result<SendRequestRe, Box<dyn Error>> {
+            if let Some(account) = self.paper_account() {
+                return Ok(self.paper_pull_account(&account));
+            }
             let url = self.generate_exchange_url(("/api/v3/account", "/fapi/v2/account"));
             let mut param_map = std::collections::HashMap::new();
             let parsed = self
@@ -332,24 +2055,443 @@ pub mod binance_api {
             Ok(parsed)
         }
 
-        pub async fn get_position(&self) -> Result<SendRequestRe, Box<dyn Error>> {
+        #[cfg(feature = "usdm-futures")]
+        // Only symbols with a position or open orders are listed; an empty `symbol` lists them all.
+        pub async fn get_position(&self, symbol: &str) -> Result<Vec<Position>, Box<dyn Error>> {
             assert!(self.account_type == "swap", "only `swap` can get position.");
-            let url = self.generate_exchange_url(("", "/fapi/v2/positionRisk"));
+            if let Some(account) = self.paper_account() {
+                return self.paper_positions(&account, symbol).await;
+            }
+            let url = self.generate_exchange_url(("", "/fapi/v3/positionRisk"));
             let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
             let parsed = self
                 .send_request(url.as_str(), "GET", &mut param_map, true)
                 .await?;
-            Ok(parsed)
+            Self::parse_response_list(parsed)
+        }
+
+        // Closes `percent` of every position named in a margin call with a market order.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn reduce_positions(
+            &self,
+            margin_call: &MarginCallEvent,
+            percent: Decimal,
+        ) -> Result<Vec<SendRequestRe>, Box<dyn Error>> {
+            let mut reports = vec![];
+            for position in &margin_call.positions {
+                let qty = position.position_amt.abs() * percent / Decimal::from(100);
+                if qty.is_zero() {
+                    continue;
+                }
+                let order = NewOrder::close_position(
+                    &position.symbol,
+                    &position.position_side,
+                    position.position_amt,
+                    qty,
+                )
+                .auto_round(true);
+                tracing::warn!(symbol = %position.symbol, qty = %qty, "reducing position after margin call");
+                reports.push(self.place_order(&order).await?);
+            }
+            Ok(reports)
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_account_config(&self) -> Result<AccountConfig, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get account config."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/accountConfig"));
+            let mut param_map = std::collections::HashMap::new();
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
         }
 
-        pub async fn get_balance(&self) -> Result<SendRequestRe, Box<dyn Error>> {
-            assert!(self.account_type == "swap", "only `swap` can get balance.");
+        // Leverage and margin type per symbol; an empty `symbol` returns every symbol.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_symbol_config(
+            &self,
+            symbol: &str,
+        ) -> Result<Vec<SymbolConfig>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get symbol config."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/symbolConfig"));
+            let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response_list(parsed)
+        }
+
+        // The quantitative rules indicators of the account; an empty `symbol` returns every
+        // symbol. Check `near_limit` to slow down before Binance restricts the api key.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_api_trading_status(
+            &self,
+            symbol: &str,
+        ) -> Result<ApiTradingStatus, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get api trading status."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/apiTradingStatus"));
+            let mut param_map = std::collections::HashMap::new();
+            if symbol.is_empty() {
+            } else {
+                param_map.insert("symbol".to_string(), symbol.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        fn position_margin_type(add_or_reduce: &str) -> &'static str {
+            match add_or_reduce {
+                "add" => "1",
+                "reduce" => "2",
+                _ => Self::panic_not_define("Position margin change", add_or_reduce, ""),
+            }
+        }
+
+        // Adds margin to or removes it from an isolated position; `add_or_reduce` is `add` or
+        // `reduce`, `position_side` is "" in one-way mode, else LONG or SHORT.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn modify_position_margin(
+            &self,
+            symbol: &str,
+            amount: Decimal,
+            add_or_reduce: &str,
+            position_side: &str,
+        ) -> Result<SendRequestRe, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can modify position margin."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/positionMargin"));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("amount".to_string(), amount.normalize().to_string());
+            param_map.insert(
+                "type".to_string(),
+                Self::position_margin_type(add_or_reduce).to_string(),
+            );
+            if !position_side.is_empty() {
+                param_map.insert("positionSide".to_string(), position_side.to_string());
+            }
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        // Margin changes of `symbol`'s isolated positions, `add` or `reduce` only unless
+        // `add_or_reduce` is ""; the exchange returns the last 7 days without a start time.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn get_position_margin_history(
+            &self,
+            symbol: &str,
+            add_or_reduce: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<PositionMarginChange>, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get position margin history."
+            );
+            let url = self.generate_exchange_url(("", "/fapi/v1/positionMargin/history"));
+            let (start_time, end_time) =
+                self.time_range_ms(start_time_utc.into(), end_time_utc.into())?;
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            if !add_or_reduce.is_empty() {
+                param_map.insert(
+                    "type".to_string(),
+                    Self::position_margin_type(add_or_reduce).to_string(),
+                );
+            }
+            param_map.insert("limit".to_string(), "500".to_string());
+            if start_time > 0 {
+                param_map.insert("startTime".to_string(), start_time.to_string());
+            }
+            if end_time < i64::MAX {
+                param_map.insert("endTime".to_string(), end_time.to_string());
+            }
+            self.paginate(
+                url.as_str(),
+                &mut param_map,
+                true,
+                |page: &[PositionMarginChange], param_map| {
+                    let last = page.iter().map(|a| a.time).max().unwrap_or_default();
+                    param_map.insert("startTime".to_string(), (last + 1).to_string());
+                    page.len() == 500
+                },
+            )
+            .await
+        }
+
+        // Adds `amount` of margin to each isolated position of `symbol` whose mark price is within
+        // `min_distance_percent` of its liquidation price; run it on a timer to keep positions
+        // from being liquidated. Cross positions are left alone.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn top_up_isolated_margin(
+            &self,
+            symbol: &str,
+            min_distance_percent: Decimal,
+            amount: Decimal,
+        ) -> Result<Vec<SendRequestRe>, Box<dyn Error>> {
+            let mut reports = vec![];
+            let positions = self.get_position(symbol).await?;
+            for position in positions {
+                if position.position_amt.is_zero() || position.isolated_margin.is_zero() {
+                    continue;
+                }
+                match position.liquidation_distance_percent() {
+                    Some(distance) if distance < min_distance_percent => {}
+                    _ => continue,
+                }
+                let position_side = if position.position_side == "BOTH" {
+                    ""
+                } else {
+                    &position.position_side
+                };
+                tracing::warn!(symbol = %position.symbol, amount = %amount, "topping up isolated margin");
+                reports.push(
+                    self.modify_position_margin(&position.symbol, amount, "add", position_side)
+                        .await?,
+                );
+            }
+            Ok(reports)
+        }
+
+        // Every asset of the spot account or the futures wallet, typed the same for both.
+        pub async fn get_balance(&self) -> Result<Vec<AssetBalance>, Box<dyn Error>> {
+            if let Some(account) = self.paper_account() {
+                return Ok(account.asset_balances());
+            }
+            if self.account_type == "spot" {
+                let account = self.pull_account().await?;
+                let mut balances: Vec<AssetBalance> =
+                    serde_json::from_value(account["balances"].clone())?;
+                let update_time = account["updateTime"].as_i64().unwrap_or_default();
+                for balance in balances.iter_mut() {
+                    balance.balance = balance.free + balance.locked;
+                    balance.update_time = update_time;
+                }
+                return Ok(balances);
+            }
             let url = self.base_url.to_string() + "/fapi/v2/balance";
             let mut param_map = HashMap::new();
             let parsed = self
                 .send_request(url.as_str(), "GET", &mut param_map, true)
                 .await?;
-            Ok(parsed)
+            Self::parse_response_list(parsed)
+        }
+
+        // Places `order` through `/api/v3/orderList/oco`.
+        #[cfg(feature = "spot")]
+        pub async fn new_oco_order(
+            &self,
+            order: &NewOcoOrder,
+        ) -> Result<OcoOrderReport, Box<dyn Error>> {
+            assert!(
+                self.account_type == "spot",
+                "only `spot` can place OCO orders."
+            );
+            self.ensure_not_paper("new_oco_order")?;
+            let url = self.generate_exchange_url(("/api/v3/orderList/oco", ""));
+            let mut param_map = order.to_param_map();
+            let parsed = self
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "spot")]
+        pub async fn cancel_oco(
+            &self,
+            symbol: &str,
+            order_list_id: &str,
+        ) -> Result<OcoOrderReport, Box<dyn Error>> {
+            assert!(
+                self.account_type == "spot",
+                "only `spot` can cancel OCO orders."
+            );
+            self.ensure_not_paper("cancel_oco")?;
+            let url = self.generate_exchange_url(("/api/v3/orderList", ""));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("symbol".to_string(), symbol.to_string());
+            param_map.insert("orderListId".to_string(), order_list_id.to_string());
+            let parsed = self
+                .send_request(url.as_str(), "DELETE", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+
+        #[cfg(feature = "spot")]
+        pub async fn get_oco(&self, order_list_id: &str) -> Result<OcoOrderReport, Box<dyn Error>> {
+            assert!(
+                self.account_type == "spot",
+                "only `spot` can get OCO orders."
+            );
+            self.ensure_not_paper("get_oco")?;
+            let url = self.generate_exchange_url(("/api/v3/orderList", ""));
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("orderListId".to_string(), order_list_id.to_string());
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            Self::parse_response(parsed)
+        }
+    }
+
+    // The client defaults to spot, which needs the `spot` feature.
+    #[cfg(all(test, feature = "spot"))]
+    mod tests {
+        use super::*;
+        use crate::transport::transport::MockTransport;
+
+        #[tokio::test]
+        async fn signed_request_sends_the_string_it_signed() {
+            let transport = Arc::new(MockTransport::new().with_fixture("/api/v3/order", "{}"));
+            let bn_api = BinanceAPI::builder()
+                .keys(
+                    "vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A",
+                    "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j",
+                )
+                .recv_window(5000)
+                .transport(transport.clone())
+                .build()
+                .await
+                .unwrap();
+            let mut param_map: HashMap<String, String> = [
+                ("symbol", "LTCBTC"),
+                ("side", "BUY"),
+                ("type", "LIMIT"),
+                ("timeInForce", "GTC"),
+                ("quantity", "1"),
+                ("price", "0.1"),
+                ("newClientOrderId", "a b&c/:"),
+            ]
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+            bn_api
+                .send_request(
+                    "https://api.binance.com/api/v3/order",
+                    "POST",
+                    &mut param_map,
+                    true,
+                )
+                .await
+                .unwrap();
+            let requests = transport.requests();
+            let full_url = requests.last().unwrap().full_url();
+            let (_, query) = full_url.split_once('?').unwrap();
+            let (signed, signature) = query.split_once("&signature=").unwrap();
+            assert_eq!(
+                signature,
+                HmacSigner::new("NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j")
+                    .sign(signed)
+            );
+            assert!(signed.contains("newClientOrderId=a+b%26c%2F%3A&"));
+            assert!(signed.contains("&recvWindow=5000&"));
+        }
+
+        #[tokio::test]
+        async fn client_futures_are_send() {
+            fn assert_send<T: Send>(_: &T) {}
+            let bn_api = BinanceAPI::builder()
+                .keys("key", "secret")
+                .transport(Arc::new(MockTransport::new()))
+                .build()
+                .await
+                .unwrap();
+            assert_send(&bn_api);
+            assert_send(&bn_api.get_price("BTCUSDT"));
+            assert_send(&bn_api.place_order(&NewOrder::new("BTCUSDT", "BUY", "MARKET")));
+        }
+
+        #[tokio::test]
+        async fn keys_rotate_on_a_shared_client() {
+            let transport = Arc::new(MockTransport::new().with_fixture("/api/v3/account", "{}"));
+            let bn_api = Arc::new(
+                BinanceAPI::builder()
+                    .keys("old_key", "old_secret")
+                    .transport(transport.clone())
+                    .build()
+                    .await
+                    .unwrap(),
+            );
+            let shared = bn_api.clone();
+            shared
+                .rotate_keys("new_key", "new_secret", None)
+                .await
+                .unwrap();
+            bn_api.pull_account().await.unwrap();
+            let request = transport.requests().pop().unwrap();
+            assert!(request
+                .headers
+                .contains(&("X-MBX-APIKEY".to_string(), "new_key".to_string())));
+            let full_url = request.full_url();
+            let (_, query) = full_url.split_once('?').unwrap();
+            let (signed, signature) = query.split_once("&signature=").unwrap();
+            assert_eq!(signature, HmacSigner::new("new_secret").sign(signed));
+        }
+
+        #[tokio::test]
+        async fn idempotent_order_is_looked_up_until_it_shows_up() {
+            let transport = Arc::new(MockTransport::new());
+            transport.push_response("/api/v3/order", 503, "");
+            transport.push_response(
+                "/api/v3/order",
+                400,
+                r#"{"code":-2013,"msg":"Order does not exist."}"#,
+            );
+            transport.push_response(
+                "/api/v3/order",
+                200,
+                r#"{"symbol":"BTCUSDT","orderId":7,"status":"FILLED"}"#,
+            );
+            let bn_api = BinanceAPI::builder()
+                .keys("key", "secret")
+                .idempotent_orders(3)
+                .transport(transport.clone())
+                .build()
+                .await
+                .unwrap();
+            let order = NewOrder::new("BTCUSDT", "BUY", "MARKET").quantity(Decimal::ONE);
+            let placed = bn_api.place_order(&order).await.unwrap();
+            assert_eq!(placed["orderId"], 7);
+            let requests = transport.requests();
+            let methods = requests
+                .iter()
+                .map(|a| a.method.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(methods, ["POST", "GET", "GET"]);
+            let client_order_id = |request: &HttpRequest| {
+                request
+                    .query
+                    .iter()
+                    .find(|(key, _)| key == "newClientOrderId" || key == "origClientOrderId")
+                    .map(|(_, value)| value.clone())
+            };
+            assert!(client_order_id(&requests[0]).is_some());
+            assert_eq!(client_order_id(&requests[0]), client_order_id(&requests[2]));
         }
     }
 }
@@ -0,0 +1,404 @@
+pub mod blocking {
+    #[cfg(all(feature = "usdm-futures", feature = "websocket"))]
+    use crate::basis::basis::BasisMonitor;
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::builder::builder::BinanceAPIBuilder;
+    #[cfg(feature = "usdm-futures")]
+    use crate::execution::execution::EmulatedOco;
+    use crate::execution::execution::{TrailingStop, TwapExecutor, TwapReport};
+    #[cfg(feature = "usdm-futures")]
+    use crate::funding::funding::FundingTracker;
+    #[cfg(feature = "usdm-futures")]
+    use crate::history_export::history_export::{ExportKind, ExportStatus, HistoryExport};
+    #[cfg(feature = "wallet")]
+    use crate::models::models::{
+        AccountApiTradingStatus, AccountStatus, ApiKeyPermissions, AssetTransfer, CoinInfo,
+        DustAssets, DustTransferResult, MarginInterest, MarginInterestRate, MaxBorrowable,
+        SystemStatus,
+    };
+    #[cfg(feature = "usdm-futures")]
+    use crate::models::models::{
+        AccountConfig, ApiTradingStatus, AssetIndex, ContractType, IndexInfo, MarginCallEvent,
+        Position, PositionMarginChange, SymbolConfig,
+    };
+    use crate::models::models::{
+        AccountTrade, AggTrade, AssetBalance, BookTicker, DepthSnapshot, Kline, KlineQuery,
+        NewOrder, OrderUpdate, SymbolFilters, TimeInput, Trade,
+    };
+    #[cfg(feature = "spot")]
+    use crate::models::models::{AvgPrice, NewOcoOrder, OcoOrderReport};
+    #[cfg(feature = "websocket")]
+    use crate::notifier::notifier::Notifier;
+    #[cfg(feature = "websocket")]
+    use crate::order_book::order_book::OrderBook;
+    use crate::order_tracker::order_tracker::OrderTracker;
+    use crate::pnl::pnl::{PerformanceRow, PnlCalculator};
+    use crate::portfolio::portfolio::PortfolioValue;
+    use crate::risk::risk::CloseAllReport;
+    #[cfg(feature = "usdm-futures")]
+    use crate::rollover::rollover::{ContractRoller, QuarterlyContracts, Rollover};
+    use crate::scheduler::scheduler::DcaScheduler;
+    use crate::signer::signer::Signer;
+    use crate::strategy::strategy::{GridStrategy, StopGridReport};
+    use crate::symbol_registry::symbol_registry::SymbolInfo;
+    use crate::transport::transport::{HttpRequest, HttpResponse, Transport, TransportFuture};
+    use rust_decimal::Decimal;
+    #[cfg(feature = "websocket")]
+    use std::net::TcpStream;
+    use std::{
+        collections::HashMap,
+        error::Error,
+        future::Future,
+        pin::{pin, Pin},
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+        time::Duration,
+    };
+    #[cfg(feature = "websocket")]
+    use tungstenite::{protocol::WebSocket, stream::MaybeTlsStream};
+
+    type SendRequestRe = serde_json::Value;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    // reqwest::blocking panics inside a tokio runtime, so futures are driven on the calling thread.
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut context = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(res) => return res,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct BlockingTransport {
+        client: reqwest::blocking::Client,
+    }
+
+    impl BlockingTransport {
+        pub fn new(client: reqwest::blocking::Client) -> Self {
+            BlockingTransport { client: client }
+        }
+
+        fn send_blocking(&self, request: HttpRequest) -> Result<HttpResponse, Box<dyn Error>> {
+            let url = request.full_url();
+            let mut res = match request.method.as_str() {
+                "GET" => self.client.get(&url),
+                "POST" => self.client.post(&url),
+                "PUT" => self.client.put(&url),
+                "DELETE" => self.client.delete(&url),
+                method => return Err(format!("Request method `{method}` is not defined.").into()),
+            };
+            for (key, value) in &request.headers {
+                res = res.header(key, value);
+            }
+            let res = res.send()?;
+            let status = res.status().as_u16();
+            let headers = res
+                .headers()
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        key.to_string(),
+                        value.to_str().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect();
+            Ok(HttpResponse {
+                status: status,
+                headers: headers,
+                body: res.text()?,
+            })
+        }
+    }
+
+    impl Transport for BlockingTransport {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async move { self.send_blocking(request) })
+        }
+
+        fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            thread::sleep(duration);
+            Box::pin(std::future::ready(()))
+        }
+    }
+
+    impl<'a> BinanceAPIBuilder<'a> {
+        pub fn build_blocking(mut self) -> Result<BlockingBinanceAPI<'a>, Box<dyn Error>> {
+            if self.transport.is_none() {
+                let mut client = reqwest::blocking::Client::builder()
+                    .gzip(self.compression)
+                    .brotli(self.compression);
+                if let Some(timeout) = self.timeout {
+                    client = client.timeout(timeout);
+                }
+                if let Some(connect_timeout) = self.connect_timeout {
+                    client = client.connect_timeout(connect_timeout);
+                }
+                if let Some(user_agent) = &self.user_agent {
+                    client = client.user_agent(user_agent);
+                }
+                self.transport = Some(Arc::new(BlockingTransport::new(client.build()?)));
+            }
+            Ok(BlockingBinanceAPI {
+                inner: block_on(self.build())?,
+            })
+        }
+    }
+
+    macro_rules! blocking_methods {
+        ($($(#[$meta:meta])* $name:ident($($arg:ident: $type:ty),*) -> $re:ty;)*) => {
+            $(
+                $(#[$meta])*
+                pub fn $name(&self, $($arg: $type),*) -> Result<$re, Box<dyn Error>> {
+                    block_on(self.inner.$name($($arg),*))
+                }
+            )*
+        };
+    }
+
+    #[derive(Debug)]
+    pub struct BlockingBinanceAPI<'a> {
+        inner: BinanceAPI<'a>,
+    }
+
+    impl<'a> BlockingBinanceAPI<'a> {
+        pub fn new(
+            api_key: &'a str,
+            secret_key: &'a str,
+            account_type: &'a str,
+        ) -> Result<Self, Box<dyn Error>> {
+            BinanceAPI::builder()
+                .keys(api_key, secret_key)
+                .account_type(account_type)
+                .auto_listen_key(true)
+                .build_blocking()
+        }
+
+        pub fn with_transport(
+            api_key: &'a str,
+            secret_key: &'a str,
+            account_type: &'a str,
+            transport: impl Transport + 'static,
+        ) -> Result<Self, Box<dyn Error>> {
+            BinanceAPI::builder()
+                .keys(api_key, secret_key)
+                .account_type(account_type)
+                .transport(transport)
+                .auto_listen_key(true)
+                .build_blocking()
+        }
+
+        pub fn inner(&self) -> &BinanceAPI<'a> {
+            &self.inner
+        }
+
+        pub fn rotate_keys(
+            &self,
+            api_key: &str,
+            secret_key: &str,
+            signer: Option<Box<dyn Signer>>,
+        ) -> Result<(), Box<dyn Error>> {
+            block_on(self.inner.rotate_keys(api_key, secret_key, signer))
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn generate_websocket(&self, type_ws: &str) -> WebSocket<MaybeTlsStream<TcpStream>> {
+            self.inner.generate_websocket(type_ws)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn websocket_read_once(&self, ws: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> String {
+            self.inner.websocket_read_once(ws)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn close_websocket(&self, ws: &mut WebSocket<MaybeTlsStream<TcpStream>>) {
+            self.inner.close_websocket(ws)
+        }
+
+        blocking_methods! {
+            send_request_raw(url: &str, method: &str, param_map: &mut HashMap<String, String>, signature: bool) -> HttpResponse;
+            ping() -> ();
+            server_time() -> i64;
+            #[cfg(feature = "wallet")]
+            system_status() -> SystemStatus;
+            #[cfg(feature = "wallet")]
+            get_api_key_permissions() -> ApiKeyPermissions;
+            #[cfg(feature = "wallet")]
+            ensure_api_key_permissions(required: &Vec<&str>) -> ApiKeyPermissions;
+            #[cfg(feature = "wallet")]
+            get_account_status() -> AccountStatus;
+            #[cfg(feature = "wallet")]
+            get_account_api_trading_status() -> AccountApiTradingStatus;
+            #[cfg(feature = "wallet")]
+            ensure_account_active() -> ();
+            #[cfg(feature = "wallet")]
+            get_dust_assets() -> DustAssets;
+            #[cfg(feature = "wallet")]
+            dust_transfer(assets: &Vec<&str>) -> DustTransferResult;
+            #[cfg(feature = "wallet")]
+            get_all_coins_info() -> Vec<CoinInfo>;
+            #[cfg(feature = "wallet")]
+            futures_transfer(asset: &str, amount: Decimal, direction: &str) -> i64;
+            #[cfg(feature = "wallet")]
+            get_futures_transfer_history(direction: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<AssetTransfer>;
+            #[cfg(feature = "wallet")]
+            get_transfer_history(kind: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<AssetTransfer>;
+            #[cfg(feature = "wallet")]
+            get_transfer_history_all(kinds: &Vec<&str>, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<AssetTransfer>;
+            #[cfg(feature = "wallet")]
+            get_margin_interest_history(asset: &str, isolated_symbol: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<MarginInterest>;
+            #[cfg(feature = "wallet")]
+            get_cross_margin_interest_rate(assets: &Vec<&str>) -> Vec<MarginInterestRate>;
+            #[cfg(feature = "wallet")]
+            get_max_borrowable(asset: &str, isolated_symbol: &str) -> MaxBorrowable;
+            #[cfg(feature = "websocket")]
+            listen_key_manager(method: &str) -> String;
+            #[cfg(feature = "websocket")]
+            subscribe_websocket(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, symbols: &Vec<&str>, sub_type: &str) -> ();
+            #[cfg(feature = "websocket")]
+            subscribe_partial_depth(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, symbols: &Vec<&str>, levels: u32, update_ms: u32) -> ();
+            #[cfg(feature = "websocket")]
+            unsubscribe_websocket(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, symbols: &Vec<&str>, sub_type: &str) -> ();
+            #[cfg(feature = "websocket")]
+            set_websocket_property(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, property: &str, value: SendRequestRe) -> ();
+            #[cfg(feature = "websocket")]
+            get_websocket_property(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, property: &str) -> SendRequestRe;
+            #[cfg(feature = "websocket")]
+            shutdown() -> ();
+            history_klines(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            history_klines_with(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>, query: &KlineQuery) -> Vec<Kline>;
+            history_klines_many(symbols: &Vec<&str>, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>, concurrency: usize) -> HashMap<String, Vec<Kline>>;
+            #[cfg(feature = "spot")]
+            get_ui_klines(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>, time_zone: &str) -> Vec<Kline>;
+            #[cfg(feature = "usdm-futures")]
+            get_mark_price_klines(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            #[cfg(feature = "usdm-futures")]
+            get_index_price_klines(pair: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            #[cfg(feature = "usdm-futures")]
+            get_continuous_klines(pair: &str, contract_type: ContractType, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            get_exchange_info() -> SendRequestRe;
+            refresh_symbols() -> ();
+            get_symbol_info(symbol: &str) -> SymbolInfo;
+            get_symbol_filters(symbol: &str) -> SymbolFilters;
+            round_price(symbol: &str, price: Decimal) -> Decimal;
+            round_qty(symbol: &str, qty: Decimal) -> Decimal;
+            get_price(symbol: &str) -> SendRequestRe;
+            #[cfg(feature = "spot")]
+            get_avg_price(symbol: &str) -> AvgPrice;
+            #[cfg(feature = "usdm-futures")]
+            get_mark_price(symbol: &str) -> SendRequestRe;
+            get_ticker(symbol: &str) -> SendRequestRe;
+            get_depth(symbol: &str, limit: u32) -> DepthSnapshot;
+            get_recent_trades(symbol: &str, limit: usize) -> Vec<Trade>;
+            get_historical_trades(symbol: &str, from_id: i64, limit: usize) -> Vec<Trade>;
+            get_agg_trades(symbol: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<AggTrade>;
+            get_book_ticker(symbol: &str) -> Vec<BookTicker>;
+            #[cfg(feature = "usdm-futures")]
+            get_index_info(symbol: &str) -> Vec<IndexInfo>;
+            #[cfg(feature = "usdm-futures")]
+            get_asset_index() -> Vec<AssetIndex>;
+            new_order(symbol: &str, side: &str, trade_type: &str, quantity: &str, price: &str, time_inforce: &str, good_till_date: i64) -> SendRequestRe;
+            place_order(order: &NewOrder) -> SendRequestRe;
+            place_tracked(tracker: &mut OrderTracker, order: &NewOrder) -> SendRequestRe;
+            reconcile_orders(tracker: &mut OrderTracker) -> usize;
+            run_twap(executor: &mut TwapExecutor) -> TwapReport;
+            move_trailing_stop(stop: &mut TrailingStop, stop_price: Decimal) -> bool;
+            #[cfg(feature = "usdm-futures")]
+            place_emulated_oco(oco: &mut EmulatedOco) -> Vec<SendRequestRe>;
+            #[cfg(feature = "usdm-futures")]
+            handle_oco_update(oco: &mut EmulatedOco, update: &OrderUpdate) -> bool;
+            start_grid(grid: &mut GridStrategy) -> Vec<SendRequestRe>;
+            handle_grid_update(grid: &mut GridStrategy, update: &OrderUpdate) -> Option<SendRequestRe>;
+            stop_grid(grid: &mut GridStrategy) -> StopGridReport;
+            run_due_schedules(scheduler: &mut DcaScheduler) -> Vec<SendRequestRe>;
+            run_scheduler(scheduler: &mut DcaScheduler) -> ();
+            account_equity(asset: &str) -> Decimal;
+            get_account_trades(symbol: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<AccountTrade>;
+            get_performance_report(symbols: &Vec<&str>, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<PerformanceRow>;
+            realized_pnl(symbols: &Vec<&str>, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> PnlCalculator;
+            size_position(symbol: &str, risk_percent: Decimal, entry: Decimal, stop: Decimal, leverage: u32) -> Decimal;
+            portfolio_value(quote_asset: &str) -> PortfolioValue;
+            #[cfg(feature = "spot")]
+            new_order_test(order: &NewOrder, compute_commission_rates: bool) -> SendRequestRe;
+            validate_order(order: &NewOrder) -> ();
+            get_open_orders(symbol: &str) -> SendRequestRe;
+            get_order(symbol: &str, order_id: &str, orig_client_order_id: &str) -> SendRequestRe;
+            cancel_order(symbol: &str, order_id: &str, all: bool) -> SendRequestRe;
+            panic_close_all(dry_run: bool, sell_spot_to: &str) -> CloseAllReport;
+            #[cfg(feature = "usdm-futures")]
+            auto_cancel_all(symbol: &str, countdown_ms: u64) -> SendRequestRe;
+            #[cfg(feature = "usdm-futures")]
+            keep_auto_cancel_all(symbol: &str, countdown_ms: u64, refresh: Duration) -> ();
+            get_all_orders(symbol: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<SendRequestRe>;
+            get_my_trades(symbol: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<SendRequestRe>;
+            #[cfg(feature = "usdm-futures")]
+            get_income_history(symbol: &str, income_type: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<SendRequestRe>;
+            #[cfg(feature = "usdm-futures")]
+            request_history_export(kind: ExportKind, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> String;
+            #[cfg(feature = "usdm-futures")]
+            get_history_export(kind: ExportKind, download_id: &str) -> ExportStatus;
+            #[cfg(feature = "usdm-futures")]
+            wait_history_export(kind: ExportKind, download_id: &str, poll_every: Duration) -> ExportStatus;
+            #[cfg(feature = "usdm-futures")]
+            download_history_export(status: &ExportStatus) -> HistoryExport;
+            #[cfg(feature = "usdm-futures")]
+            export_history(kind: ExportKind, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>, poll_every: Duration) -> HistoryExport;
+            #[cfg(feature = "usdm-futures")]
+            sync_funding(tracker: &Mutex<FundingTracker>) -> ();
+            #[cfg(all(feature = "usdm-futures", feature = "websocket"))]
+            run_funding_tracker(tracker: &Mutex<FundingTracker>) -> ();
+            #[cfg(all(feature = "usdm-futures", feature = "websocket"))]
+            run_basis_monitor(perp: &BinanceAPI<'_>, monitor: &Mutex<BasisMonitor>) -> ();
+            #[cfg(feature = "websocket")]
+            run_notifier(notifier: &Notifier) -> ();
+            #[cfg(feature = "websocket")]
+            run_order_book(book: &Mutex<OrderBook>, update_ms: u32) -> ();
+            pull_account() -> SendRequestRe;
+            #[cfg(feature = "usdm-futures")]
+            get_position(symbol: &str) -> Vec<Position>;
+            #[cfg(feature = "usdm-futures")]
+            get_quarterly_contracts(pair: &str) -> QuarterlyContracts;
+            #[cfg(feature = "usdm-futures")]
+            check_rollover(roller: &mut ContractRoller) -> Option<Rollover>;
+            #[cfg(feature = "usdm-futures")]
+            wait_rollover(roller: &mut ContractRoller, check_every: Duration) -> Rollover;
+            #[cfg(all(feature = "usdm-futures", feature = "websocket"))]
+            resubscribe_rollover(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, rollover: &Rollover, sub_types: &Vec<&str>) -> ();
+            #[cfg(feature = "usdm-futures")]
+            reduce_positions(margin_call: &MarginCallEvent, percent: Decimal) -> Vec<SendRequestRe>;
+            #[cfg(feature = "usdm-futures")]
+            get_account_config() -> AccountConfig;
+            #[cfg(feature = "usdm-futures")]
+            get_symbol_config(symbol: &str) -> Vec<SymbolConfig>;
+            #[cfg(feature = "usdm-futures")]
+            get_api_trading_status(symbol: &str) -> ApiTradingStatus;
+            #[cfg(feature = "usdm-futures")]
+            modify_position_margin(symbol: &str, amount: Decimal, add_or_reduce: &str, position_side: &str) -> SendRequestRe;
+            #[cfg(feature = "usdm-futures")]
+            get_position_margin_history(symbol: &str, add_or_reduce: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<PositionMarginChange>;
+            #[cfg(feature = "usdm-futures")]
+            top_up_isolated_margin(symbol: &str, min_distance_percent: Decimal, amount: Decimal) -> Vec<SendRequestRe>;
+            get_balance() -> Vec<AssetBalance>;
+            #[cfg(feature = "spot")]
+            new_oco_order(order: &NewOcoOrder) -> OcoOrderReport;
+            #[cfg(feature = "spot")]
+            cancel_oco(symbol: &str, order_list_id: &str) -> OcoOrderReport;
+            #[cfg(feature = "spot")]
+            get_oco(order_list_id: &str) -> OcoOrderReport;
+            paper_match() -> Vec<SendRequestRe>;
+        }
+    }
+}
@@ -0,0 +1,291 @@
+pub mod builder {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::logging::logging::{LogSink, LoggingTransport};
+    use crate::signer::signer::Signer;
+    use crate::transport::transport::{ReqwestTransport, Transport};
+    use chrono::{DateTime, Utc};
+    use std::{borrow::Cow, error::Error, sync::Arc, time::Duration};
+    use url::Url;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum RateLimitBehavior {
+        // Hand the 429/418 response back to the caller as a Binance error.
+        #[default]
+        Error,
+        // Sleep for `Retry-After` and resend, at most `max_retries` times.
+        Wait {
+            max_retries: u32,
+        },
+    }
+
+    // Unit of every time field Binance returns; microseconds are spot only.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum TimeUnit {
+        #[default]
+        Millisecond,
+        Microsecond,
+    }
+
+    impl TimeUnit {
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                TimeUnit::Millisecond => "MILLISECOND",
+                TimeUnit::Microsecond => "MICROSECOND",
+            }
+        }
+
+        pub fn from_millis(&self, millis: i64) -> i64 {
+            match self {
+                TimeUnit::Millisecond => millis,
+                TimeUnit::Microsecond => millis.saturating_mul(1000),
+            }
+        }
+
+        pub fn to_millis(&self, time: i64) -> i64 {
+            match self {
+                TimeUnit::Millisecond => time,
+                TimeUnit::Microsecond => time.div_euclid(1000),
+            }
+        }
+
+        pub fn to_datetime(&self, time: i64) -> Option<DateTime<Utc>> {
+            match self {
+                TimeUnit::Millisecond => DateTime::from_timestamp_millis(time),
+                TimeUnit::Microsecond => DateTime::from_timestamp_micros(time),
+            }
+        }
+    }
+
+    pub struct BinanceAPIBuilder<'a> {
+        pub(crate) api_key: Cow<'a, str>,
+        pub(crate) secret_key: Cow<'a, str>,
+        pub(crate) account_type: &'a str,
+        pub(crate) testnet: bool,
+        pub(crate) base_url: Option<Cow<'a, str>>,
+        pub(crate) wss_url: Option<Cow<'a, str>>,
+        pub(crate) sapi_url: Option<Cow<'a, str>>,
+        pub(crate) recv_window: u64,
+        pub(crate) timeout: Option<Duration>,
+        pub(crate) connect_timeout: Option<Duration>,
+        pub(crate) user_agent: Option<String>,
+        pub(crate) compression: bool,
+        pub(crate) rate_limit: RateLimitBehavior,
+        pub(crate) order_retries: Option<u32>,
+        pub(crate) signer: Option<Arc<dyn Signer>>,
+        pub(crate) transport: Option<Arc<dyn Transport>>,
+        pub(crate) log_sink: Option<Arc<dyn LogSink>>,
+        pub(crate) auto_listen_key: bool,
+        pub(crate) ws_compression: bool,
+        pub(crate) time_unit: TimeUnit,
+    }
+
+    impl<'a> Default for BinanceAPIBuilder<'a> {
+        fn default() -> Self {
+            BinanceAPIBuilder {
+                api_key: Cow::Borrowed(""),
+                secret_key: Cow::Borrowed(""),
+                account_type: "spot",
+                testnet: false,
+                base_url: None,
+                wss_url: None,
+                sapi_url: None,
+                recv_window: 0,
+                timeout: None,
+                connect_timeout: None,
+                user_agent: None,
+                compression: true,
+                rate_limit: Default::default(),
+                order_retries: None,
+                signer: None,
+                transport: None,
+                log_sink: None,
+                auto_listen_key: false,
+                ws_compression: false,
+                time_unit: Default::default(),
+            }
+        }
+    }
+
+    impl<'a> BinanceAPIBuilder<'a> {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        pub fn keys(
+            mut self,
+            api_key: impl Into<Cow<'a, str>>,
+            secret_key: impl Into<Cow<'a, str>>,
+        ) -> Self {
+            self.api_key = api_key.into();
+            self.secret_key = secret_key.into();
+            self
+        }
+
+        pub fn account_type(mut self, account_type: &'a str) -> Self {
+            self.account_type = account_type;
+            self
+        }
+
+        pub fn testnet(mut self, testnet: bool) -> Self {
+            self.testnet = testnet;
+            self
+        }
+
+        pub fn base_url(mut self, base_url: impl Into<Cow<'a, str>>) -> Self {
+            self.base_url = Some(base_url.into());
+            self
+        }
+
+        pub fn wss_url(mut self, wss_url: impl Into<Cow<'a, str>>) -> Self {
+            self.wss_url = Some(wss_url.into());
+            self
+        }
+
+        // Host of the `/sapi` wallet endpoints. Defaults to `base_url` on spot when that is
+        // overridden, otherwise to api.binance.com.
+        pub fn sapi_url(mut self, sapi_url: impl Into<Cow<'a, str>>) -> Self {
+            self.sapi_url = Some(sapi_url.into());
+            self
+        }
+
+        pub fn recv_window(mut self, recv_window: u64) -> Self {
+            self.recv_window = recv_window;
+            self
+        }
+
+        pub fn timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = Some(timeout);
+            self
+        }
+
+        pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+            self.connect_timeout = Some(connect_timeout);
+            self
+        }
+
+        pub fn user_agent(mut self, user_agent: &str) -> Self {
+            self.user_agent = Some(user_agent.to_string());
+            self
+        }
+
+        // Ask for gzip/brotli responses and decode them; on by default.
+        pub fn compression(mut self, compression: bool) -> Self {
+            self.compression = compression;
+            self
+        }
+
+        pub fn rate_limit(mut self, rate_limit: RateLimitBehavior) -> Self {
+            self.rate_limit = rate_limit;
+            self
+        }
+
+        // Gives every order without a client order id a unique one, and when an order request
+        // times out or gets a 5xx, looks the order up by that id for about five seconds before
+        // sending it again, at most `max_retries` times, so a network flap never places it twice.
+        pub fn idempotent_orders(mut self, max_retries: u32) -> Self {
+            self.order_retries = Some(max_retries);
+            self
+        }
+
+        pub fn signer(mut self, signer: impl Signer + 'static) -> Self {
+            self.signer = Some(Arc::new(signer));
+            self
+        }
+
+        // Timeouts, the user agent and compression only apply to the default reqwest transport.
+        pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+            self.transport = Some(Arc::new(transport));
+            self
+        }
+
+        // Logs every REST call to `sink` through a `LoggingTransport`, whichever transport is used.
+        pub fn log_requests(mut self, sink: impl LogSink + 'static) -> Self {
+            self.log_sink = Some(Arc::new(sink));
+            self
+        }
+
+        pub fn auto_listen_key(mut self, auto_listen_key: bool) -> Self {
+            self.auto_listen_key = auto_listen_key;
+            self
+        }
+
+        // Ask for permessage-deflate on the streams the client runs (`stream_*`, `run_*`,
+        // `maintain_*`); off by default. It saves bandwidth on all-market and depth streams at
+        // some CPU, and streams the server won't compress are read as before.
+        pub fn ws_compression(mut self, ws_compression: bool) -> Self {
+            self.ws_compression = ws_compression;
+            self
+        }
+
+        pub fn time_unit(mut self, time_unit: TimeUnit) -> Self {
+            self.time_unit = time_unit;
+            self
+        }
+
+        pub(crate) fn check(&self) -> Result<(), Box<dyn Error>> {
+            if !["spot", "swap"].contains(&self.account_type) {
+                return Err(format!("Account type `{}` is not defined.", self.account_type).into());
+            }
+            let feature = if self.account_type == "spot" {
+                ("spot", cfg!(feature = "spot"))
+            } else {
+                ("usdm-futures", cfg!(feature = "usdm-futures"))
+            };
+            if !feature.1 {
+                return Err(format!(
+                    "Account type `{}` needs the `{}` feature.",
+                    self.account_type, feature.0
+                )
+                .into());
+            }
+            if self.time_unit == TimeUnit::Microsecond && self.account_type != "spot" {
+                return Err("timeUnit `MICROSECOND` is only supported on spot.".into());
+            }
+            for (name, url, schemes) in [
+                ("base_url", &self.base_url, ["http", "https"]),
+                ("sapi_url", &self.sapi_url, ["http", "https"]),
+                ("wss_url", &self.wss_url, ["ws", "wss"]),
+            ] {
+                if let Some(url) = url {
+                    match Url::parse(url) {
+                        Ok(parsed) if schemes.contains(&parsed.scheme()) => {}
+                        _ => return Err(format!("{name} `{url}` is not a valid url.").into()),
+                    }
+                }
+            }
+            if self.recv_window > 60000 {
+                return Err(
+                    format!("recvWindow `{}` can't exceed 60000.", self.recv_window).into(),
+                );
+            }
+            Ok(())
+        }
+
+        pub async fn build(mut self) -> Result<BinanceAPI<'a>, Box<dyn Error>> {
+            self.check()?;
+            let transport = match self.transport.take() {
+                Some(transport) => transport,
+                None => {
+                    let mut client = reqwest::Client::builder()
+                        .gzip(self.compression)
+                        .brotli(self.compression);
+                    if let Some(timeout) = self.timeout {
+                        client = client.timeout(timeout);
+                    }
+                    if let Some(connect_timeout) = self.connect_timeout {
+                        client = client.connect_timeout(connect_timeout);
+                    }
+                    if let Some(user_agent) = &self.user_agent {
+                        client = client.user_agent(user_agent);
+                    }
+                    Arc::new(ReqwestTransport::new(client.build()?))
+                }
+            };
+            let transport: Arc<dyn Transport> = match self.log_sink.take() {
+                Some(sink) => Arc::new(LoggingTransport::new(transport, sink)),
+                None => transport,
+            };
+            BinanceAPI::from_builder(self, transport).await
+        }
+    }
+}
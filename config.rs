@@ -0,0 +1,113 @@
+pub mod config {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::transport::transport::redact;
+    use serde::Deserialize;
+    use std::{env, error::Error, fmt, fs};
+
+    #[derive(Clone, Deserialize)]
+    #[serde(default)]
+    pub struct ClientConfig {
+        pub api_key: String,
+        pub secret_key: String,
+        pub account_type: String,
+        pub testnet: bool,
+        pub recv_window: u64,
+        // Host overrides, "" for the default of the account type.
+        pub base_url: String,
+        pub wss_url: String,
+        pub sapi_url: String,
+    }
+
+    impl fmt::Debug for ClientConfig {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ClientConfig")
+                .field("api_key", &redact(&self.api_key))
+                .field("secret_key", &redact(&self.secret_key))
+                .field("account_type", &self.account_type)
+                .field("testnet", &self.testnet)
+                .field("recv_window", &self.recv_window)
+                .field("base_url", &self.base_url)
+                .field("wss_url", &self.wss_url)
+                .field("sapi_url", &self.sapi_url)
+                .finish()
+        }
+    }
+
+    impl Default for ClientConfig {
+        fn default() -> Self {
+            ClientConfig {
+                api_key: String::new(),
+                secret_key: String::new(),
+                account_type: "spot".to_string(),
+                testnet: false,
+                recv_window: 0,
+                base_url: String::new(),
+                wss_url: String::new(),
+                sapi_url: String::new(),
+            }
+        }
+    }
+
+    impl ClientConfig {
+        // BINANCE_API_KEY, BINANCE_SECRET_KEY, BINANCE_ACCOUNT_TYPE, BINANCE_TESTNET, BINANCE_RECV_WINDOW,
+        // BINANCE_BASE_URL, BINANCE_WSS_URL, BINANCE_SAPI_URL.
+        pub fn from_env() -> Result<Self, Box<dyn Error>> {
+            let var = |name: &str| env::var(name).ok().filter(|a| !a.is_empty());
+            let mut config = ClientConfig {
+                api_key: var("BINANCE_API_KEY").unwrap_or_default(),
+                secret_key: var("BINANCE_SECRET_KEY").unwrap_or_default(),
+                base_url: var("BINANCE_BASE_URL").unwrap_or_default(),
+                wss_url: var("BINANCE_WSS_URL").unwrap_or_default(),
+                sapi_url: var("BINANCE_SAPI_URL").unwrap_or_default(),
+                ..Default::default()
+            };
+            if let Some(account_type) = var("BINANCE_ACCOUNT_TYPE") {
+                config.account_type = account_type;
+            }
+            if let Some(testnet) = var("BINANCE_TESTNET") {
+                config.testnet = ["1", "true", "yes"].contains(&testnet.to_lowercase().as_str());
+            }
+            if let Some(recv_window) = var("BINANCE_RECV_WINDOW") {
+                config.recv_window = recv_window.parse()?;
+            }
+            Ok(config)
+        }
+
+        pub fn from_toml_file(path: &str) -> Result<Self, Box<dyn Error>> {
+            Ok(toml::from_str(&fs::read_to_string(path)?)?)
+        }
+    }
+
+    impl BinanceAPI<'static> {
+        pub async fn from_env() -> Result<Self, Box<dyn Error>> {
+            let config = ClientConfig::from_env()?;
+            Self::from_config(&config).await
+        }
+
+        pub async fn from_config(config: &ClientConfig) -> Result<Self, Box<dyn Error>> {
+            let account_type = match config.account_type.as_str() {
+                "spot" => "spot",
+                "swap" => "swap",
+                account_type => {
+                    return Err(format!("Account type `{account_type}` is not defined.").into())
+                }
+            };
+            let mut builder = Self::builder()
+                .keys(config.api_key.clone(), config.secret_key.clone())
+                .account_type(account_type)
+                .testnet(config.testnet)
+                .recv_window(config.recv_window)
+                .auto_listen_key(true);
+            if !config.base_url.is_empty() {
+                builder = builder.base_url(config.base_url.clone());
+            }
+            if !config.wss_url.is_empty() {
+                builder = builder.wss_url(config.wss_url.clone());
+            }
+            if !config.sapi_url.is_empty() {
+                builder = builder.sapi_url(config.sapi_url.clone());
+            }
+            builder.build().await
+        }
+    }
+}
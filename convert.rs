@@ -0,0 +1,145 @@
+pub mod convert {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Deserializer, Serialize};
+    use std::error::Error;
+
+    fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(s) => Ok(s),
+            other => Ok(other.to_string()),
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ConvertQuote {
+        pub quote_id: String,
+        pub ratio: Decimal,
+        pub inverse_ratio: Decimal,
+        pub valid_timestamp: i64,
+        pub to_amount: Decimal,
+        pub from_amount: Decimal,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ConvertAcceptance {
+        #[serde(deserialize_with = "string_or_number")]
+        pub order_id: String,
+        pub create_time: i64,
+        pub order_status: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ConvertOrder {
+        #[serde(default)]
+        pub quote_id: String,
+        #[serde(deserialize_with = "string_or_number")]
+        pub order_id: String,
+        pub order_status: String,
+        pub from_asset: String,
+        pub from_amount: Decimal,
+        pub to_asset: String,
+        pub to_amount: Decimal,
+        pub ratio: Decimal,
+        pub inverse_ratio: Decimal,
+        pub create_time: i64,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ConvertTradeFlow {
+        pub list: Vec<ConvertOrder>,
+        pub start_time: i64,
+        pub end_time: i64,
+        pub limit: i64,
+        pub more_data: bool,
+    }
+
+    pub struct Convert<'b, 'a> {
+        bn_api: &'b BinanceAPI<'a>,
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        pub fn convert(&self) -> Convert<'_, 'a> {
+            Convert { bn_api: self }
+        }
+    }
+
+    impl<'b, 'a> Convert<'b, 'a> {
+        pub async fn get_convert_quote(
+            &self,
+            from_asset: &str,
+            to_asset: &str,
+            from_amount: &str,
+            to_amount: &str,
+        ) -> Result<ConvertQuote, Box<dyn Error>> {
+            let url = self.bn_api.sapi_url.to_string() + "/sapi/v1/convert/getQuote";
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("fromAsset".to_string(), from_asset.to_string());
+            param_map.insert("toAsset".to_string(), to_asset.to_string());
+            if from_amount.is_empty() {
+                param_map.insert("toAmount".to_string(), to_amount.to_string());
+            } else {
+                param_map.insert("fromAmount".to_string(), from_amount.to_string());
+            }
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+
+        pub async fn accept_quote(
+            &self,
+            quote_id: &str,
+        ) -> Result<ConvertAcceptance, Box<dyn Error>> {
+            let url = self.bn_api.sapi_url.to_string() + "/sapi/v1/convert/acceptQuote";
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("quoteId".to_string(), quote_id.to_string());
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+
+        pub async fn order_status(
+            &self,
+            order_id: &str,
+            quote_id: &str,
+        ) -> Result<ConvertOrder, Box<dyn Error>> {
+            let url = self.bn_api.sapi_url.to_string() + "/sapi/v1/convert/orderStatus";
+            let mut param_map = std::collections::HashMap::new();
+            if order_id.is_empty() {
+                param_map.insert("quoteId".to_string(), quote_id.to_string());
+            } else {
+                param_map.insert("orderId".to_string(), order_id.to_string());
+            }
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+
+        pub async fn trade_flow(
+            &self,
+            start_time: i64,
+            end_time: i64,
+        ) -> Result<ConvertTradeFlow, Box<dyn Error>> {
+            let url = self.bn_api.sapi_url.to_string() + "/sapi/v1/convert/tradeFlow";
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("startTime".to_string(), start_time.to_string());
+            param_map.insert("endTime".to_string(), end_time.to_string());
+            param_map.insert("limit".to_string(), "1000".to_string());
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+    }
+}
@@ -0,0 +1,51 @@
+pub mod dataframe {
+    use crate::models::models::{AggTrade, Kline, Trade};
+    use polars::prelude::*;
+    use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+    fn to_f64(values: Vec<&Decimal>) -> Vec<f64> {
+        values
+            .iter()
+            .map(|a| a.to_f64().unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    pub fn klines_to_dataframe(klines: &[Kline]) -> PolarsResult<DataFrame> {
+        df!(
+            "open_time" => klines.iter().map(|a| a.open_time).collect::<Vec<_>>(),
+            "open" => to_f64(klines.iter().map(|a| &a.open).collect()),
+            "high" => to_f64(klines.iter().map(|a| &a.high).collect()),
+            "low" => to_f64(klines.iter().map(|a| &a.low).collect()),
+            "close" => to_f64(klines.iter().map(|a| &a.close).collect()),
+            "volume" => to_f64(klines.iter().map(|a| &a.volume).collect()),
+            "close_time" => klines.iter().map(|a| a.close_time).collect::<Vec<_>>(),
+            "quote_volume" => to_f64(klines.iter().map(|a| &a.quote_volume).collect()),
+            "trade_count" => klines.iter().map(|a| a.trade_count).collect::<Vec<_>>(),
+            "taker_buy_base_volume" => to_f64(klines.iter().map(|a| &a.taker_buy_base_volume).collect()),
+            "taker_buy_quote_volume" => to_f64(klines.iter().map(|a| &a.taker_buy_quote_volume).collect()),
+        )
+    }
+
+    pub fn trades_to_dataframe(trades: &[Trade]) -> PolarsResult<DataFrame> {
+        df!(
+            "id" => trades.iter().map(|a| a.id).collect::<Vec<_>>(),
+            "price" => to_f64(trades.iter().map(|a| &a.price).collect()),
+            "qty" => to_f64(trades.iter().map(|a| &a.qty).collect()),
+            "quote_qty" => to_f64(trades.iter().map(|a| &a.quote_qty).collect()),
+            "time" => trades.iter().map(|a| a.time).collect::<Vec<_>>(),
+            "is_buyer_maker" => trades.iter().map(|a| a.is_buyer_maker).collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn agg_trades_to_dataframe(trades: &[AggTrade]) -> PolarsResult<DataFrame> {
+        df!(
+            "agg_trade_id" => trades.iter().map(|a| a.agg_trade_id).collect::<Vec<_>>(),
+            "price" => to_f64(trades.iter().map(|a| &a.price).collect()),
+            "qty" => to_f64(trades.iter().map(|a| &a.qty).collect()),
+            "first_trade_id" => trades.iter().map(|a| a.first_trade_id).collect::<Vec<_>>(),
+            "last_trade_id" => trades.iter().map(|a| a.last_trade_id).collect::<Vec<_>>(),
+            "time" => trades.iter().map(|a| a.time).collect::<Vec<_>>(),
+            "is_buyer_maker" => trades.iter().map(|a| a.is_buyer_maker).collect::<Vec<_>>(),
+        )
+    }
+}
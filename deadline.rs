@@ -0,0 +1,125 @@
+pub mod deadline {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use futures::future::{select, Either};
+    use std::{
+        error::Error,
+        fmt,
+        future::Future,
+        pin::pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use tokio::sync::Notify;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DeadlineExceeded {
+        pub deadline: Duration,
+    }
+
+    impl fmt::Display for DeadlineExceeded {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "Request abandoned after {}ms.",
+                self.deadline.as_millis()
+            )
+        }
+    }
+
+    impl Error for DeadlineExceeded {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Cancelled;
+
+    impl fmt::Display for Cancelled {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Request cancelled.")
+        }
+    }
+
+    impl Error for Cancelled {}
+
+    #[derive(Debug, Default)]
+    struct TokenState {
+        cancelled: AtomicBool,
+        notify: Notify,
+    }
+
+    // Cancels every request run with `with_cancel` on it, or on a clone of it.
+    #[derive(Debug, Clone, Default)]
+    pub struct CancelToken(Arc<TokenState>);
+
+    impl CancelToken {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        pub fn cancel(&self) {
+            self.0.cancelled.store(true, Ordering::SeqCst);
+            self.0.notify.notify_waiters();
+        }
+
+        pub fn is_cancelled(&self) -> bool {
+            self.0.cancelled.load(Ordering::SeqCst)
+        }
+
+        pub async fn cancelled(&self) {
+            loop {
+                // Created before the check so a `cancel` in between still wakes it.
+                let notified = self.0.notify.notified();
+                if self.is_cancelled() {
+                    return;
+                }
+                notified.await;
+            }
+        }
+    }
+
+    // Every REST call can be dropped at an await: the rate limiter's queue place is given back
+    // and nothing else is left half done. A dropped order may still have reached the exchange,
+    // so look it up by client order id before placing it again. Stream channels' `recv` is
+    // cancel-safe too; websocket requests (`subscribe_websocket` and the like) block until
+    // acknowledged, for at most 10 seconds.
+    impl<'a> BinanceAPI<'a> {
+        // Runs `request`, e.g. `bn_api.get_price("BTCUSDT")`, and gives up with
+        // `DeadlineExceeded` after `deadline`, so a tick can go on without a slow answer. The
+        // timer is the transport's sleep, so with the blocking client use `timeout` instead.
+        pub async fn with_deadline<T>(
+            &self,
+            deadline: Duration,
+            request: impl Future<Output = Result<T, Box<dyn Error>>>,
+        ) -> Result<T, Box<dyn Error>> {
+            let request = pin!(request);
+            match select(request, self.transport.0.sleep(deadline)).await {
+                Either::Left((res, _)) => res,
+                Either::Right(_) => {
+                    tracing::debug!(
+                        deadline_ms = deadline.as_millis() as u64,
+                        "request abandoned"
+                    );
+                    Err(Box::new(DeadlineExceeded { deadline: deadline }))
+                }
+            }
+        }
+
+        // Runs `request` until it finishes or `token` is cancelled, then errors with `Cancelled`.
+        pub async fn with_cancel<T>(
+            &self,
+            token: &CancelToken,
+            request: impl Future<Output = Result<T, Box<dyn Error>>>,
+        ) -> Result<T, Box<dyn Error>> {
+            if token.is_cancelled() {
+                return Err(Box::new(Cancelled));
+            }
+            let request = pin!(request);
+            let cancelled = pin!(token.cancelled());
+            match select(request, cancelled).await {
+                Either::Left((res, _)) => res,
+                Either::Right(_) => Err(Box::new(Cancelled)),
+            }
+        }
+    }
+}
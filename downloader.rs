@@ -0,0 +1,234 @@
+pub mod downloader {
+    use crate::models::models::{AggTrade, Kline, Trade};
+    use chrono::{Datelike, Duration, NaiveDate};
+    use sha2::{Digest, Sha256};
+    use std::{error::Error, io::Read};
+
+    const VISION_URL: &str = "https://data.binance.vision/data";
+
+    pub struct HistoryDownloader {
+        market: &'static str,
+        client: reqwest::Client,
+    }
+
+    impl HistoryDownloader {
+        pub fn new(account_type: &str) -> Self {
+            let market = match account_type {
+                "spot" => "spot",
+                "swap" => "futures/um",
+                _ => {
+                    assert!(false, "Account type `{account_type}` is not defined.");
+                    ""
+                }
+            };
+            HistoryDownloader {
+                market: market,
+                client: reqwest::Client::new(),
+            }
+        }
+
+        fn archive_url(&self, period: &str, data_path: &str, file_stem: &str) -> String {
+            assert!(
+                ["daily", "monthly"].contains(&period),
+                "Archive period `{period}` is not defined."
+            );
+            format!(
+                "{}/{}/{}/{}/{}.zip",
+                VISION_URL, self.market, period, data_path, file_stem
+            )
+        }
+
+        async fn fetch_archive(&self, url: &str) -> Result<String, Box<dyn Error>> {
+            let res = self.client.get(url).send().await?;
+            if !res.status().is_success() {
+                return Err(format!("Can't download `{url}`: {}.", res.status()).into());
+            }
+            let archive = res.bytes().await?;
+            let checksum = self
+                .client
+                .get(format!("{url}.CHECKSUM"))
+                .send()
+                .await?
+                .text()
+                .await?;
+            let expected = checksum.split_whitespace().next().unwrap_or_default();
+            let actual = hex::encode(Sha256::digest(&archive));
+            if !expected.eq_ignore_ascii_case(&actual) {
+                return Err(format!("Checksum mismatch for `{url}`.").into());
+            }
+            let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive))?;
+            let mut content = String::new();
+            zip.by_index(0)?.read_to_string(&mut content)?;
+            Ok(content)
+        }
+
+        fn csv_rows(content: &str) -> Vec<Vec<&str>> {
+            content
+                .lines()
+                .map(|a| a.trim().split(',').collect::<Vec<_>>())
+                .filter(|a| a[0].parse::<i64>().is_ok())
+                .collect()
+        }
+
+        // Spot archives switched to microsecond timestamps in 2025.
+        fn millis(value: &str) -> Result<i64, Box<dyn Error>> {
+            let value = value.parse::<i64>()?;
+            Ok(if value >= 100_000_000_000_000 {
+                value / 1000
+            } else {
+                value
+            })
+        }
+
+        fn flag(value: &str) -> bool {
+            value.eq_ignore_ascii_case("true")
+        }
+
+        pub async fn download_klines(
+            &self,
+            symbol: &str,
+            interval: &str,
+            period: &str,
+            date: &str,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            let url = self.archive_url(
+                period,
+                &format!("klines/{symbol}/{interval}"),
+                &format!("{symbol}-{interval}-{date}"),
+            );
+            let content = self.fetch_archive(&url).await?;
+            let mut klines = vec![];
+            for row in Self::csv_rows(&content) {
+                klines.push(Kline {
+                    open_time: Self::millis(row[0])?,
+                    open: row[1].parse()?,
+                    high: row[2].parse()?,
+                    low: row[3].parse()?,
+                    close: row[4].parse()?,
+                    volume: row[5].parse()?,
+                    close_time: Self::millis(row[6])?,
+                    quote_volume: row[7].parse()?,
+                    trade_count: row[8].parse()?,
+                    taker_buy_base_volume: row[9].parse()?,
+                    taker_buy_quote_volume: row[10].parse()?,
+                });
+            }
+            Ok(klines)
+        }
+
+        pub async fn download_trades(
+            &self,
+            symbol: &str,
+            period: &str,
+            date: &str,
+        ) -> Result<Vec<Trade>, Box<dyn Error>> {
+            let url = self.archive_url(
+                period,
+                &format!("trades/{symbol}"),
+                &format!("{symbol}-trades-{date}"),
+            );
+            let content = self.fetch_archive(&url).await?;
+            let mut trades = vec![];
+            for row in Self::csv_rows(&content) {
+                trades.push(Trade {
+                    id: row[0].parse()?,
+                    price: row[1].parse()?,
+                    qty: row[2].parse()?,
+                    quote_qty: row[3].parse()?,
+                    time: Self::millis(row[4])?,
+                    is_buyer_maker: Self::flag(row[5]),
+                    is_best_match: row.get(6).map(|a| Self::flag(a)),
+                });
+            }
+            Ok(trades)
+        }
+
+        pub async fn download_agg_trades(
+            &self,
+            symbol: &str,
+            period: &str,
+            date: &str,
+        ) -> Result<Vec<AggTrade>, Box<dyn Error>> {
+            let url = self.archive_url(
+                period,
+                &format!("aggTrades/{symbol}"),
+                &format!("{symbol}-aggTrades-{date}"),
+            );
+            let content = self.fetch_archive(&url).await?;
+            let mut trades = vec![];
+            for row in Self::csv_rows(&content) {
+                trades.push(AggTrade {
+                    agg_trade_id: row[0].parse()?,
+                    price: row[1].parse()?,
+                    qty: row[2].parse()?,
+                    first_trade_id: row[3].parse()?,
+                    last_trade_id: row[4].parse()?,
+                    time: Self::millis(row[5])?,
+                    is_buyer_maker: Self::flag(row[6]),
+                    is_best_match: row.get(7).map(|a| Self::flag(a)),
+                });
+            }
+            Ok(trades)
+        }
+
+        // Whole months come from monthly archives, the partial months at either end from daily ones.
+        fn archive_periods(
+            start_date: &str,
+            end_date: &str,
+        ) -> Result<Vec<(&'static str, String)>, Box<dyn Error>> {
+            let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")?;
+            let end = NaiveDate::parse_from_str(end_date, "%Y-%m-%d")?;
+            let mut periods = vec![];
+            let mut day = start;
+            while day <= end {
+                let month_end = NaiveDate::from_ymd_opt(
+                    day.year() + day.month() as i32 / 12,
+                    day.month() % 12 + 1,
+                    1,
+                )
+                .unwrap()
+                    - Duration::days(1);
+                if day.day() == 1 && month_end <= end {
+                    periods.push(("monthly", day.format("%Y-%m").to_string()));
+                    day = month_end + Duration::days(1);
+                } else {
+                    periods.push(("daily", day.format("%Y-%m-%d").to_string()));
+                    day += Duration::days(1);
+                }
+            }
+            Ok(periods)
+        }
+
+        pub async fn download_klines_range(
+            &self,
+            symbol: &str,
+            interval: &str,
+            start_date: &str,
+            end_date: &str,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            let mut klines = vec![];
+            let periods = Self::archive_periods(start_date, end_date)?;
+            for (period, date) in periods {
+                klines.extend(
+                    self.download_klines(symbol, interval, period, &date)
+                        .await?,
+                );
+            }
+            Ok(klines)
+        }
+
+        pub async fn download_agg_trades_range(
+            &self,
+            symbol: &str,
+            start_date: &str,
+            end_date: &str,
+        ) -> Result<Vec<AggTrade>, Box<dyn Error>> {
+            let mut trades = vec![];
+            let periods = Self::archive_periods(start_date, end_date)?;
+            for (period, date) in periods {
+                trades.extend(self.download_agg_trades(symbol, period, &date).await?);
+            }
+            Ok(trades)
+        }
+    }
+}
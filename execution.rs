@@ -0,0 +1,764 @@
+pub mod execution {
+    use crate::binance_api::binance_api::{BinanceAPI, BinanceError};
+    use crate::models::models::{NewOrder, OrderUpdate};
+    #[cfg(feature = "websocket")]
+    use crate::stream::stream::UserDataEvent;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    use std::{collections::HashMap, error::Error, str::FromStr, time::Duration};
+    use tokio::sync::mpsc;
+
+    pub(crate) fn decimal_field(value: &Value, key: &str) -> Option<Decimal> {
+        match &value[key] {
+            Value::String(a) => Decimal::from_str(a).ok(),
+            Value::Number(a) => Decimal::from_str(&a.to_string()).ok(),
+            _ => None,
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub struct Fill {
+        pub qty: Decimal,
+        pub quote: Decimal,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct TwapReport {
+        pub symbol: String,
+        pub side: String,
+        pub target_qty: Decimal,
+        pub filled_qty: Decimal,
+        pub avg_price: Decimal,
+        // Last price when the executor started.
+        pub arrival_price: Decimal,
+        // Positive when the average price is worse than the arrival price for `side`.
+        pub slippage_bps: Decimal,
+        pub orders: Vec<Value>,
+    }
+
+    // Splits `total_qty` into `slices` MARKET child orders spread evenly over `duration`. Fills
+    // come from the order responses and from user stream execution updates.
+    #[derive(Debug, Clone)]
+    pub struct TwapExecutor {
+        pub symbol: String,
+        pub side: String,
+        pub total_qty: Decimal,
+        pub duration: Duration,
+        pub slices: u32,
+        client_id_prefix: String,
+        arrival_price: Decimal,
+        response_fills: HashMap<String, Fill>,
+        stream_fills: HashMap<String, Fill>,
+        orders: Vec<Value>,
+    }
+
+    impl TwapExecutor {
+        pub fn new(
+            symbol: &str,
+            side: &str,
+            total_qty: Decimal,
+            duration: Duration,
+            slices: u32,
+        ) -> Self {
+            assert!(slices > 0, "a TWAP needs at least 1 slice.");
+            assert!(
+                ["BUY", "SELL"].contains(&side),
+                "TWAP side must be `BUY` or `SELL`."
+            );
+            TwapExecutor {
+                symbol: symbol.to_string(),
+                side: side.to_string(),
+                total_qty: total_qty,
+                duration: duration,
+                slices: slices,
+                client_id_prefix: format!("twap_{}", Utc::now().timestamp_millis()),
+                arrival_price: Decimal::ZERO,
+                response_fills: HashMap::new(),
+                stream_fills: HashMap::new(),
+                orders: Vec::new(),
+            }
+        }
+
+        // Child orders are sent as `<prefix>_<slice>`, which is how stream updates are matched.
+        pub fn client_id_prefix(mut self, client_id_prefix: &str) -> Self {
+            self.client_id_prefix = client_id_prefix.to_string();
+            self
+        }
+
+        pub fn interval(&self) -> Duration {
+            self.duration / self.slices
+        }
+
+        // Quantity of slice `index`: its share of the schedule minus what has filled so far, so
+        // rounding to the lot size or a short fill is made up by the next slice.
+        pub fn slice_qty(&self, index: u32) -> Decimal {
+            let target = self.total_qty * Decimal::from(index + 1) / Decimal::from(self.slices);
+            (target.min(self.total_qty) - self.fill().qty).max(Decimal::ZERO)
+        }
+
+        fn client_order_id(&self, index: u32) -> String {
+            format!("{}_{}", self.client_id_prefix, index)
+        }
+
+        fn owns(&self, client_order_id: &str) -> bool {
+            client_order_id
+                .strip_prefix(&self.client_id_prefix)
+                .map_or(false, |a| a.starts_with('_'))
+        }
+
+        fn record_response(&mut self, client_order_id: String, response: Value) {
+            let qty = decimal_field(&response, "executedQty").unwrap_or_default();
+            let quote = decimal_field(&response, "cummulativeQuoteQty")
+                .or_else(|| decimal_field(&response, "cumQuote"))
+                .unwrap_or_else(|| decimal_field(&response, "avgPrice").unwrap_or_default() * qty);
+            self.response_fills.insert(
+                client_order_id,
+                Fill {
+                    qty: qty,
+                    quote: quote,
+                },
+            );
+            self.orders.push(response);
+        }
+
+        // Applies a user stream execution update for one of this executor's child orders;
+        // updates for other orders are ignored. Returns whether it was applied.
+        pub fn apply_update(&mut self, update: &OrderUpdate) -> bool {
+            if update.symbol != self.symbol || !self.owns(&update.client_order_id) {
+                return false;
+            }
+            let fill = self
+                .stream_fills
+                .entry(update.client_order_id.clone())
+                .or_default();
+            // Updates can repeat after a reconnect, so only the quantity beyond what was seen counts.
+            if update.cum_filled_qty > fill.qty {
+                fill.quote += (update.cum_filled_qty - fill.qty) * update.last_filled_price;
+                fill.qty = update.cum_filled_qty;
+            }
+            true
+        }
+
+        // Total of all child orders; per order the source that has seen more of the fill wins,
+        // since stream updates can still be in flight when the response comes back.
+        pub fn fill(&self) -> Fill {
+            let mut fills = self.response_fills.clone();
+            for (key, value) in &self.stream_fills {
+                let fill = fills.entry(key.clone()).or_default();
+                if value.qty > fill.qty {
+                    *fill = *value;
+                }
+            }
+            fills.values().fold(Fill::default(), |total, a| Fill {
+                qty: total.qty + a.qty,
+                quote: total.quote + a.quote,
+            })
+        }
+
+        pub fn report(&self) -> TwapReport {
+            let fill = self.fill();
+            let avg_price = if fill.qty.is_zero() {
+                Decimal::ZERO
+            } else {
+                fill.quote / fill.qty
+            };
+            let slippage_bps = if avg_price.is_zero() || self.arrival_price.is_zero() {
+                Decimal::ZERO
+            } else {
+                let sign = if self.side == "BUY" {
+                    Decimal::ONE
+                } else {
+                    Decimal::NEGATIVE_ONE
+                };
+                sign * (avg_price - self.arrival_price) / self.arrival_price * Decimal::from(10000)
+            };
+            TwapReport {
+                symbol: self.symbol.clone(),
+                side: self.side.clone(),
+                target_qty: self.total_qty,
+                filled_qty: fill.qty,
+                avg_price: avg_price,
+                arrival_price: self.arrival_price,
+                slippage_bps: slippage_bps,
+                orders: self.orders.clone(),
+            }
+        }
+    }
+
+    // Keeps a protective stop `trail` away from the best price seen since it started: below the
+    // highest price for a SELL stop (long position), above the lowest for a BUY stop. The stop
+    // only ever moves in the position's favour, and only by at least `min_step`.
+    #[derive(Debug, Clone)]
+    pub struct TrailingStop {
+        pub symbol: String,
+        pub side: String,
+        pub qty: Decimal,
+        pub trail: Decimal,
+        pub trail_percent: bool,
+        pub min_step: Decimal,
+        best_price: Option<Decimal>,
+        stop_price: Option<Decimal>,
+        order: Option<Value>,
+        // The stop price `order` was placed at.
+        order_stop_price: Option<Decimal>,
+        next_id: u64,
+    }
+
+    impl TrailingStop {
+        // `side` is the side of the stop order, i.e. SELL to protect a long position.
+        pub fn new(symbol: &str, side: &str, qty: Decimal, trail: Decimal) -> Self {
+            assert!(
+                ["BUY", "SELL"].contains(&side),
+                "trailing stop side must be `BUY` or `SELL`."
+            );
+            TrailingStop {
+                symbol: symbol.to_string(),
+                side: side.to_string(),
+                qty: qty,
+                trail: trail,
+                trail_percent: false,
+                min_step: Decimal::ZERO,
+                best_price: None,
+                stop_price: None,
+                order: None,
+                order_stop_price: None,
+                next_id: 0,
+            }
+        }
+
+        // Reads `trail` as a percentage of the best price instead of a price distance.
+        pub fn trail_percent(mut self, trail_percent: bool) -> Self {
+            self.trail_percent = trail_percent;
+            self
+        }
+
+        // Skips moves smaller than `min_step`, each move costs a cancel and a new order.
+        pub fn min_step(mut self, min_step: Decimal) -> Self {
+            self.min_step = min_step;
+            self
+        }
+
+        pub fn stop_price(&self) -> Option<Decimal> {
+            self.stop_price
+        }
+
+        // The live stop order as the exchange returned it.
+        pub fn order(&self) -> Option<&Value> {
+            self.order.as_ref()
+        }
+
+        // Returns the new stop price when `price` moves the stop.
+        pub fn on_price(&mut self, price: Decimal) -> Option<Decimal> {
+            let is_sell = self.side == "SELL";
+            let best = match self.best_price {
+                Some(best) if (is_sell && best >= price) || (!is_sell && best <= price) => best,
+                _ => price,
+            };
+            self.best_price = Some(best);
+            let distance = if self.trail_percent {
+                best * self.trail / Decimal::from(100)
+            } else {
+                self.trail
+            };
+            let candidate = if is_sell {
+                best - distance
+            } else {
+                best + distance
+            };
+            let moved = match self.stop_price {
+                Some(stop) if is_sell => candidate > stop && candidate - stop >= self.min_step,
+                Some(stop) => candidate < stop && stop - candidate >= self.min_step,
+                None => true,
+            };
+            if moved {
+                self.stop_price = Some(candidate);
+                Some(candidate)
+            } else {
+                None
+            }
+        }
+    }
+
+    // A take-profit and a stop leg closing the same futures position; whichever fills first
+    // cancels the other. Both are reduce-only conditional market orders.
+    #[derive(Debug, Clone)]
+    pub struct EmulatedOco {
+        pub symbol: String,
+        pub side: String,
+        pub qty: Decimal,
+        pub take_profit_price: Decimal,
+        pub stop_price: Decimal,
+        pub cancel_retries: u32,
+        client_id_prefix: String,
+        orders: Vec<Value>,
+        filled_leg: Option<String>,
+        done: bool,
+    }
+
+    impl EmulatedOco {
+        // `side` closes the position, i.e. SELL for a long one.
+        pub fn new(
+            symbol: &str,
+            side: &str,
+            qty: Decimal,
+            take_profit_price: Decimal,
+            stop_price: Decimal,
+        ) -> Self {
+            assert!(
+                ["BUY", "SELL"].contains(&side),
+                "OCO side must be `BUY` or `SELL`."
+            );
+            EmulatedOco {
+                symbol: symbol.to_string(),
+                side: side.to_string(),
+                qty: qty,
+                take_profit_price: take_profit_price,
+                stop_price: stop_price,
+                cancel_retries: 3,
+                client_id_prefix: format!("oco_{}", Utc::now().timestamp_millis()),
+                orders: Vec::new(),
+                filled_leg: None,
+                done: false,
+            }
+        }
+
+        pub fn cancel_retries(mut self, cancel_retries: u32) -> Self {
+            self.cancel_retries = cancel_retries;
+            self
+        }
+
+        pub fn take_profit_client_id(&self) -> String {
+            format!("{}_tp", self.client_id_prefix)
+        }
+
+        pub fn stop_client_id(&self) -> String {
+            format!("{}_sl", self.client_id_prefix)
+        }
+
+        // Client order id of the leg that filled, once one did, partly or fully.
+        pub fn filled_leg(&self) -> Option<&String> {
+            self.filled_leg.as_ref()
+        }
+
+        // Whether one leg is finished and the other has been canceled, or kept for the rest of a
+        // partly filled one.
+        pub fn is_done(&self) -> bool {
+            self.done
+        }
+
+        pub fn orders(&self) -> &Vec<Value> {
+            &self.orders
+        }
+
+        fn other_leg(&self, client_order_id: &str) -> Option<String> {
+            if client_order_id == self.take_profit_client_id() {
+                Some(self.stop_client_id())
+            } else if client_order_id == self.stop_client_id() {
+                Some(self.take_profit_client_id())
+            } else {
+                None
+            }
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        async fn twap_start(&self, executor: &mut TwapExecutor) -> Result<(), Box<dyn Error>> {
+            let price = self.get_price(&executor.symbol).await?;
+            executor.arrival_price = decimal_field(&price, "price").ok_or_else(|| {
+                format!(
+                    "Can't get the price of `{}` from `{price}`.",
+                    executor.symbol
+                )
+            })?;
+            Ok(())
+        }
+
+        async fn twap_slice(
+            &self,
+            executor: &mut TwapExecutor,
+            index: u32,
+        ) -> Result<(), Box<dyn Error>> {
+            let qty = executor.slice_qty(index);
+            if qty.is_zero() {
+                return Ok(());
+            }
+            let client_order_id = executor.client_order_id(index);
+            let order = NewOrder::new(&executor.symbol, &executor.side, "MARKET")
+                .quantity(qty)
+                .client_order_id(&client_order_id)
+                .response_type("RESULT")
+                .auto_round(true);
+            // A rejected slice stops the TWAP rather than counting as an empty fill; `report` still
+            // covers the slices that went through.
+            let response = match self.place_order(&order).await {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!(client_order_id = %client_order_id, error = %e, "TWAP slice rejected, stopping");
+                    return Err(e);
+                }
+            };
+            executor.record_response(client_order_id, response);
+            Ok(())
+        }
+
+        // Places the slices one interval apart and reports the fills from the order responses.
+        pub async fn run_twap(
+            &self,
+            executor: &mut TwapExecutor,
+        ) -> Result<TwapReport, Box<dyn Error>> {
+            self.twap_start(executor).await?;
+            for index in 0..executor.slices {
+                if index > 0 {
+                    self.transport.0.sleep(executor.interval()).await;
+                }
+                self.twap_slice(executor, index).await?;
+            }
+            Ok(executor.report())
+        }
+
+        // Like `run_twap`, and applies the execution updates from `stream_user_data` between
+        // slices; other user data events are dropped.
+        #[cfg(feature = "websocket")]
+        pub async fn run_twap_with_stream(
+            &self,
+            executor: &mut TwapExecutor,
+            user_data: &mut mpsc::Receiver<UserDataEvent>,
+        ) -> Result<TwapReport, Box<dyn Error>> {
+            let drain = |executor: &mut TwapExecutor,
+                         user_data: &mut mpsc::Receiver<UserDataEvent>| {
+                while let Ok(event) = user_data.try_recv() {
+                    if let UserDataEvent::Event(event) = event {
+                        if let Some(update) = OrderUpdate::from_event(&event) {
+                            executor.apply_update(&update);
+                        }
+                    }
+                }
+            };
+            self.twap_start(executor).await?;
+            for index in 0..executor.slices {
+                if index > 0 {
+                    self.transport.0.sleep(executor.interval()).await;
+                    drain(executor, user_data);
+                }
+                self.twap_slice(executor, index).await?;
+            }
+            drain(executor, user_data);
+            Ok(executor.report())
+        }
+
+        async fn place_trailing_stop(
+            &self,
+            stop: &mut TrailingStop,
+            stop_price: Decimal,
+        ) -> Result<Value, Box<dyn Error>> {
+            // Spot STOP_LOSS fires a market order at `stopPrice`; futures closes with STOP_MARKET.
+            let order_type = if self.account_type == "spot" {
+                "STOP_LOSS"
+            } else {
+                "STOP_MARKET"
+            };
+            stop.next_id += 1;
+            let mut order = NewOrder::new(&stop.symbol, &stop.side, order_type)
+                .quantity(stop.qty)
+                .stop_price(stop_price)
+                .client_order_id(&format!(
+                    "tstop_{}_{}",
+                    Utc::now().timestamp_millis(),
+                    stop.next_id
+                ))
+                .auto_round(true);
+            if self.account_type != "spot" {
+                order = order.reduce_only(true);
+            }
+            let response = self.place_order(&order).await?;
+            stop.order = Some(response.clone());
+            stop.order_stop_price = Some(stop_price);
+            Ok(response)
+        }
+
+        // Moves the stop to `stop_price`. Returns false when the old stop is gone from the book,
+        // i.e. it has triggered (or was canceled) and there is nothing left to trail. When the new
+        // stop is rejected the old one is placed again before the error is returned.
+        pub async fn move_trailing_stop(
+            &self,
+            stop: &mut TrailingStop,
+            stop_price: Decimal,
+        ) -> Result<bool, Box<dyn Error>> {
+            let previous = stop.order_stop_price;
+            if let Some(order) = &stop.order {
+                let order_id = order["orderId"].to_string();
+                match self.cancel_order(&stop.symbol, &order_id, false).await {
+                    Ok(_) => {
+                        stop.order = None;
+                        stop.order_stop_price = None;
+                    }
+                    Err(e) if e.downcast_ref::<BinanceError>().map(|a| a.code) == Some(-2011) => {
+                        stop.order = None;
+                        stop.order_stop_price = None;
+                        return Ok(false);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            // As text, so the rejection can be kept across placing the old stop again.
+            let error = match self.place_trailing_stop(stop, stop_price).await {
+                Ok(_) => return Ok(true),
+                Err(e) => e.to_string(),
+            };
+            // The old stop is canceled already, without it the position is unprotected.
+            let previous = match previous {
+                Some(previous) => previous,
+                None => return Err(error.into()),
+            };
+            tracing::warn!(error = %error, stop_price = %previous, "restoring the previous trailing stop");
+            if let Err(e) = self.place_trailing_stop(stop, previous).await {
+                return Err(format!(
+                    "Moving the trailing stop failed: {error}; placing it again at {previous} failed too, the position has no stop: {e}"
+                )
+                .into());
+            }
+            // So the next price retries the move.
+            stop.stop_price = Some(previous);
+            Err(error.into())
+        }
+
+        // Trails the stop with `prices` (e.g. `stream_last_price`) until the stop is gone from the
+        // book or the channel closes; prices that queued up during a move cost one move at most.
+        pub async fn run_trailing_stop(
+            &self,
+            stop: &mut TrailingStop,
+            prices: &mut mpsc::Receiver<Decimal>,
+        ) -> Result<(), Box<dyn Error>> {
+            while let Some(price) = prices.recv().await {
+                let mut target = stop.on_price(price);
+                while let Ok(price) = prices.try_recv() {
+                    target = stop.on_price(price).or(target);
+                }
+                if let Some(stop_price) = target {
+                    if !self.move_trailing_stop(stop, stop_price).await? {
+                        return Ok(());
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // Places both legs; if the second one is rejected the first is canceled again.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn place_emulated_oco(
+            &self,
+            oco: &mut EmulatedOco,
+        ) -> Result<Vec<Value>, Box<dyn Error>> {
+            if self.account_type != "swap" {
+                return Err("Only `swap` can emulate OCO.".into());
+            }
+            let leg = |order_type: &str, stop_price: Decimal, client_order_id: &str| {
+                NewOrder::new(&oco.symbol, &oco.side, order_type)
+                    .quantity(oco.qty)
+                    .stop_price(stop_price)
+                    .reduce_only(true)
+                    .client_order_id(client_order_id)
+                    .auto_round(true)
+            };
+            let take_profit = leg(
+                "TAKE_PROFIT_MARKET",
+                oco.take_profit_price,
+                &oco.take_profit_client_id(),
+            );
+            let stop = leg("STOP_MARKET", oco.stop_price, &oco.stop_client_id());
+            let first = self.place_order(&take_profit).await?;
+            // As text, so the rejection can be kept across the cancel.
+            let second = self.place_order(&stop).await.map_err(|e| e.to_string());
+            let second = match second {
+                Ok(second) => second,
+                Err(e) => {
+                    let canceled = self
+                        .cancel_order(&oco.symbol, &first["orderId"].to_string(), false)
+                        .await;
+                    return Err(match canceled {
+                        Ok(_) => e.into(),
+                        Err(cancel_error) => format!(
+                            "{e}; canceling take profit {} failed too: {cancel_error}",
+                            first["orderId"]
+                        )
+                        .into(),
+                    });
+                }
+            };
+            oco.orders = vec![first, second];
+            Ok(oco.orders.clone())
+        }
+
+        // Cancels the leg with `client_order_id`, retrying with a growing pause; a leg that is
+        // already gone counts as canceled.
+        #[cfg(feature = "usdm-futures")]
+        async fn cancel_oco_leg(
+            &self,
+            oco: &EmulatedOco,
+            client_order_id: &str,
+        ) -> Result<(), Box<dyn Error>> {
+            // `orders` holds the take profit, then the stop.
+            let leg = (client_order_id == oco.stop_client_id()) as usize;
+            let order_id = oco
+                .orders
+                .get(leg)
+                .map(|a| a["orderId"].to_string())
+                .ok_or_else(|| format!("OCO leg `{client_order_id}` was never placed."))?;
+            let mut attempt = 0;
+            loop {
+                let error = match self.cancel_order(&oco.symbol, &order_id, false).await {
+                    Ok(_) => return Ok(()),
+                    Err(e) if e.downcast_ref::<BinanceError>().map(|a| a.code) == Some(-2011) => {
+                        return Ok(())
+                    }
+                    Err(e) if attempt >= oco.cancel_retries => return Err(e),
+                    Err(e) => e.to_string(),
+                };
+                attempt += 1;
+                tracing::warn!(error = %error, attempt = attempt, "retrying OCO leg cancel");
+                self.transport
+                    .0
+                    .sleep(Duration::from_millis(200 * attempt as u64))
+                    .await;
+            }
+        }
+
+        #[cfg(feature = "usdm-futures")]
+        async fn apply_oco_status(
+            &self,
+            oco: &mut EmulatedOco,
+            client_order_id: &str,
+            status: &str,
+        ) -> Result<bool, Box<dyn Error>> {
+            let other = match oco.other_leg(client_order_id) {
+                Some(other) if !oco.done => other,
+                _ => return Ok(oco.done),
+            };
+            let partly_filled = oco.filled_leg.as_deref() == Some(client_order_id);
+            match status {
+                "FILLED" => oco.filled_leg = Some(client_order_id.to_string()),
+                // The other leg stays for the rest; being reduce-only it closes no more than is
+                // left of the position.
+                "PARTIALLY_FILLED" => {
+                    oco.filled_leg = Some(client_order_id.to_string());
+                    return Ok(false);
+                }
+                // Ended after a partial fill: the other leg is all that guards the rest now.
+                "CANCELED" | "EXPIRED" if partly_filled => {
+                    tracing::warn!(
+                        client_order_id = client_order_id,
+                        "OCO leg ended partly filled, keeping the other leg"
+                    );
+                    oco.done = true;
+                    return Ok(true);
+                }
+                "CANCELED" | "EXPIRED" | "REJECTED" => {}
+                _ => return Ok(false),
+            }
+            self.cancel_oco_leg(oco, &other).await?;
+            oco.done = true;
+            Ok(true)
+        }
+
+        // Once a leg fills completely the other is canceled; a leg canceled or expired from
+        // elsewhere also cancels the other so no half of the bracket is left behind, unless it had
+        // partly filled. Returns `oco.is_done()`.
+        #[cfg(feature = "usdm-futures")]
+        pub async fn handle_oco_update(
+            &self,
+            oco: &mut EmulatedOco,
+            update: &OrderUpdate,
+        ) -> Result<bool, Box<dyn Error>> {
+            if update.symbol != oco.symbol {
+                return Ok(oco.done);
+            }
+            self.apply_oco_status(oco, &update.client_order_id, &update.status)
+                .await
+        }
+
+        // A leg unknown to the exchange, e.g. rejected before it was recorded, counts as rejected.
+        #[cfg(all(feature = "usdm-futures", feature = "websocket"))]
+        async fn oco_leg_status(
+            &self,
+            oco: &EmulatedOco,
+            client_order_id: &str,
+        ) -> Result<String, Box<dyn Error>> {
+            match self.get_order(&oco.symbol, "", client_order_id).await {
+                Ok(order) => Ok(order["status"].as_str().unwrap_or_default().to_string()),
+                Err(e) if e.downcast_ref::<BinanceError>().map(|a| a.code) == Some(-2013) => {
+                    Ok("REJECTED".to_string())
+                }
+                Err(e) => Err(e),
+            }
+        }
+
+        // Drives `handle_oco_update` from `stream_user_data` until one leg has finished. After a
+        // stream gap both legs are looked up over REST in case the fill was missed.
+        #[cfg(all(feature = "usdm-futures", feature = "websocket"))]
+        pub async fn run_emulated_oco(
+            &self,
+            oco: &mut EmulatedOco,
+            user_data: &mut mpsc::Receiver<UserDataEvent>,
+        ) -> Result<(), Box<dyn Error>> {
+            while let Some(event) = user_data.recv().await {
+                match event {
+                    UserDataEvent::Event(event) => {
+                        if let Some(update) = OrderUpdate::from_event(&event) {
+                            if self.handle_oco_update(oco, &update).await? {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    UserDataEvent::Gap { .. } => {
+                        for client_order_id in [oco.take_profit_client_id(), oco.stop_client_id()] {
+                            let status = self.oco_leg_status(oco, &client_order_id).await?;
+                            if self
+                                .apply_oco_status(oco, &client_order_id, &status)
+                                .await?
+                            {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn sell_stop_trails_below_the_highest_price() {
+            let mut stop = TrailingStop::new("BTCUSDT", "SELL", Decimal::ONE, Decimal::from(10));
+            assert_eq!(stop.on_price(Decimal::from(100)), Some(Decimal::from(90)));
+            assert_eq!(stop.on_price(Decimal::from(95)), None);
+            assert_eq!(stop.on_price(Decimal::from(105)), Some(Decimal::from(95)));
+            assert_eq!(stop.on_price(Decimal::from(100)), None);
+            assert_eq!(stop.stop_price(), Some(Decimal::from(95)));
+        }
+
+        #[test]
+        fn buy_stop_trails_a_percentage_above_the_lowest_price() {
+            let mut stop = TrailingStop::new("BTCUSDT", "BUY", Decimal::ONE, Decimal::from(5))
+                .trail_percent(true);
+            assert_eq!(stop.on_price(Decimal::from(100)), Some(Decimal::from(105)));
+            assert_eq!(stop.on_price(Decimal::from(110)), None);
+            assert_eq!(stop.on_price(Decimal::from(90)), Some(Decimal::new(945, 1)));
+            assert_eq!(stop.stop_price(), Some(Decimal::new(945, 1)));
+        }
+
+        #[test]
+        fn moves_smaller_than_min_step_are_skipped() {
+            let mut stop = TrailingStop::new("BTCUSDT", "SELL", Decimal::ONE, Decimal::from(10))
+                .min_step(Decimal::from(2));
+            assert_eq!(stop.on_price(Decimal::from(100)), Some(Decimal::from(90)));
+            assert_eq!(stop.on_price(Decimal::from(101)), None);
+            assert_eq!(stop.stop_price(), Some(Decimal::from(90)));
+            assert_eq!(stop.on_price(Decimal::from(102)), Some(Decimal::from(92)));
+        }
+    }
+}
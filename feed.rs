@@ -0,0 +1,220 @@
+pub mod feed {
+    #[cfg(feature = "websocket")]
+    use crate::binance_api::binance_api::BinanceAPI;
+    #[cfg(feature = "websocket")]
+    use crate::execution::execution::decimal_field;
+    use crate::kline_store::kline_store::KlineStore;
+    use crate::models::models::{AggTrade, DepthSnapshot, Kline, PartialDepth};
+    #[cfg(feature = "websocket")]
+    use crate::public::public::PublicBinanceAPI;
+    use rust_decimal::Decimal;
+    use std::{
+        collections::HashMap,
+        error::Error,
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use tokio::sync::mpsc;
+
+    pub type FeedFuture<'b, T> = Pin<Box<dyn Future<Output = Result<T, Box<dyn Error>>> + 'b>>;
+
+    // The market data a strategy reads, so the same code runs against the live client, a replay
+    // or a paper setup. Subscriptions end when the receiver is dropped.
+    pub trait MarketDataFeed {
+        fn subscribe_trades(&self, symbol: &str) -> mpsc::Receiver<AggTrade>;
+
+        // Closed candles only.
+        fn subscribe_klines(&self, symbol: &str, interval: &str) -> mpsc::Receiver<Kline>;
+
+        // The top `levels` of the book, see `partial_depth_type` for the valid values.
+        fn subscribe_depth(&self, symbol: &str, levels: u32) -> mpsc::Receiver<PartialDepth>;
+
+        fn snapshot_depth(&self, symbol: &str, limit: u32) -> FeedFuture<'_, DepthSnapshot>;
+
+        fn last_price(&self, symbol: &str) -> FeedFuture<'_, Decimal>;
+    }
+
+    #[cfg(feature = "websocket")]
+    impl<'a> MarketDataFeed for BinanceAPI<'a> {
+        fn subscribe_trades(&self, symbol: &str) -> mpsc::Receiver<AggTrade> {
+            self.stream_agg_trades(symbol)
+        }
+
+        fn subscribe_klines(&self, symbol: &str, interval: &str) -> mpsc::Receiver<Kline> {
+            self.stream_klines(symbol, interval)
+        }
+
+        fn subscribe_depth(&self, symbol: &str, levels: u32) -> mpsc::Receiver<PartialDepth> {
+            self.stream_partial_depth(symbol, levels, 100)
+        }
+
+        fn snapshot_depth(&self, symbol: &str, limit: u32) -> FeedFuture<'_, DepthSnapshot> {
+            let symbol = symbol.to_string();
+            Box::pin(async move { self.get_depth(&symbol, limit).await })
+        }
+
+        fn last_price(&self, symbol: &str) -> FeedFuture<'_, Decimal> {
+            let symbol = symbol.to_string();
+            Box::pin(async move {
+                let price = self.get_price(&symbol).await?;
+                decimal_field(&price, "price")
+                    .ok_or_else(|| format!("No price for `{symbol}`.").into())
+            })
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    impl<'a> MarketDataFeed for PublicBinanceAPI<'a> {
+        fn subscribe_trades(&self, symbol: &str) -> mpsc::Receiver<AggTrade> {
+            self.inner().subscribe_trades(symbol)
+        }
+
+        fn subscribe_klines(&self, symbol: &str, interval: &str) -> mpsc::Receiver<Kline> {
+            self.inner().subscribe_klines(symbol, interval)
+        }
+
+        fn subscribe_depth(&self, symbol: &str, levels: u32) -> mpsc::Receiver<PartialDepth> {
+            self.inner().subscribe_depth(symbol, levels)
+        }
+
+        fn snapshot_depth(&self, symbol: &str, limit: u32) -> FeedFuture<'_, DepthSnapshot> {
+            self.inner().snapshot_depth(symbol, limit)
+        }
+
+        fn last_price(&self, symbol: &str) -> FeedFuture<'_, Decimal> {
+            self.inner().last_price(symbol)
+        }
+    }
+
+    // Plays back cached klines and aggTrades through `MarketDataFeed`. Every subscription starts
+    // from the first record; with a `speed` the gaps between records are replayed scaled (2.0 is
+    // twice real time), otherwise records are sent as fast as the consumer reads them. Klines
+    // are sent at their close time. There is no depth to replay, so depth subscriptions end
+    // right away and `snapshot_depth` errors. Subscribing needs a running tokio runtime.
+    #[derive(Debug, Clone, Default)]
+    pub struct ReplayFeed {
+        klines: HashMap<(String, String), Arc<Vec<Kline>>>,
+        trades: HashMap<String, Arc<Vec<AggTrade>>>,
+        speed: Option<f64>,
+        last_prices: Arc<Mutex<HashMap<String, Decimal>>>,
+    }
+
+    impl ReplayFeed {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        pub fn klines(mut self, symbol: &str, interval: &str, klines: Vec<Kline>) -> Self {
+            self.klines.insert(
+                (symbol.to_uppercase(), interval.to_string()),
+                Arc::new(klines),
+            );
+            self
+        }
+
+        pub fn agg_trades(mut self, symbol: &str, trades: Vec<AggTrade>) -> Self {
+            self.trades.insert(symbol.to_uppercase(), Arc::new(trades));
+            self
+        }
+
+        // Klines kept by a `KlineStore`, e.g. after `sync`.
+        pub fn store_klines(
+            self,
+            store: &KlineStore,
+            account_type: &str,
+            symbol: &str,
+            interval: &str,
+        ) -> Result<Self, Box<dyn Error>> {
+            let klines = store.load(account_type, symbol, interval)?;
+            Ok(self.klines(symbol, interval, klines))
+        }
+
+        pub fn speed(mut self, speed: f64) -> Self {
+            assert!(speed > 0.0, "replay speed must be positive.");
+            self.speed = Some(speed);
+            self
+        }
+
+        pub fn as_fast_as_possible(mut self) -> Self {
+            self.speed = None;
+            self
+        }
+
+        fn replay<T: Clone + Send + Sync + 'static>(
+            &self,
+            symbol: &str,
+            records: Option<Arc<Vec<T>>>,
+            time: fn(&T) -> i64,
+            price: fn(&T) -> Decimal,
+        ) -> mpsc::Receiver<T> {
+            // As fast as possible, a small buffer keeps the replay close to the consumer.
+            let (tx, rx) = mpsc::channel(if self.speed.is_some() { 1024 } else { 1 });
+            let records = match records {
+                Some(records) => records,
+                None => {
+                    tracing::warn!(symbol = symbol, "nothing to replay");
+                    return rx;
+                }
+            };
+            let symbol = symbol.to_uppercase();
+            let speed = self.speed;
+            let last_prices = self.last_prices.clone();
+            tokio::spawn(async move {
+                let mut last_time: Option<i64> = None;
+                for record in records.iter() {
+                    if let (Some(speed), Some(last_time)) = (speed, last_time) {
+                        let gap = (time(record) - last_time).max(0) as f64 / 1000.0 / speed;
+                        tokio::time::sleep(Duration::from_secs_f64(gap)).await;
+                    }
+                    last_time = Some(time(record));
+                    last_prices
+                        .lock()
+                        .unwrap()
+                        .insert(symbol.clone(), price(record));
+                    if tx.send(record.clone()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+            rx
+        }
+    }
+
+    impl MarketDataFeed for ReplayFeed {
+        fn subscribe_trades(&self, symbol: &str) -> mpsc::Receiver<AggTrade> {
+            let trades = self.trades.get(&symbol.to_uppercase()).cloned();
+            self.replay(symbol, trades, |a| a.time, |a| a.price)
+        }
+
+        fn subscribe_klines(&self, symbol: &str, interval: &str) -> mpsc::Receiver<Kline> {
+            let klines = self
+                .klines
+                .get(&(symbol.to_uppercase(), interval.to_string()))
+                .cloned();
+            self.replay(symbol, klines, |a| a.close_time, |a| a.close)
+        }
+
+        fn subscribe_depth(&self, _symbol: &str, _levels: u32) -> mpsc::Receiver<PartialDepth> {
+            mpsc::channel(1).1
+        }
+
+        fn snapshot_depth(&self, symbol: &str, _limit: u32) -> FeedFuture<'_, DepthSnapshot> {
+            let e = format!("No depth to replay for `{symbol}`.");
+            Box::pin(async move { Err(e.into()) })
+        }
+
+        // The price of the last record sent on any subscription of `symbol`; as fast as possible
+        // that can be one record ahead of the one being handled.
+        fn last_price(&self, symbol: &str) -> FeedFuture<'_, Decimal> {
+            let symbol = symbol.to_uppercase();
+            Box::pin(async move {
+                match self.last_prices.lock().unwrap().get(&symbol) {
+                    Some(price) => Ok(*price),
+                    None => Err(format!("Nothing replayed for `{symbol}` yet.").into()),
+                }
+            })
+        }
+    }
+}
@@ -0,0 +1,192 @@
+pub mod funding {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::execution::execution::decimal_field;
+    use crate::models::models::{MarkPriceEvent, Position};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    use std::{
+        collections::{HashMap, HashSet},
+        error::Error,
+        sync::Mutex,
+    };
+
+    // How often `run_funding_tracker` reloads positions, in ms.
+    #[cfg(feature = "websocket")]
+    const POSITION_REFRESH_MS: i64 = 60_000;
+
+    // Funding of one symbol; amounts are in the margin asset and positive when received.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct FundingPosition {
+        pub symbol: String,
+        // Net over every position side (hedge mode longs and shorts).
+        pub position_amt: Decimal,
+        pub mark_price: Decimal,
+        pub funding_rate: Decimal,
+        pub next_funding_time: i64,
+        // Sum of the FUNDING_FEE income since the tracker started.
+        pub settled: Decimal,
+        pub last_settlement_time: i64,
+    }
+
+    impl FundingPosition {
+        // What the next settlement pays at the current mark price and rate: longs pay a positive
+        // rate and receive a negative one.
+        pub fn projected_funding(&self) -> Decimal {
+            -self.position_amt * self.mark_price * self.funding_rate
+        }
+    }
+
+    // Funding settled and projected per symbol, fed with positions (`get_position`), mark price
+    // events (`stream_mark_price`) and FUNDING_FEE income records; `run_funding_tracker` does
+    // all three. Income records already added are skipped by their tranId.
+    #[derive(Debug, Clone, Default)]
+    pub struct FundingTracker {
+        positions: HashMap<String, FundingPosition>,
+        // Income is fetched from here on, in ms.
+        since: i64,
+        seen: HashSet<i64>,
+        // Set when a settlement passed and its income is not fetched yet.
+        settlement_due: bool,
+    }
+
+    impl FundingTracker {
+        // Tracks the funding settled from `since_utc` ("%Y-%m-%d %H:%M:%S") on, or from now if "".
+        pub fn new(since_utc: &str) -> Self {
+            let since = if since_utc.is_empty() {
+                Utc::now().timestamp_millis()
+            } else {
+                chrono::NaiveDateTime::parse_from_str(since_utc, "%Y-%m-%d %H:%M:%S")
+                    .map(|a| Utc.from_utc_datetime(&a).timestamp_millis())
+                    .unwrap_or_else(|_| BinanceAPI::panic_not_define("since_utc", since_utc, 0))
+            };
+            FundingTracker {
+                since: since,
+                ..Default::default()
+            }
+        }
+
+        fn entry(&mut self, symbol: &str) -> &mut FundingPosition {
+            self.positions
+                .entry(symbol.to_string())
+                .or_insert_with(|| FundingPosition {
+                    symbol: symbol.to_string(),
+                    ..Default::default()
+                })
+        }
+
+        // Replaces the position amounts with `positions`; symbols missing from it are flat.
+        pub fn set_positions(&mut self, positions: &Vec<Position>) {
+            for position in self.positions.values_mut() {
+                position.position_amt = Decimal::ZERO;
+            }
+            for position in positions {
+                let entry = self.entry(&position.symbol);
+                entry.position_amt += position.position_amt;
+                if entry.mark_price.is_zero() {
+                    entry.mark_price = position.mark_price;
+                }
+            }
+        }
+
+        // Only symbols already tracked are updated, so `!markPrice@arr` can be fed as is.
+        pub fn apply_mark_price(&mut self, event: &MarkPriceEvent) {
+            let position = match self.positions.get_mut(&event.symbol) {
+                Some(position) => position,
+                None => return,
+            };
+            if position.next_funding_time > 0
+                && event.next_funding_time > position.next_funding_time
+                && !position.position_amt.is_zero()
+            {
+                self.settlement_due = true;
+            }
+            position.mark_price = event.mark_price;
+            position.funding_rate = event.funding_rate;
+            position.next_funding_time = event.next_funding_time;
+        }
+
+        // Adds a FUNDING_FEE income record; other income types and records seen before are
+        // ignored.
+        pub fn add_income(&mut self, income: &Value) {
+            if income["incomeType"].as_str() != Some("FUNDING_FEE") {
+                return;
+            }
+            let tran_id = income["tranId"].as_i64().unwrap_or_default();
+            if !self.seen.insert(tran_id) {
+                return;
+            }
+            let time = income["time"].as_i64().unwrap_or_default();
+            let symbol = income["symbol"].as_str().unwrap_or_default().to_string();
+            let entry = self.entry(&symbol);
+            entry.settled += decimal_field(income, "income").unwrap_or_default();
+            entry.last_settlement_time = entry.last_settlement_time.max(time);
+            self.since = self.since.max(time);
+        }
+
+        pub fn position(&self, symbol: &str) -> Option<&FundingPosition> {
+            self.positions.get(&symbol.to_uppercase())
+        }
+
+        pub fn positions(&self) -> Vec<&FundingPosition> {
+            let mut positions: Vec<_> = self.positions.values().collect();
+            positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+            positions
+        }
+
+        pub fn total_settled(&self) -> Decimal {
+            self.positions.values().map(|a| a.settled).sum()
+        }
+
+        // The next settlement of every open position at the current rates.
+        pub fn projected_total(&self) -> Decimal {
+            self.positions.values().map(|a| a.projected_funding()).sum()
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Reloads positions and the FUNDING_FEE income since the last record added.
+        pub async fn sync_funding(
+            &self,
+            tracker: &Mutex<FundingTracker>,
+        ) -> Result<(), Box<dyn Error>> {
+            let positions = self.get_position("").await?;
+            let since = tracker.lock().unwrap().since;
+            let incomes = self
+                .get_income_history("", "FUNDING_FEE", since, "")
+                .await?;
+            let mut tracker = tracker.lock().unwrap();
+            tracker.set_positions(&positions);
+            for income in &incomes {
+                tracker.add_income(income);
+            }
+            tracker.settlement_due = false;
+            Ok(())
+        }
+
+        // Keeps `tracker` up to date until the mark price stream ends or a request fails: rates
+        // from `!markPrice@arr`, positions every minute and income after each settlement. Read
+        // the tracker from another task through the mutex.
+        #[cfg(feature = "websocket")]
+        pub async fn run_funding_tracker(
+            &self,
+            tracker: &Mutex<FundingTracker>,
+        ) -> Result<(), Box<dyn Error>> {
+            self.sync_funding(tracker).await?;
+            let mut next_sync = Utc::now().timestamp_millis() + POSITION_REFRESH_MS;
+            let mut mark_prices = self.stream_mark_price("");
+            while let Some(event) = mark_prices.recv().await {
+                let settlement_due = {
+                    let mut tracker = tracker.lock().unwrap();
+                    tracker.apply_mark_price(&event);
+                    tracker.settlement_due
+                };
+                if settlement_due || event.event_time >= next_sync {
+                    self.sync_funding(tracker).await?;
+                    next_sync = event.event_time + POSITION_REFRESH_MS;
+                }
+            }
+            Err("Mark price stream ended.".into())
+        }
+    }
+}
@@ -0,0 +1,225 @@
+pub mod history_export {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::TimeInput;
+    use chrono::Utc;
+    use serde::{Deserialize, Serialize};
+    use std::{
+        collections::HashMap,
+        error::Error,
+        io::{Cursor, Read},
+        time::Duration,
+    };
+
+    // What a futures history export holds.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ExportKind {
+        Orders,
+        Trades,
+        // The income history: funding fees, commissions, realized pnl, transfers...
+        Transactions,
+    }
+
+    impl ExportKind {
+        fn path(&self) -> &'static str {
+            match self {
+                ExportKind::Orders => "/fapi/v1/order/asyn",
+                ExportKind::Trades => "/fapi/v1/trade/asyn",
+                ExportKind::Transactions => "/fapi/v1/income/asyn",
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ExportStatus {
+        pub download_id: String,
+        // `processing` or `completed`.
+        pub status: String,
+        // Empty until completed.
+        #[serde(default)]
+        pub url: String,
+        #[serde(default)]
+        pub expiration_timestamp: i64,
+        #[serde(default)]
+        pub is_expired: Option<bool>,
+    }
+
+    impl ExportStatus {
+        pub fn is_completed(&self) -> bool {
+            self.status == "completed" && !self.url.is_empty()
+        }
+    }
+
+    // A downloaded export: the CSV header and its rows, as text.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct HistoryExport {
+        pub headers: Vec<String>,
+        pub rows: Vec<Vec<String>>,
+    }
+
+    impl HistoryExport {
+        pub fn parse(content: &str) -> Self {
+            let mut lines = content
+                .trim_start_matches('\u{feff}')
+                .lines()
+                .filter(|a| !a.trim().is_empty())
+                .map(csv_fields);
+            HistoryExport {
+                headers: lines.next().unwrap_or_default(),
+                rows: lines.collect(),
+            }
+        }
+
+        pub fn column(&self, header: &str) -> Option<usize> {
+            self.headers
+                .iter()
+                .position(|a| a.eq_ignore_ascii_case(header))
+        }
+
+        // Each row as header -> value.
+        pub fn records(&self) -> Vec<HashMap<String, String>> {
+            self.rows
+                .iter()
+                .map(|row| {
+                    self.headers
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().cloned())
+                        .collect()
+                })
+                .collect()
+        }
+    }
+
+    // One CSV line; fields may be quoted, with `""` for a quote.
+    fn csv_fields(line: &str) -> Vec<String> {
+        let mut fields = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = line.trim_end_matches('\r').chars().peekable();
+        while let Some(a) = chars.next() {
+            match a {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                _ => field.push(a),
+            }
+        }
+        fields.push(field);
+        fields
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Asks Binance to prepare an export of up to a year and returns its download id. Binance
+        // allows 5 exports a month per account, the website's included.
+        pub async fn request_history_export(
+            &self,
+            kind: ExportKind,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<String, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can export history."
+            );
+            let url = self.generate_exchange_url(("", kind.path()));
+            let start_time = start_time_utc.into().millis()?.unwrap_or(0);
+            let end_time = end_time_utc
+                .into()
+                .millis()?
+                .unwrap_or(Utc::now().timestamp_millis());
+            let mut param_map = HashMap::new();
+            param_map.insert("startTime".to_string(), start_time.to_string());
+            param_map.insert("endTime".to_string(), end_time.to_string());
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            let parsed: serde_json::Value = BinanceAPI::parse_response(parsed)?;
+            match parsed["downloadId"].as_str() {
+                Some(download_id) => Ok(download_id.to_string()),
+                None => Err(format!("No download id in `{parsed}`.").into()),
+            }
+        }
+
+        pub async fn get_history_export(
+            &self,
+            kind: ExportKind,
+            download_id: &str,
+        ) -> Result<ExportStatus, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can export history."
+            );
+            let url = self.generate_exchange_url(("", &format!("{}/id", kind.path())));
+            let mut param_map = HashMap::new();
+            param_map.insert("downloadId".to_string(), download_id.to_string());
+            let parsed = self
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+
+        // Polls every `poll_every` until the export is ready; wrap it in `with_deadline` to give
+        // up after a while.
+        pub async fn wait_history_export(
+            &self,
+            kind: ExportKind,
+            download_id: &str,
+            poll_every: Duration,
+        ) -> Result<ExportStatus, Box<dyn Error>> {
+            loop {
+                let status = self.get_history_export(kind, download_id).await?;
+                if status.is_completed() {
+                    return Ok(status);
+                }
+                self.transport.0.sleep(poll_every).await;
+            }
+        }
+
+        // Downloads a completed export; the link is zipped or plain CSV.
+        pub async fn download_history_export(
+            &self,
+            status: &ExportStatus,
+        ) -> Result<HistoryExport, Box<dyn Error>> {
+            if !status.is_completed() {
+                return Err(format!("Export `{}` is not ready.", status.download_id).into());
+            }
+            if status.is_expired == Some(true) {
+                return Err(format!("Export `{}` has expired.", status.download_id).into());
+            }
+            let res = reqwest::get(&status.url).await?;
+            if !res.status().is_success() {
+                return Err(format!("Can't download `{}`: {}.", status.url, res.status()).into());
+            }
+            let bytes = res.bytes().await?;
+            let mut content = String::new();
+            if bytes.starts_with(b"PK\x03\x04") {
+                let mut zip = zip::ZipArchive::new(Cursor::new(bytes))?;
+                zip.by_index(0)?.read_to_string(&mut content)?;
+            } else {
+                content = String::from_utf8(bytes.to_vec())?;
+            }
+            Ok(HistoryExport::parse(&content))
+        }
+
+        // Requests, waits for and downloads an export in one go.
+        pub async fn export_history(
+            &self,
+            kind: ExportKind,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+            poll_every: Duration,
+        ) -> Result<HistoryExport, Box<dyn Error>> {
+            let download_id = self
+                .request_history_export(kind, start_time_utc, end_time_utc)
+                .await?;
+            let status = self
+                .wait_history_export(kind, &download_id, poll_every)
+                .await?;
+            self.download_history_export(&status).await
+        }
+    }
+}
@@ -0,0 +1,84 @@
+pub mod kline_store {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::{from_csv, CsvRecord, Kline, TimeInput};
+    use std::{
+        error::Error,
+        fs,
+        io::Write,
+        path::{Path, PathBuf},
+    };
+
+    pub struct KlineStore {
+        dir: PathBuf,
+    }
+
+    impl KlineStore {
+        pub fn new(dir: &str) -> Result<Self, Box<dyn Error>> {
+            fs::create_dir_all(dir)?;
+            Ok(KlineStore {
+                dir: Path::new(dir).to_path_buf(),
+            })
+        }
+
+        fn file_path(&self, account_type: &str, symbol: &str, interval: &str) -> PathBuf {
+            self.dir
+                .join(format!("{}_{}_{}.csv", account_type, symbol, interval))
+        }
+
+        pub fn load(
+            &self,
+            account_type: &str,
+            symbol: &str,
+            interval: &str,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            let path = self.file_path(account_type, symbol, interval);
+            if !path.exists() {
+                return Ok(vec![]);
+            }
+            from_csv(path.to_str().unwrap())
+        }
+
+        pub fn append(
+            &self,
+            account_type: &str,
+            symbol: &str,
+            interval: &str,
+            klines: &Vec<Kline>,
+        ) -> Result<(), Box<dyn Error>> {
+            let path = self.file_path(account_type, symbol, interval);
+            let is_new = !path.exists();
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            if is_new {
+                writeln!(file, "{}", Kline::HEADER)?;
+            }
+            for k in klines {
+                writeln!(file, "{}", k.to_csv_row())?;
+            }
+            Ok(())
+        }
+
+        pub async fn sync(
+            &self,
+            bn_api: &BinanceAPI<'_>,
+            symbol: &str,
+            interval: &str,
+            start_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<Kline>, Box<dyn Error>> {
+            let mut klines = self.load(bn_api.account_type, symbol, interval)?;
+            // Stored close times are in the client's time unit, `TimeInput` is in ms.
+            let start_time_utc = match klines.last() {
+                Some(last) => TimeInput::Millis(bn_api.time_unit().to_millis(last.close_time) + 1),
+                None => start_time_utc.into(),
+            };
+            let new_klines = bn_api
+                .history_klines(symbol, interval, start_time_utc, "")
+                .await?;
+            self.append(bn_api.account_type, symbol, interval, &new_klines)?;
+            klines.extend(new_klines);
+            Ok(klines)
+        }
+    }
+}
@@ -0,0 +1,162 @@
+pub mod logging {
+    use crate::transport::transport::{redact, HttpRequest, Transport, TransportFuture};
+    use serde::Serialize;
+    use std::{
+        fmt::Debug,
+        fs,
+        future::Future,
+        io::Write,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+    use url::Url;
+
+    // One REST call as seen by `LoggingTransport`. Signatures and listen keys in `params` are
+    // redacted and headers, which carry the api key, are left out.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct RequestLog {
+        pub time: i64,
+        pub method: String,
+        pub path: String,
+        pub params: Vec<(String, String)>,
+        // None if the request never got a response.
+        pub status: Option<u16>,
+        pub latency_ms: u64,
+        pub error_code: Option<i64>,
+        pub error: Option<String>,
+    }
+
+    impl RequestLog {
+        fn new(request: &HttpRequest) -> Self {
+            let url_params = Url::parse(&request.url)
+                .map(|a| a.query_pairs().into_owned().collect::<Vec<_>>())
+                .unwrap_or_default();
+            let params = url_params
+                .iter()
+                .chain(request.query.iter())
+                .map(|(key, value)| match key.to_lowercase().as_str() {
+                    "signature" | "listenkey" => (key.clone(), redact(value).to_string()),
+                    _ => (key.clone(), value.clone()),
+                })
+                .collect();
+            RequestLog {
+                time: chrono::Utc::now().timestamp_millis(),
+                method: request.method.clone(),
+                path: request.path(),
+                params: params,
+                status: None,
+                latency_ms: 0,
+                error_code: None,
+                error: None,
+            }
+        }
+    }
+
+    pub trait LogSink: Debug + Send + Sync {
+        fn record(&self, log: &RequestLog);
+    }
+
+    // Emits every request as a `tracing` event, at warn level when it failed.
+    #[derive(Debug, Default)]
+    pub struct TracingSink;
+
+    impl LogSink for TracingSink {
+        fn record(&self, log: &RequestLog) {
+            let params = serde_json::to_string(&log.params).unwrap_or_default();
+            if log.error.is_some() || log.error_code.is_some() {
+                tracing::warn!(
+                    method = %log.method,
+                    path = %log.path,
+                    params = %params,
+                    status = log.status,
+                    latency_ms = log.latency_ms,
+                    error_code = log.error_code,
+                    error = log.error.as_deref().unwrap_or_default(),
+                    "request log"
+                );
+            } else {
+                tracing::info!(
+                    method = %log.method,
+                    path = %log.path,
+                    params = %params,
+                    status = log.status,
+                    latency_ms = log.latency_ms,
+                    "request log"
+                );
+            }
+        }
+    }
+
+    // Appends one JSON line per request to a file.
+    #[derive(Debug)]
+    pub struct FileSink {
+        file: Mutex<fs::File>,
+    }
+
+    impl FileSink {
+        pub fn open(path: &str) -> Result<Self, std::io::Error> {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            Ok(FileSink {
+                file: Mutex::new(file),
+            })
+        }
+    }
+
+    impl LogSink for FileSink {
+        fn record(&self, log: &RequestLog) {
+            let line = serde_json::to_string(log).unwrap_or_default();
+            if let Err(e) = writeln!(self.file.lock().unwrap(), "{line}") {
+                tracing::warn!(error = %e, "can't write request log");
+            }
+        }
+    }
+
+    // Wraps a transport and hands a `RequestLog` of every request to `sink`.
+    #[derive(Debug)]
+    pub struct LoggingTransport<T> {
+        inner: T,
+        sink: Arc<dyn LogSink>,
+    }
+
+    impl<T: Transport> LoggingTransport<T> {
+        pub fn new(inner: T, sink: Arc<dyn LogSink>) -> Self {
+            LoggingTransport {
+                inner: inner,
+                sink: sink,
+            }
+        }
+    }
+
+    impl<T: Transport> Transport for LoggingTransport<T> {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async move {
+                let mut log = RequestLog::new(&request);
+                let started = Instant::now();
+                let res = self.inner.send(request).await;
+                log.latency_ms = started.elapsed().as_millis() as u64;
+                match &res {
+                    Ok(res) => {
+                        log.status = Some(res.status);
+                        let body: serde_json::Value =
+                            serde_json::from_str(&res.body).unwrap_or_default();
+                        if let Some(code) = body["code"].as_i64().filter(|a| *a < 0) {
+                            log.error_code = Some(code);
+                            log.error = body["msg"].as_str().map(|a| a.to_string());
+                        }
+                    }
+                    Err(e) => log.error = Some(e.to_string()),
+                }
+                self.sink.record(&log);
+                res
+            })
+        }
+
+        fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            self.inner.sleep(duration)
+        }
+    }
+}
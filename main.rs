@@ -1,36 +1,162 @@
+mod bar_builder;
+#[cfg(feature = "usdm-futures")]
+mod basis;
 mod binance_api;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod builder;
+mod cassette;
+mod config;
+#[cfg(feature = "wallet")]
+mod convert;
+#[cfg(feature = "polars")]
+mod dataframe;
+mod deadline;
+mod downloader;
+mod execution;
+mod feed;
+#[cfg(feature = "usdm-futures")]
+mod funding;
+#[cfg(feature = "usdm-futures")]
+mod history_export;
+mod kline_store;
+mod logging;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod models;
+#[cfg(feature = "websocket")]
+mod notifier;
+mod order_book;
+mod order_tracker;
+mod paper;
+mod partial_book;
+mod pnl;
+mod portfolio;
+mod public;
+mod quote_cache;
+mod rate_limiter;
+#[cfg(feature = "websocket")]
+mod recorder;
+mod risk;
+#[cfg(feature = "usdm-futures")]
+mod rollover;
+mod scheduler;
+mod signer;
+#[cfg(feature = "wallet")]
+mod simple_earn;
+mod sizing;
+mod strategy;
+#[cfg(feature = "websocket")]
+mod stream;
+mod symbol_registry;
+mod transport;
+#[cfg(feature = "websocket")]
+mod ws_deflate;
+#[cfg(feature = "websocket")]
+mod ws_health;
 use binance_api::binance_api::BinanceAPI;
-use std::error::Error;
+use config::config::ClientConfig;
+use models::models::{CsvRecord, Kline, NewOrder};
+use std::{env, error::Error};
+
+const USAGE: &str = "usage: binance-cli [--config <file.toml>] [--swap] <command> [args]
+
+commands:
+  price <symbol>
+  klines <symbol> <interval> --from <date> [--to <date>]
+  order <buy|sell> <symbol> <quantity> [price] [--tif <GTC|IOC|FOK>]
+  cancel <symbol> <order_id>
+  cancel-all <symbol>
+  open-orders [symbol]
+  balance
+  account
+
+without --config, keys are read from BINANCE_API_KEY and BINANCE_SECRET_KEY; dates are `YYYY-MM-DD[ HH:MM:SS]` in UTC.";
+
+fn print_json(value: &serde_json::Value) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+fn utc_time(date: &str) -> String {
+    if date.len() == 10 {
+        format!("{date} 00:00:00")
+    } else {
+        date.to_string()
+    }
+}
+
+// Pulls `--name value` out of the arguments, leaving the positional ones.
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == name)?;
+    if i + 1 >= args.len() {
+        return None;
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Some(value)
+}
+
+async fn run(bn_api: &BinanceAPI<'_>, mut args: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let command = if args.is_empty() {
+        "".to_string()
+    } else {
+        args.remove(0)
+    };
+    let from = take_option(&mut args, "--from").map(|a| utc_time(&a));
+    let to = take_option(&mut args, "--to").map(|a| utc_time(&a));
+    let time_in_force = take_option(&mut args, "--tif").unwrap_or("GTC".to_string());
+    let arg = |i: usize| args.get(i).map(|a| a.as_str()).ok_or(USAGE);
+    match command.as_str() {
+        "price" => print_json(&bn_api.get_price(arg(0)?).await?),
+        "klines" => {
+            let from = from.ok_or(USAGE)?;
+            let klines = bn_api
+                .history_klines(arg(0)?, arg(1)?, &from, &to.unwrap_or_default())
+                .await?;
+            println!("{}", Kline::HEADER);
+            for kline in klines {
+                println!("{}", kline.to_csv_row());
+            }
+        }
+        "order" => {
+            let side = arg(0)?.to_uppercase();
+            let order = match args.get(3) {
+                Some(price) => NewOrder::new(arg(1)?, &side, "LIMIT")
+                    .price(price.parse()?)
+                    .time_in_force(&time_in_force),
+                None => NewOrder::new(arg(1)?, &side, "MARKET"),
+            };
+            print_json(
+                &bn_api
+                    .place_order(&order.quantity(arg(2)?.parse()?))
+                    .await?,
+            );
+        }
+        "cancel" => print_json(&bn_api.cancel_order(arg(0)?, arg(1)?, false).await?),
+        "cancel-all" => print_json(&bn_api.cancel_order(arg(0)?, "", true).await?),
+        "open-orders" => print_json(&bn_api.get_open_orders(arg(0).unwrap_or("")).await?),
+        "balance" => print_json(&serde_json::to_value(bn_api.get_balance().await?)?),
+        "account" => print_json(&bn_api.pull_account().await?),
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let bn_api = BinanceAPI::new("api_key", "secret_key", "swap")
-        .await
-        .unwrap();
-    // let delay = bn_api.listen_key_manager("delay").await.unwrap();
-    // let delete = bn_api.listen_key_manager("delete").await.unwrap();
-    let mut ws = bn_api.generate_websocket("market");
-    bn_api
-        .subscribe_websocket(&mut ws, &vec!["BTCUSDT", "ETHUSDT"], "aggTrade")
-        .await
-        .unwrap();
-    for _ in 0..10 {
-        dbg!(bn_api.websocket_read_once(&mut ws));
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let mut config = match take_option(&mut args, "--config") {
+        Some(path) => ClientConfig::from_toml_file(&path)?,
+        None => ClientConfig::from_env()?,
+    };
+    if let Some(i) = args.iter().position(|a| a == "--swap") {
+        args.remove(i);
+        config.account_type = "swap".to_string();
+    }
+    let bn_api = BinanceAPI::from_config(&config).await?;
+    if let Err(e) = run(&bn_api, args).await {
+        eprintln!("{e}");
+        std::process::exit(1);
     }
-    let klines = bn_api
-        .history_klines("BTCUSDT", "1h", "2024-01-01 00:00:00", "")
-        .await
-        .unwrap();
-    let exchange_info = bn_api.get_exchange_info().await.unwrap();
-    let account_info = bn_api.pull_account().await.unwrap();
-    let position = bn_api.get_position().await.unwrap();
-    let prices = bn_api.get_price("").await.unwrap();
-    let tickers = bn_api.get_ticker("").await.unwrap();
-    let balance = bn_api.get_balance().await.unwrap();
-    let an_order = bn_api
-        .new_order("BTCUSDT", "BUY", "LIMIT", "0.1", "50000", "GTC")
-        .await
-        .unwrap();
-    let cancel_order = bn_api.cancel_order("BTCUSDT", "0", true).await.unwrap();
     Ok(())
 }
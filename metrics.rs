@@ -0,0 +1,55 @@
+pub mod metrics {
+    use ::metrics::{counter, gauge, histogram};
+    use chrono::Utc;
+    use std::{error::Error, net::SocketAddr};
+
+    // Serves `/metrics` for Prometheus to scrape; must be called inside a tokio runtime.
+    pub fn install_prometheus_exporter(addr: SocketAddr) -> Result<(), Box<dyn Error>> {
+        metrics_exporter_prometheus::PrometheusBuilder::new()
+            .with_http_listener(addr)
+            .install()?;
+        Ok(())
+    }
+
+    pub(crate) fn record_request(endpoint: &str, method: &str, status: u16, latency_ms: u64) {
+        let labels = [
+            ("endpoint", endpoint.to_string()),
+            ("method", method.to_string()),
+            ("status", status.to_string()),
+        ];
+        counter!("binance_requests_total", &labels).increment(1);
+        histogram!("binance_request_latency_ms", &labels[..2]).record(latency_ms as f64);
+    }
+
+    pub(crate) fn record_transport_error(endpoint: &str, method: &str) {
+        counter!(
+            "binance_transport_errors_total",
+            "endpoint" => endpoint.to_string(),
+            "method" => method.to_string()
+        )
+        .increment(1);
+    }
+
+    pub(crate) fn record_used_weight(weight: u64) {
+        gauge!("binance_used_weight_1m").set(weight as f64);
+    }
+
+    pub(crate) fn record_error_code(code: i64) {
+        counter!("binance_error_codes_total", "code" => code.to_string()).increment(1);
+    }
+
+    pub(crate) fn record_ws_message(message: &str) {
+        counter!("binance_ws_messages_total").increment(1);
+        let event_time = serde_json::from_str::<serde_json::Value>(message)
+            .ok()
+            .and_then(|a| a["E"].as_i64());
+        if let Some(event_time) = event_time {
+            let lag = Utc::now().timestamp_millis() - event_time;
+            histogram!("binance_ws_message_lag_ms").record(lag as f64);
+        }
+    }
+
+    pub fn record_ws_reconnect(stream: &str) {
+        counter!("binance_ws_reconnects_total", "stream" => stream.to_string()).increment(1);
+    }
+}
@@ -0,0 +1,1810 @@
+pub mod models {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+    use std::{collections::HashMap, error::Error, fs, io::Write};
+
+    pub trait CsvRecord: Sized {
+        const HEADER: &'static str;
+        fn to_csv_row(&self) -> String;
+        fn from_csv_row(row: &[&str]) -> Result<Self, Box<dyn Error>>;
+    }
+
+    pub fn to_csv<T: CsvRecord>(path: &str, records: &[T]) -> Result<(), Box<dyn Error>> {
+        let mut file = fs::File::create(path)?;
+        writeln!(file, "{}", T::HEADER)?;
+        for record in records {
+            writeln!(file, "{}", record.to_csv_row())?;
+        }
+        Ok(())
+    }
+
+    pub fn from_csv<T: CsvRecord>(path: &str) -> Result<Vec<T>, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let mut lines = content.lines();
+        match lines.next() {
+            Some(header) if header.trim() == T::HEADER => {}
+            _ => return Err(format!("`{path}` doesn't start with `{}`.", T::HEADER).into()),
+        }
+        lines
+            .filter(|a| !a.trim().is_empty())
+            .map(|a| T::from_csv_row(&a.trim().split(',').collect::<Vec<_>>()))
+            .collect()
+    }
+
+    fn csv_flag(value: &Option<bool>) -> String {
+        value.map(|a| a.to_string()).unwrap_or_default()
+    }
+
+    fn parse_csv_flag(value: Option<&&str>) -> Result<Option<bool>, Box<dyn Error>> {
+        match value {
+            Some(a) if !a.is_empty() => Ok(Some(a.parse()?)),
+            _ => Ok(None),
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OcoOrderReport {
+        pub order_list_id: i64,
+        pub contingency_type: String,
+        pub list_status_type: String,
+        pub list_order_status: String,
+        pub list_client_order_id: String,
+        pub transaction_time: i64,
+        pub symbol: String,
+        pub orders: Vec<OcoOrderRef>,
+        #[serde(default)]
+        pub order_reports: Vec<OcoLegReport>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OcoOrderRef {
+        pub symbol: String,
+        pub order_id: i64,
+        pub client_order_id: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OcoLegReport {
+        pub symbol: String,
+        pub order_id: i64,
+        pub order_list_id: i64,
+        pub client_order_id: String,
+        pub transact_time: i64,
+        pub price: Decimal,
+        pub orig_qty: Decimal,
+        pub executed_qty: Decimal,
+        pub cummulative_quote_qty: Decimal,
+        pub status: String,
+        pub time_in_force: String,
+        #[serde(rename = "type")]
+        pub order_type: String,
+        pub side: String,
+        #[serde(default)]
+        pub stop_price: Decimal,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct PriceLevel {
+        pub price: Decimal,
+        pub qty: Decimal,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct DepthSnapshot {
+        #[serde(rename = "lastUpdateId")]
+        pub last_update_id: i64,
+        #[serde(rename = "E", default)]
+        pub event_time: Option<i64>,
+        #[serde(rename = "T", default)]
+        pub transaction_time: Option<i64>,
+        pub bids: Vec<PriceLevel>,
+        pub asks: Vec<PriceLevel>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Trade {
+        pub id: i64,
+        pub price: Decimal,
+        pub qty: Decimal,
+        pub quote_qty: Decimal,
+        pub time: i64,
+        pub is_buyer_maker: bool,
+        #[serde(default)]
+        pub is_best_match: Option<bool>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct AggTrade {
+        #[serde(rename = "a")]
+        pub agg_trade_id: i64,
+        #[serde(rename = "p")]
+        pub price: Decimal,
+        #[serde(rename = "q")]
+        pub qty: Decimal,
+        #[serde(rename = "f")]
+        pub first_trade_id: i64,
+        #[serde(rename = "l")]
+        pub last_trade_id: i64,
+        #[serde(rename = "T")]
+        pub time: i64,
+        #[serde(rename = "m")]
+        pub is_buyer_maker: bool,
+        #[serde(rename = "M", default)]
+        pub is_best_match: Option<bool>,
+    }
+
+    // An own fill from spot `myTrades` or futures `userTrades`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AccountTrade {
+        pub symbol: String,
+        pub id: i64,
+        pub order_id: i64,
+        pub price: Decimal,
+        pub qty: Decimal,
+        pub quote_qty: Decimal,
+        pub commission: Decimal,
+        pub commission_asset: String,
+        pub time: i64,
+        #[serde(alias = "buyer")]
+        pub is_buyer: bool,
+        #[serde(alias = "maker")]
+        pub is_maker: bool,
+        // Futures only.
+        #[serde(default)]
+        pub realized_pnl: Option<Decimal>,
+        #[serde(default)]
+        pub position_side: Option<String>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AvgPrice {
+        pub mins: i64,
+        pub price: Decimal,
+        pub close_time: i64,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BookTicker {
+        pub symbol: String,
+        pub bid_price: Decimal,
+        pub bid_qty: Decimal,
+        pub ask_price: Decimal,
+        pub ask_qty: Decimal,
+        #[serde(default)]
+        pub time: Option<i64>,
+    }
+
+    type KlineRow = (
+        i64,
+        Decimal,
+        Decimal,
+        Decimal,
+        Decimal,
+        Decimal,
+        i64,
+        Decimal,
+        u64,
+        Decimal,
+        Decimal,
+        String,
+    );
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(from = "KlineRow", into = "KlineRow")]
+    pub struct Kline {
+        pub open_time: i64,
+        pub open: Decimal,
+        pub high: Decimal,
+        pub low: Decimal,
+        pub close: Decimal,
+        pub volume: Decimal,
+        pub close_time: i64,
+        pub quote_volume: Decimal,
+        pub trade_count: u64,
+        pub taker_buy_base_volume: Decimal,
+        pub taker_buy_quote_volume: Decimal,
+    }
+
+    impl From<KlineRow> for Kline {
+        fn from(row: KlineRow) -> Self {
+            Kline {
+                open_time: row.0,
+                open: row.1,
+                high: row.2,
+                low: row.3,
+                close: row.4,
+                volume: row.5,
+                close_time: row.6,
+                quote_volume: row.7,
+                trade_count: row.8,
+                taker_buy_base_volume: row.9,
+                taker_buy_quote_volume: row.10,
+            }
+        }
+    }
+
+    impl From<Kline> for KlineRow {
+        fn from(kline: Kline) -> Self {
+            (
+                kline.open_time,
+                kline.open,
+                kline.high,
+                kline.low,
+                kline.close,
+                kline.volume,
+                kline.close_time,
+                kline.quote_volume,
+                kline.trade_count,
+                kline.taker_buy_base_volume,
+                kline.taker_buy_quote_volume,
+                "0".to_string(),
+            )
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct KlineQuery {
+        // Klines per request; 0 leaves it to the exchange default (500).
+        pub limit: u32,
+        // Keep the candle that is still forming at the end of the range.
+        pub include_open: bool,
+        // Whether a candle opening exactly at the end time belongs to the range.
+        pub inclusive_end: bool,
+        // Time chunks of the range fetched at once; 0 or 1 fetches page after page.
+        pub concurrency: usize,
+    }
+
+    impl Default for KlineQuery {
+        fn default() -> Self {
+            KlineQuery {
+                limit: 1000,
+                include_open: false,
+                inclusive_end: true,
+                concurrency: 1,
+            }
+        }
+    }
+
+    impl KlineQuery {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        pub fn limit(mut self, limit: u32) -> Self {
+            self.limit = limit;
+            self
+        }
+
+        pub fn include_open(mut self, include_open: bool) -> Self {
+            self.include_open = include_open;
+            self
+        }
+
+        pub fn inclusive_end(mut self, inclusive_end: bool) -> Self {
+            self.inclusive_end = inclusive_end;
+            self
+        }
+
+        pub fn concurrency(mut self, concurrency: usize) -> Self {
+            self.concurrency = concurrency;
+            self
+        }
+    }
+
+    // String formats a `TimeInput` is read in, all UTC; RFC 3339 times carry their own offset.
+    const TIME_FORMATS: [&str; 5] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ];
+
+    // A point in time for the ranged endpoints: "" (no bound), unix ms, a `DateTime`, or a
+    // string such as "2024-01-02 03:04:05", "2024-01-02", "2024-01-02T03:04:05Z" or
+    // "2024-01-02T03:04:05+08:00". A string that fits none of these is an error of the request.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum TimeInput {
+        Unset,
+        Millis(i64),
+        Text(String),
+    }
+
+    impl TimeInput {
+        // Unix ms, or None when unset.
+        pub fn millis(&self) -> Result<Option<i64>, Box<dyn Error>> {
+            let text = match self {
+                TimeInput::Unset => return Ok(None),
+                TimeInput::Millis(millis) => return Ok(Some(*millis)),
+                TimeInput::Text(text) => text.trim(),
+            };
+            if text.is_empty() {
+                return Ok(None);
+            }
+            if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+                return Ok(Some(time.timestamp_millis()));
+            }
+            if let Some(time) = TIME_FORMATS
+                .iter()
+                .find_map(|a| NaiveDateTime::parse_from_str(text, a).ok())
+            {
+                return Ok(Some(time.and_utc().timestamp_millis()));
+            }
+            match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+                Ok(date) => Ok(Some(
+                    date.and_time(NaiveTime::MIN).and_utc().timestamp_millis(),
+                )),
+                Err(_) => Err(format!("Can't read `{text}` as a time.").into()),
+            }
+        }
+    }
+
+    impl From<&str> for TimeInput {
+        fn from(text: &str) -> Self {
+            TimeInput::Text(text.to_string())
+        }
+    }
+
+    impl From<&String> for TimeInput {
+        fn from(text: &String) -> Self {
+            TimeInput::Text(text.clone())
+        }
+    }
+
+    impl From<String> for TimeInput {
+        fn from(text: String) -> Self {
+            TimeInput::Text(text)
+        }
+    }
+
+    impl From<i64> for TimeInput {
+        fn from(millis: i64) -> Self {
+            TimeInput::Millis(millis)
+        }
+    }
+
+    impl<Tz: TimeZone> From<DateTime<Tz>> for TimeInput {
+        fn from(time: DateTime<Tz>) -> Self {
+            TimeInput::Millis(time.timestamp_millis())
+        }
+    }
+
+    // Read as UTC.
+    impl From<NaiveDateTime> for TimeInput {
+        fn from(time: NaiveDateTime) -> Self {
+            TimeInput::Millis(time.and_utc().timestamp_millis())
+        }
+    }
+
+    impl From<NaiveDate> for TimeInput {
+        fn from(date: NaiveDate) -> Self {
+            TimeInput::from(date.and_time(NaiveTime::MIN))
+        }
+    }
+
+    impl<T: Into<TimeInput>> From<Option<T>> for TimeInput {
+        fn from(time: Option<T>) -> Self {
+            time.map_or(TimeInput::Unset, |a| a.into())
+        }
+    }
+
+    impl From<&TimeInput> for TimeInput {
+        fn from(time: &TimeInput) -> Self {
+            time.clone()
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct KlineEvent {
+        #[serde(rename = "E")]
+        pub event_time: i64,
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "k")]
+        pub kline: WsKline,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct WsKline {
+        #[serde(rename = "t")]
+        pub open_time: i64,
+        #[serde(rename = "T")]
+        pub close_time: i64,
+        #[serde(rename = "i")]
+        pub interval: String,
+        #[serde(rename = "o")]
+        pub open: Decimal,
+        #[serde(rename = "h")]
+        pub high: Decimal,
+        #[serde(rename = "l")]
+        pub low: Decimal,
+        #[serde(rename = "c")]
+        pub close: Decimal,
+        #[serde(rename = "v")]
+        pub volume: Decimal,
+        #[serde(rename = "q")]
+        pub quote_volume: Decimal,
+        #[serde(rename = "n")]
+        pub trade_count: u64,
+        #[serde(rename = "V")]
+        pub taker_buy_base_volume: Decimal,
+        #[serde(rename = "Q")]
+        pub taker_buy_quote_volume: Decimal,
+        #[serde(rename = "x")]
+        pub is_closed: bool,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct TradeEvent {
+        #[serde(rename = "E")]
+        pub event_time: i64,
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "t")]
+        pub trade_id: i64,
+        #[serde(rename = "p")]
+        pub price: Decimal,
+        #[serde(rename = "q")]
+        pub qty: Decimal,
+        #[serde(rename = "T")]
+        pub trade_time: i64,
+        #[serde(rename = "m")]
+        pub is_buyer_maker: bool,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct DepthEvent {
+        #[serde(rename = "E")]
+        pub event_time: i64,
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "U")]
+        pub first_update_id: i64,
+        #[serde(rename = "u")]
+        pub final_update_id: i64,
+        // Futures only: the final update id of the previous event.
+        #[serde(rename = "pu", default)]
+        pub prev_final_update_id: Option<i64>,
+        #[serde(rename = "b")]
+        pub bids: Vec<PriceLevel>,
+        #[serde(rename = "a")]
+        pub asks: Vec<PriceLevel>,
+    }
+
+    // A `bookTicker` stream event; spot ones have no event or transaction time.
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct BookTickerEvent {
+        #[serde(rename = "u")]
+        pub update_id: i64,
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "b")]
+        pub bid_price: Decimal,
+        #[serde(rename = "B")]
+        pub bid_qty: Decimal,
+        #[serde(rename = "a")]
+        pub ask_price: Decimal,
+        #[serde(rename = "A")]
+        pub ask_qty: Decimal,
+        #[serde(rename = "E", default)]
+        pub event_time: Option<i64>,
+        #[serde(rename = "T", default)]
+        pub transaction_time: Option<i64>,
+    }
+
+    impl OrderUpdate {
+        // Picks the order update out of a user data event, if it is one.
+        pub fn from_event(event: &serde_json::Value) -> Option<Self> {
+            match event["e"].as_str()? {
+                "executionReport" => serde_json::from_value(event.clone()).ok(),
+                "ORDER_TRADE_UPDATE" => {
+                    let mut update: OrderUpdate =
+                        serde_json::from_value(event["o"].clone()).ok()?;
+                    update.event_time = event["E"].as_i64().unwrap_or_default();
+                    Some(update)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    // `<symbol>@depth<levels>` snapshots: spot sends `lastUpdateId/bids/asks` only, futures
+    // adds the event header and uses `u/b/a`.
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct PartialDepth {
+        #[serde(rename = "lastUpdateId", alias = "u")]
+        pub last_update_id: i64,
+        #[serde(rename = "E", default)]
+        pub event_time: Option<i64>,
+        #[serde(rename = "s", default)]
+        pub symbol: Option<String>,
+        #[serde(alias = "b")]
+        pub bids: Vec<PriceLevel>,
+        #[serde(alias = "a")]
+        pub asks: Vec<PriceLevel>,
+    }
+
+    // Spot `executionReport` carries these fields at the top level, futures
+    // `ORDER_TRADE_UPDATE` under `o`.
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct OrderUpdate {
+        #[serde(rename = "E", default)]
+        pub event_time: i64,
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "c")]
+        pub client_order_id: String,
+        #[serde(rename = "S")]
+        pub side: String,
+        #[serde(rename = "o")]
+        pub order_type: String,
+        #[serde(rename = "x")]
+        pub execution_type: String,
+        #[serde(rename = "X")]
+        pub status: String,
+        #[serde(rename = "i")]
+        pub order_id: i64,
+        #[serde(rename = "p")]
+        pub price: Decimal,
+        #[serde(rename = "q")]
+        pub qty: Decimal,
+        #[serde(rename = "l")]
+        pub last_filled_qty: Decimal,
+        #[serde(rename = "z")]
+        pub cum_filled_qty: Decimal,
+        #[serde(rename = "L")]
+        pub last_filled_price: Decimal,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct MarginCallEvent {
+        #[serde(rename = "E")]
+        pub event_time: i64,
+        // Cross wallet balance, only sent for crossed positions.
+        #[serde(rename = "cw", default)]
+        pub cross_wallet_balance: Option<Decimal>,
+        #[serde(rename = "p")]
+        pub positions: Vec<MarginCallPosition>,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct MarginCallPosition {
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "ps")]
+        pub position_side: String,
+        #[serde(rename = "pa")]
+        pub position_amt: Decimal,
+        #[serde(rename = "mt")]
+        pub margin_type: String,
+        #[serde(rename = "iw")]
+        pub isolated_wallet: Decimal,
+        #[serde(rename = "mp")]
+        pub mark_price: Decimal,
+        #[serde(rename = "up")]
+        pub unrealized_profit: Decimal,
+        #[serde(rename = "mm")]
+        pub maint_margin: Decimal,
+    }
+
+    // `markPriceUpdate` events; `!markPrice@arr` sends a list of them, one per symbol.
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct MarkPriceEvent {
+        #[serde(rename = "E")]
+        pub event_time: i64,
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "p")]
+        pub mark_price: Decimal,
+        #[serde(rename = "i")]
+        pub index_price: Decimal,
+        // Only meaningful in the last hour before settlement.
+        #[serde(rename = "P")]
+        pub estimated_settle_price: Decimal,
+        #[serde(rename = "r")]
+        pub funding_rate: Decimal,
+        #[serde(rename = "T")]
+        pub next_funding_time: i64,
+    }
+
+    // `forceOrder` events, one per liquidation order whether from `<symbol>@forceOrder` or
+    // `!forceOrder@arr`.
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct LiquidationEvent {
+        #[serde(rename = "E")]
+        pub event_time: i64,
+        #[serde(rename = "o")]
+        pub order: LiquidationOrder,
+    }
+
+    #[derive(Debug, Clone, Deserialize)]
+    pub struct LiquidationOrder {
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "S")]
+        pub side: String,
+        #[serde(rename = "o")]
+        pub order_type: String,
+        #[serde(rename = "f")]
+        pub time_in_force: String,
+        #[serde(rename = "q")]
+        pub orig_qty: Decimal,
+        #[serde(rename = "p")]
+        pub price: Decimal,
+        #[serde(rename = "ap")]
+        pub avg_price: Decimal,
+        #[serde(rename = "X")]
+        pub status: String,
+        #[serde(rename = "l")]
+        pub last_filled_qty: Decimal,
+        #[serde(rename = "z")]
+        pub filled_qty: Decimal,
+        #[serde(rename = "T")]
+        pub trade_time: i64,
+    }
+
+    impl LiquidationOrder {
+        pub fn filled_notional(&self) -> Decimal {
+            self.filled_qty * self.avg_price
+        }
+    }
+
+    impl From<WsKline> for Kline {
+        fn from(kline: WsKline) -> Self {
+            Kline {
+                open_time: kline.open_time,
+                open: kline.open,
+                high: kline.high,
+                low: kline.low,
+                close: kline.close,
+                volume: kline.volume,
+                close_time: kline.close_time,
+                quote_volume: kline.quote_volume,
+                trade_count: kline.trade_count,
+                taker_buy_base_volume: kline.taker_buy_base_volume,
+                taker_buy_quote_volume: kline.taker_buy_quote_volume,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum ContractType {
+        Perpetual,
+        CurrentQuarter,
+        NextQuarter,
+    }
+
+    impl ContractType {
+        pub fn as_str(&self) -> &'static str {
+            match self {
+                ContractType::Perpetual => "PERPETUAL",
+                ContractType::CurrentQuarter => "CURRENT_QUARTER",
+                ContractType::NextQuarter => "NEXT_QUARTER",
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IndexInfo {
+        pub symbol: String,
+        pub time: i64,
+        pub component: String,
+        pub base_asset_list: Vec<IndexConstituent>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IndexConstituent {
+        pub base_asset: String,
+        pub quote_asset: String,
+        pub weight_in_quantity: Decimal,
+        pub weight_in_percentage: Decimal,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AssetIndex {
+        pub symbol: String,
+        pub time: i64,
+        pub index: Decimal,
+        pub bid_buffer: Decimal,
+        pub ask_buffer: Decimal,
+        pub bid_rate: Decimal,
+        pub ask_rate: Decimal,
+        pub auto_exchange_bid_buffer: Decimal,
+        pub auto_exchange_ask_buffer: Decimal,
+        pub auto_exchange_bid_rate: Decimal,
+        pub auto_exchange_ask_rate: Decimal,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Position {
+        pub symbol: String,
+        // BOTH in one-way mode, LONG or SHORT in hedge mode.
+        pub position_side: String,
+        pub position_amt: Decimal,
+        pub entry_price: Decimal,
+        pub break_even_price: Decimal,
+        pub mark_price: Decimal,
+        #[serde(rename = "unRealizedProfit")]
+        pub unrealized_profit: Decimal,
+        pub liquidation_price: Decimal,
+        #[serde(default)]
+        pub notional: Decimal,
+        #[serde(default)]
+        pub margin_asset: String,
+        #[serde(default)]
+        pub isolated_margin: Decimal,
+        #[serde(default)]
+        pub initial_margin: Decimal,
+        #[serde(default)]
+        pub maint_margin: Decimal,
+        #[serde(default)]
+        pub adl: i32,
+        #[serde(default)]
+        pub update_time: i64,
+    }
+
+    impl Position {
+        // At the mark price; always positive.
+        pub fn notional_value(&self) -> Decimal {
+            (self.position_amt * self.mark_price).abs()
+        }
+
+        // Return on the initial margin in percent, zero without margin.
+        pub fn roe(&self) -> Decimal {
+            if self.initial_margin.is_zero() {
+                Decimal::ZERO
+            } else {
+                self.unrealized_profit / self.initial_margin * Decimal::ONE_HUNDRED
+            }
+        }
+
+        // How far the mark price may move against the position before liquidation, in percent of
+        // the mark price; None without a liquidation price.
+        pub fn liquidation_distance_percent(&self) -> Option<Decimal> {
+            if self.liquidation_price.is_zero() || self.mark_price.is_zero() {
+                return None;
+            }
+            Some(
+                (self.mark_price - self.liquidation_price).abs() / self.mark_price
+                    * Decimal::ONE_HUNDRED,
+            )
+        }
+
+        // LONG or SHORT, from `position_side` in hedge mode and the sign of `position_amt` in
+        // one-way mode; "" when flat.
+        pub fn side(&self) -> &str {
+            if self.position_amt.is_zero() {
+                ""
+            } else if self.position_side != "BOTH" {
+                &self.position_side
+            } else if self.position_amt > Decimal::ZERO {
+                "LONG"
+            } else {
+                "SHORT"
+            }
+        }
+
+        pub fn is_hedge_mode(&self) -> bool {
+            self.position_side != "BOTH"
+        }
+    }
+
+    // One asset of `get_balance`, from the spot account or the futures wallet.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AssetBalance {
+        pub asset: String,
+        // What can be used for new orders: `free` on spot, `availableBalance` on futures.
+        #[serde(default, alias = "availableBalance")]
+        pub free: Decimal,
+        // Held by open orders; spot only.
+        #[serde(default)]
+        pub locked: Decimal,
+        // Free plus locked on spot, the wallet balance on futures.
+        #[serde(default)]
+        pub balance: Decimal,
+        #[serde(default)]
+        pub cross_wallet_balance: Decimal,
+        #[serde(default)]
+        pub cross_un_pnl: Decimal,
+        #[serde(default)]
+        pub max_withdraw_amount: Decimal,
+        #[serde(default)]
+        pub update_time: i64,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AccountConfig {
+        pub fee_tier: i32,
+        pub can_trade: bool,
+        pub can_deposit: bool,
+        pub can_withdraw: bool,
+        // Hedge mode when true, one-way mode otherwise.
+        pub dual_side_position: bool,
+        pub multi_assets_margin: bool,
+        pub trade_group_id: i64,
+        pub update_time: i64,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SymbolConfig {
+        pub symbol: String,
+        pub margin_type: String,
+        #[serde(deserialize_with = "bool_or_string")]
+        pub is_auto_add_margin: bool,
+        pub leverage: u32,
+        pub max_notional_value: String,
+    }
+
+    // One isolated margin change from `/fapi/v1/positionMargin/history`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PositionMarginChange {
+        pub symbol: String,
+        // 1 added, 2 reduced.
+        #[serde(rename = "type")]
+        pub change_type: i32,
+        // e.g. USER_ADJUST.
+        #[serde(default)]
+        pub delta_type: String,
+        pub amount: Decimal,
+        pub asset: String,
+        pub time: i64,
+        pub position_side: String,
+    }
+
+    // One quantitative rules indicator, e.g. UFR (unfilled ratio), IFER (IOC/FOK expiration
+    // ratio), GCR (GTC cancellation ratio) or DR (dust ratio).
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TradingIndicator {
+        pub indicator: String,
+        pub is_locked: bool,
+        // In ms; 0 when not locked.
+        pub planned_recover_time: i64,
+        pub value: Decimal,
+        // Trading is restricted once `value` reaches this.
+        pub trigger_value: Decimal,
+    }
+
+    impl TradingIndicator {
+        // `value` as a fraction of `trigger_value`.
+        pub fn usage(&self) -> Decimal {
+            if self.trigger_value.is_zero() {
+                return Decimal::ZERO;
+            }
+            self.value / self.trigger_value
+        }
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ApiTradingStatus {
+        // Symbol -> its indicators; account-wide ones are under `ACCOUNT`.
+        #[serde(default)]
+        pub indicators: HashMap<String, Vec<TradingIndicator>>,
+        pub update_time: i64,
+    }
+
+    impl ApiTradingStatus {
+        pub fn is_locked(&self) -> bool {
+            self.indicators.values().flatten().any(|a| a.is_locked)
+        }
+
+        // The (symbol, indicator) pairs at or past `usage` (e.g. 0.8 for 80%) of their trigger
+        // value, locked ones included.
+        pub fn near_limit(&self, usage: Decimal) -> Vec<(&String, &TradingIndicator)> {
+            let mut near: Vec<_> = self
+                .indicators
+                .iter()
+                .flat_map(|(symbol, indicators)| indicators.iter().map(move |a| (symbol, a)))
+                .filter(|(_, a)| a.is_locked || a.usage() >= usage)
+                .collect();
+            near.sort_by(|a, b| b.1.usage().cmp(&a.1.usage()));
+            near
+        }
+    }
+
+    // Some endpoints quote booleans, e.g. `"isAutoAddMargin": "false"`.
+    fn bool_or_string<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Bool(value) => Ok(value),
+            serde_json::Value::String(value) => value.parse().map_err(serde::de::Error::custom),
+            value => Err(serde::de::Error::custom(format!(
+                "expected a bool, got {value}"
+            ))),
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SystemStatus {
+        pub status: i32,
+        pub msg: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ApiKeyPermissions {
+        pub ip_restrict: bool,
+        pub create_time: i64,
+        pub enable_reading: bool,
+        #[serde(default)]
+        pub enable_spot_and_margin_trading: bool,
+        #[serde(default)]
+        pub enable_withdrawals: bool,
+        #[serde(default)]
+        pub enable_internal_transfer: bool,
+        #[serde(default)]
+        pub enable_margin: bool,
+        #[serde(default)]
+        pub enable_futures: bool,
+        #[serde(default)]
+        pub permits_universal_transfer: bool,
+        #[serde(default)]
+        pub enable_vanilla_options: bool,
+        #[serde(default)]
+        pub trading_authority_expiration_time: Option<i64>,
+    }
+
+    // From `/sapi/v1/account/status`: `Normal`, or why the account is restricted.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct AccountStatus {
+        #[serde(rename = "data")]
+        pub status: String,
+    }
+
+    impl AccountStatus {
+        pub fn is_normal(&self) -> bool {
+            self.status.eq_ignore_ascii_case("Normal")
+        }
+    }
+
+    // One spot quantitative rules indicator of a symbol.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct AccountTradingIndicator {
+        // e.g. UFR, IFER or GCR.
+        #[serde(rename = "i")]
+        pub indicator: String,
+        // Orders the value is computed over.
+        #[serde(rename = "c")]
+        pub count: i64,
+        #[serde(rename = "v")]
+        pub value: Decimal,
+        #[serde(rename = "t")]
+        pub trigger_value: Decimal,
+    }
+
+    // The `data` of `/sapi/v1/account/apiTradingStatus`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AccountApiTradingStatus {
+        pub is_locked: bool,
+        // In ms; 0 when not locked.
+        pub planned_recover_time: i64,
+        // Indicator -> the order count it starts being checked at.
+        #[serde(default)]
+        pub trigger_condition: HashMap<String, i64>,
+        #[serde(default)]
+        pub indicators: HashMap<String, Vec<AccountTradingIndicator>>,
+        pub update_time: i64,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct DustAssets {
+        pub details: Vec<DustAsset>,
+        #[serde(rename = "totalTransferBtc")]
+        pub total_transfer_btc: Decimal,
+        #[serde(rename = "totalTransferBNB")]
+        pub total_transfer_bnb: Decimal,
+        #[serde(rename = "dribbletPercentage")]
+        pub dribblet_percentage: Decimal,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct DustAsset {
+        pub asset: String,
+        #[serde(rename = "assetFullName")]
+        pub asset_full_name: String,
+        #[serde(rename = "amountFree")]
+        pub amount_free: Decimal,
+        #[serde(rename = "toBTC")]
+        pub to_btc: Decimal,
+        #[serde(rename = "toBNB")]
+        pub to_bnb: Decimal,
+        #[serde(rename = "toBNBOffExchange")]
+        pub to_bnb_off_exchange: Decimal,
+        pub exchange: Decimal,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DustTransferResult {
+        pub total_service_charge: Decimal,
+        pub total_transfered: Decimal,
+        pub transfer_result: Vec<DustTransfer>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DustTransfer {
+        pub amount: Decimal,
+        pub from_asset: String,
+        pub operate_time: i64,
+        pub service_charge_amount: Decimal,
+        pub tran_id: i64,
+        pub transfered_amount: Decimal,
+    }
+
+    // One coin from `/sapi/v1/capital/config/getall`, with the account's balances of it.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CoinInfo {
+        pub coin: String,
+        pub name: String,
+        pub deposit_all_enable: bool,
+        pub withdraw_all_enable: bool,
+        pub free: Decimal,
+        pub locked: Decimal,
+        pub freeze: Decimal,
+        pub withdrawing: Decimal,
+        pub is_legal_money: bool,
+        pub trading: bool,
+        pub network_list: Vec<CoinNetwork>,
+    }
+
+    impl CoinInfo {
+        pub fn network(&self, network: &str) -> Option<&CoinNetwork> {
+            self.network_list.iter().find(|a| a.network == network)
+        }
+
+        pub fn default_network(&self) -> Option<&CoinNetwork> {
+            self.network_list.iter().find(|a| a.is_default)
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CoinNetwork {
+        pub network: String,
+        pub coin: String,
+        pub name: String,
+        pub is_default: bool,
+        pub deposit_enable: bool,
+        pub withdraw_enable: bool,
+        // Why deposits or withdrawals are off, if they are.
+        #[serde(default)]
+        pub deposit_desc: String,
+        #[serde(default)]
+        pub withdraw_desc: String,
+        #[serde(default)]
+        pub special_tips: String,
+        pub withdraw_fee: Decimal,
+        pub withdraw_min: Decimal,
+        pub withdraw_max: Decimal,
+        pub withdraw_integer_multiple: Decimal,
+        #[serde(default)]
+        pub deposit_dust: Option<Decimal>,
+        pub min_confirm: u32,
+        pub un_lock_confirm: u32,
+        #[serde(default)]
+        pub address_regex: String,
+        #[serde(default)]
+        pub memo_regex: String,
+        pub same_address: bool,
+        #[serde(default)]
+        pub busy: bool,
+    }
+
+    // A transfer between two wallets of the account from `/sapi/v1/asset/transfer`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AssetTransfer {
+        pub asset: String,
+        pub amount: Decimal,
+        #[serde(rename = "type")]
+        pub transfer_type: String,
+        pub status: String,
+        pub tran_id: i64,
+        pub timestamp: i64,
+    }
+
+    // The older name, from when only spot <-> futures transfers were listed.
+    pub type FuturesTransfer = AssetTransfer;
+
+    // One interest charge from `/sapi/v1/margin/interestHistory`.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MarginInterest {
+        pub tx_id: i64,
+        // Sic: `interestAccuredTime`.
+        #[serde(rename = "interestAccuredTime")]
+        pub interest_accrued_time: i64,
+        pub asset: String,
+        // The asset actually charged when interest is paid in BNB.
+        #[serde(default)]
+        pub raw_asset: Option<String>,
+        pub principal: Decimal,
+        pub interest: Decimal,
+        pub interest_rate: Decimal,
+        // e.g. PERIODIC, ON_BORROW, PERIODIC_CONVERTED or ON_BORROW_CONVERTED.
+        #[serde(rename = "type")]
+        pub interest_type: String,
+        // Empty for cross margin.
+        #[serde(default)]
+        pub isolated_symbol: Option<String>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MarginInterestRate {
+        pub asset: String,
+        pub next_hourly_interest_rate: Decimal,
+    }
+
+    impl MarginInterestRate {
+        pub fn daily_rate(&self) -> Decimal {
+            self.next_hourly_interest_rate * Decimal::from(24)
+        }
+
+        pub fn yearly_rate(&self) -> Decimal {
+            self.next_hourly_interest_rate * Decimal::from(24 * 365)
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MaxBorrowable {
+        // What can be borrowed now given the account's collateral.
+        pub amount: Decimal,
+        // The account's limit for the asset regardless of collateral.
+        pub borrow_limit: Decimal,
+    }
+
+    // One product from `/sapi/v1/simple-earn/{flexible,locked}/list`. Locked products keep
+    // their asset and rate under `detail`, so use `asset()` and `apr()` for either kind.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EarnProduct {
+        // `projectId` on locked products, what `subscribe` takes for them.
+        #[serde(alias = "projectId")]
+        pub product_id: String,
+        #[serde(default)]
+        pub asset: String,
+        #[serde(default)]
+        pub latest_annual_percentage_rate: Decimal,
+        #[serde(default)]
+        pub can_purchase: bool,
+        #[serde(default)]
+        pub can_redeem: bool,
+        #[serde(default)]
+        pub is_sold_out: bool,
+        #[serde(default)]
+        pub min_purchase_amount: Decimal,
+        #[serde(default)]
+        pub status: String,
+        #[serde(default)]
+        pub detail: Option<LockedProductDetail>,
+        #[serde(default)]
+        pub quota: Option<LockedProductQuota>,
+    }
+
+    impl EarnProduct {
+        pub fn asset(&self) -> &str {
+            match &self.detail {
+                Some(detail) => &detail.asset,
+                None => &self.asset,
+            }
+        }
+
+        pub fn apr(&self) -> Decimal {
+            match &self.detail {
+                Some(detail) => detail.apr,
+                None => self.latest_annual_percentage_rate,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LockedProductDetail {
+        pub asset: String,
+        pub reward_asset: String,
+        // In days.
+        pub duration: u32,
+        pub renewable: bool,
+        pub is_sold_out: bool,
+        pub apr: Decimal,
+        pub status: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LockedProductQuota {
+        pub total_personal_quota: Decimal,
+        pub minimum: Decimal,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EarnSubscription {
+        pub purchase_id: i64,
+        // Locked only: the position `redeem` takes.
+        #[serde(default)]
+        pub position_id: Option<String>,
+        pub success: bool,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EarnRedemption {
+        pub redeem_id: i64,
+        pub success: bool,
+    }
+
+    impl CsvRecord for Kline {
+        const HEADER: &'static str = "open_time,open,high,low,close,volume,close_time,quote_volume,trade_count,taker_buy_base_volume,taker_buy_quote_volume";
+
+        fn to_csv_row(&self) -> String {
+            format!(
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                self.open_time,
+                self.open,
+                self.high,
+                self.low,
+                self.close,
+                self.volume,
+                self.close_time,
+                self.quote_volume,
+                self.trade_count,
+                self.taker_buy_base_volume,
+                self.taker_buy_quote_volume
+            )
+        }
+
+        fn from_csv_row(row: &[&str]) -> Result<Self, Box<dyn Error>> {
+            if row.len() < 11 {
+                return Err(format!("Kline row `{}` is too short.", row.join(",")).into());
+            }
+            Ok(Kline {
+                open_time: row[0].parse()?,
+                open: row[1].parse()?,
+                high: row[2].parse()?,
+                low: row[3].parse()?,
+                close: row[4].parse()?,
+                volume: row[5].parse()?,
+                close_time: row[6].parse()?,
+                quote_volume: row[7].parse()?,
+                trade_count: row[8].parse()?,
+                taker_buy_base_volume: row[9].parse()?,
+                taker_buy_quote_volume: row[10].parse()?,
+            })
+        }
+    }
+
+    impl CsvRecord for Trade {
+        const HEADER: &'static str = "id,price,qty,quote_qty,time,is_buyer_maker,is_best_match";
+
+        fn to_csv_row(&self) -> String {
+            format!(
+                "{},{},{},{},{},{},{}",
+                self.id,
+                self.price,
+                self.qty,
+                self.quote_qty,
+                self.time,
+                self.is_buyer_maker,
+                csv_flag(&self.is_best_match)
+            )
+        }
+
+        fn from_csv_row(row: &[&str]) -> Result<Self, Box<dyn Error>> {
+            if row.len() < 6 {
+                return Err(format!("Trade row `{}` is too short.", row.join(",")).into());
+            }
+            Ok(Trade {
+                id: row[0].parse()?,
+                price: row[1].parse()?,
+                qty: row[2].parse()?,
+                quote_qty: row[3].parse()?,
+                time: row[4].parse()?,
+                is_buyer_maker: row[5].parse()?,
+                is_best_match: parse_csv_flag(row.get(6))?,
+            })
+        }
+    }
+
+    impl CsvRecord for AggTrade {
+        const HEADER: &'static str =
+            "agg_trade_id,price,qty,first_trade_id,last_trade_id,time,is_buyer_maker,is_best_match";
+
+        fn to_csv_row(&self) -> String {
+            format!(
+                "{},{},{},{},{},{},{},{}",
+                self.agg_trade_id,
+                self.price,
+                self.qty,
+                self.first_trade_id,
+                self.last_trade_id,
+                self.time,
+                self.is_buyer_maker,
+                csv_flag(&self.is_best_match)
+            )
+        }
+
+        fn from_csv_row(row: &[&str]) -> Result<Self, Box<dyn Error>> {
+            if row.len() < 7 {
+                return Err(format!("AggTrade row `{}` is too short.", row.join(",")).into());
+            }
+            Ok(AggTrade {
+                agg_trade_id: row[0].parse()?,
+                price: row[1].parse()?,
+                qty: row[2].parse()?,
+                first_trade_id: row[3].parse()?,
+                last_trade_id: row[4].parse()?,
+                time: row[5].parse()?,
+                is_buyer_maker: row[6].parse()?,
+                is_best_match: parse_csv_flag(row.get(7))?,
+            })
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct NewOrder {
+        pub symbol: String,
+        pub side: String,
+        pub order_type: String,
+        pub quantity: Option<Decimal>,
+        pub quote_order_qty: Option<Decimal>,
+        pub price: Option<Decimal>,
+        pub stop_price: Option<Decimal>,
+        pub time_in_force: String,
+        pub good_till_date: i64,
+        pub reduce_only: bool,
+        pub position_side: String,
+        pub new_client_order_id: String,
+        pub new_order_resp_type: String,
+        pub auto_round: bool,
+        pub validate: bool,
+        // Set on the close orders of `panic_close_all`, which risk limits don't apply to.
+        pub(crate) skip_risk: bool,
+    }
+
+    impl NewOrder {
+        pub fn new(symbol: &str, side: &str, order_type: &str) -> Self {
+            NewOrder {
+                symbol: symbol.to_string(),
+                side: side.to_string(),
+                order_type: order_type.to_string(),
+                ..Default::default()
+            }
+        }
+
+        // A MARKET order closing `qty` of a futures position of `position_amt` (negative when
+        // short). Hedge mode closes through the position side; reduceOnly is one-way mode only.
+        pub fn close_position(
+            symbol: &str,
+            position_side: &str,
+            position_amt: Decimal,
+            qty: Decimal,
+        ) -> Self {
+            let side = if position_amt.is_sign_positive() {
+                "SELL"
+            } else {
+                "BUY"
+            };
+            let order = NewOrder::new(symbol, side, "MARKET").quantity(qty);
+            if position_side == "BOTH" {
+                order.reduce_only(true)
+            } else {
+                order.position_side(position_side)
+            }
+        }
+
+        pub fn quantity(mut self, quantity: Decimal) -> Self {
+            self.quantity = Some(quantity);
+            self
+        }
+
+        pub fn quote_order_qty(mut self, quote_order_qty: Decimal) -> Self {
+            self.quote_order_qty = Some(quote_order_qty);
+            self
+        }
+
+        pub fn price(mut self, price: Decimal) -> Self {
+            self.price = Some(price);
+            self
+        }
+
+        pub fn stop_price(mut self, stop_price: Decimal) -> Self {
+            self.stop_price = Some(stop_price);
+            self
+        }
+
+        pub fn time_in_force(mut self, time_in_force: &str) -> Self {
+            self.time_in_force = time_in_force.to_string();
+            self
+        }
+
+        pub fn good_till_date(mut self, good_till_date: i64) -> Self {
+            self.good_till_date = good_till_date;
+            self
+        }
+
+        pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+            self.reduce_only = reduce_only;
+            self
+        }
+
+        pub fn position_side(mut self, position_side: &str) -> Self {
+            self.position_side = position_side.to_string();
+            self
+        }
+
+        pub fn client_order_id(mut self, new_client_order_id: &str) -> Self {
+            self.new_client_order_id = new_client_order_id.to_string();
+            self
+        }
+
+        // ACK, RESULT or FULL (spot only); RESULT carries the executed quantity on both markets.
+        pub fn response_type(mut self, new_order_resp_type: &str) -> Self {
+            self.new_order_resp_type = new_order_resp_type.to_string();
+            self
+        }
+
+        pub fn auto_round(mut self, auto_round: bool) -> Self {
+            self.auto_round = auto_round;
+            self
+        }
+
+        pub fn validate(mut self, validate: bool) -> Self {
+            self.validate = validate;
+            self
+        }
+
+        pub fn to_param_map(&self) -> HashMap<String, String> {
+            let mut param_map = HashMap::new();
+            param_map.insert("symbol".to_string(), self.symbol.clone());
+            param_map.insert("side".to_string(), self.side.clone());
+            param_map.insert("type".to_string(), self.order_type.clone());
+            let decimals = [
+                ("quantity", self.quantity),
+                ("quoteOrderQty", self.quote_order_qty),
+                ("price", self.price),
+                ("stopPrice", self.stop_price),
+            ];
+            for (key, value) in decimals {
+                if let Some(value) = value {
+                    param_map.insert(key.to_string(), value.normalize().to_string());
+                }
+            }
+            if !self.time_in_force.is_empty() {
+                param_map.insert("timeInForce".to_string(), self.time_in_force.clone());
+            }
+            if self.good_till_date > 0 {
+                param_map.insert("goodTillDate".to_string(), self.good_till_date.to_string());
+            }
+            if self.reduce_only {
+                param_map.insert("reduceOnly".to_string(), "true".to_string());
+            }
+            if !self.position_side.is_empty() {
+                param_map.insert("positionSide".to_string(), self.position_side.clone());
+            }
+            if !self.new_client_order_id.is_empty() {
+                param_map.insert(
+                    "newClientOrderId".to_string(),
+                    self.new_client_order_id.clone(),
+                );
+            }
+            if !self.new_order_resp_type.is_empty() {
+                param_map.insert(
+                    "newOrderRespType".to_string(),
+                    self.new_order_resp_type.clone(),
+                );
+            }
+            param_map
+        }
+    }
+
+    // A spot OCO for `new_oco_order`: a take-profit LIMIT_MAKER at `price` and a stop at
+    // `stop_price`, a STOP_LOSS_LIMIT with `stop_limit_price` or a STOP_LOSS without one.
+    #[derive(Debug, Clone, Default)]
+    pub struct NewOcoOrder {
+        pub symbol: String,
+        pub side: String,
+        pub quantity: Decimal,
+        pub price: Decimal,
+        pub stop_price: Decimal,
+        pub stop_limit_price: Option<Decimal>,
+        // GTC unless set; only sent with a stop limit price.
+        pub stop_limit_time_in_force: String,
+        pub list_client_order_id: String,
+    }
+
+    impl NewOcoOrder {
+        pub fn new(
+            symbol: &str,
+            side: &str,
+            quantity: Decimal,
+            price: Decimal,
+            stop_price: Decimal,
+        ) -> Self {
+            NewOcoOrder {
+                symbol: symbol.to_string(),
+                side: side.to_string(),
+                quantity: quantity,
+                price: price,
+                stop_price: stop_price,
+                ..Default::default()
+            }
+        }
+
+        pub fn stop_limit_price(mut self, stop_limit_price: Decimal) -> Self {
+            self.stop_limit_price = Some(stop_limit_price);
+            self
+        }
+
+        pub fn stop_limit_time_in_force(mut self, stop_limit_time_in_force: &str) -> Self {
+            self.stop_limit_time_in_force = stop_limit_time_in_force.to_string();
+            self
+        }
+
+        pub fn client_order_id(mut self, list_client_order_id: &str) -> Self {
+            self.list_client_order_id = list_client_order_id.to_string();
+            self
+        }
+
+        pub fn to_param_map(&self) -> HashMap<String, String> {
+            let mut param_map = HashMap::new();
+            param_map.insert("symbol".to_string(), self.symbol.clone());
+            param_map.insert("side".to_string(), self.side.clone());
+            param_map.insert(
+                "quantity".to_string(),
+                self.quantity.normalize().to_string(),
+            );
+            // The limit leg takes profit above the market on a SELL and below it on a BUY; the
+            // stop leg is on the other side.
+            let (limit_leg, stop_leg) = if self.side.eq_ignore_ascii_case("SELL") {
+                ("above", "below")
+            } else {
+                ("below", "above")
+            };
+            param_map.insert(format!("{limit_leg}Type"), "LIMIT_MAKER".to_string());
+            param_map.insert(
+                format!("{limit_leg}Price"),
+                self.price.normalize().to_string(),
+            );
+            param_map.insert(
+                format!("{stop_leg}StopPrice"),
+                self.stop_price.normalize().to_string(),
+            );
+            match self.stop_limit_price {
+                Some(stop_limit_price) => {
+                    param_map.insert(format!("{stop_leg}Type"), "STOP_LOSS_LIMIT".to_string());
+                    param_map.insert(
+                        format!("{stop_leg}Price"),
+                        stop_limit_price.normalize().to_string(),
+                    );
+                    let time_in_force = match self.stop_limit_time_in_force.as_str() {
+                        "" => "GTC",
+                        time_in_force => time_in_force,
+                    };
+                    param_map.insert(format!("{stop_leg}TimeInForce"), time_in_force.to_string());
+                }
+                None => {
+                    param_map.insert(format!("{stop_leg}Type"), "STOP_LOSS".to_string());
+                }
+            }
+            if !self.list_client_order_id.is_empty() {
+                param_map.insert(
+                    "listClientOrderId".to_string(),
+                    self.list_client_order_id.clone(),
+                );
+            }
+            param_map
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct OrderValidationError {
+        pub symbol: String,
+        pub filter: String,
+        pub reason: String,
+    }
+
+    impl std::fmt::Display for OrderValidationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "{} order rejected by {}: {}",
+                self.symbol, self.filter, self.reason
+            )
+        }
+    }
+
+    impl Error for OrderValidationError {}
+
+    #[derive(Debug, Clone, Default)]
+    pub struct SymbolFilters {
+        pub symbol: String,
+        pub min_price: Decimal,
+        pub max_price: Decimal,
+        pub tick_size: Decimal,
+        pub min_qty: Decimal,
+        pub max_qty: Decimal,
+        pub step_size: Decimal,
+        pub min_notional: Decimal,
+        pub multiplier_up: Decimal,
+        pub multiplier_down: Decimal,
+        pub bid_multiplier_up: Decimal,
+        pub bid_multiplier_down: Decimal,
+        pub ask_multiplier_up: Decimal,
+        pub ask_multiplier_down: Decimal,
+        pub max_num_orders: i64,
+    }
+
+    impl SymbolFilters {
+        pub fn from_exchange_info(symbol_info: &serde_json::Value) -> Result<Self, Box<dyn Error>> {
+            let mut filters = SymbolFilters {
+                symbol: symbol_info["symbol"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                ..Default::default()
+            };
+            for filter in symbol_info["filters"].as_array().into_iter().flatten() {
+                let dec = |key: &str| filter[key].as_str().unwrap_or("0").parse::<Decimal>();
+                match filter["filterType"].as_str().unwrap_or_default() {
+                    "PRICE_FILTER" => {
+                        filters.min_price = dec("minPrice")?;
+                        filters.max_price = dec("maxPrice")?;
+                        filters.tick_size = dec("tickSize")?;
+                    }
+                    "LOT_SIZE" => {
+                        filters.min_qty = dec("minQty")?;
+                        filters.max_qty = dec("maxQty")?;
+                        filters.step_size = dec("stepSize")?;
+                    }
+                    "MIN_NOTIONAL" | "NOTIONAL" => {
+                        // spot names it `minNotional`, futures `notional`
+                        filters.min_notional = dec("minNotional")?.max(dec("notional")?);
+                    }
+                    "PERCENT_PRICE" => {
+                        filters.multiplier_up = dec("multiplierUp")?;
+                        filters.multiplier_down = dec("multiplierDown")?;
+                    }
+                    "PERCENT_PRICE_BY_SIDE" => {
+                        filters.bid_multiplier_up = dec("bidMultiplierUp")?;
+                        filters.bid_multiplier_down = dec("bidMultiplierDown")?;
+                        filters.ask_multiplier_up = dec("askMultiplierUp")?;
+                        filters.ask_multiplier_down = dec("askMultiplierDown")?;
+                    }
+                    "MAX_NUM_ORDERS" => {
+                        filters.max_num_orders = filter["maxNumOrders"]
+                            .as_i64()
+                            .or(filter["limit"].as_i64())
+                            .unwrap_or_default();
+                    }
+                    _ => {}
+                }
+            }
+            Ok(filters)
+        }
+
+        pub fn round_price(&self, price: Decimal) -> Decimal {
+            if self.tick_size.is_zero() {
+                return price;
+            }
+            ((price / self.tick_size).round() * self.tick_size).normalize()
+        }
+
+        pub fn round_qty(&self, qty: Decimal) -> Decimal {
+            if self.step_size.is_zero() {
+                return qty;
+            }
+            ((qty / self.step_size).floor() * self.step_size).normalize()
+        }
+
+        fn reject(&self, filter: &str, reason: String) -> Result<(), OrderValidationError> {
+            Err(OrderValidationError {
+                symbol: self.symbol.clone(),
+                filter: filter.to_string(),
+                reason: reason,
+            })
+        }
+
+        pub fn check_order(
+            &self,
+            order: &NewOrder,
+            reference_price: Decimal,
+            open_orders: usize,
+        ) -> Result<(), OrderValidationError> {
+            if let Some(price) = order.price {
+                if price < self.min_price || (!self.max_price.is_zero() && price > self.max_price) {
+                    return self.reject(
+                        "PRICE_FILTER",
+                        format!(
+                            "price {price} is outside [{}, {}]",
+                            self.min_price, self.max_price
+                        ),
+                    );
+                }
+                if !self.tick_size.is_zero()
+                    && !((price - self.min_price) % self.tick_size).is_zero()
+                {
+                    return self.reject(
+                        "PRICE_FILTER",
+                        format!(
+                            "price {price} is not a multiple of tickSize {}",
+                            self.tick_size
+                        ),
+                    );
+                }
+                let (up, down) = match (order.side.as_str(), self.multiplier_up.is_zero()) {
+                    (_, false) => (self.multiplier_up, self.multiplier_down),
+                    ("BUY", true) => (self.bid_multiplier_up, self.bid_multiplier_down),
+                    (_, true) => (self.ask_multiplier_up, self.ask_multiplier_down),
+                };
+                if !up.is_zero() && !reference_price.is_zero() {
+                    let (high, low) = (reference_price * up, reference_price * down);
+                    if price > high || price < low {
+                        return self.reject(
+                            "PERCENT_PRICE",
+                            format!(
+                                "price {price} is outside [{low}, {high}] around {reference_price}"
+                            ),
+                        );
+                    }
+                }
+            }
+            if let Some(qty) = order.quantity {
+                if qty < self.min_qty || (!self.max_qty.is_zero() && qty > self.max_qty) {
+                    return self.reject(
+                        "LOT_SIZE",
+                        format!(
+                            "quantity {qty} is outside [{}, {}]",
+                            self.min_qty, self.max_qty
+                        ),
+                    );
+                }
+                if !self.step_size.is_zero() && !((qty - self.min_qty) % self.step_size).is_zero() {
+                    return self.reject(
+                        "LOT_SIZE",
+                        format!(
+                            "quantity {qty} is not a multiple of stepSize {}",
+                            self.step_size
+                        ),
+                    );
+                }
+                let notional = qty * order.price.unwrap_or(reference_price);
+                if notional < self.min_notional {
+                    return self.reject(
+                        "MIN_NOTIONAL",
+                        format!("notional {notional} is below {}", self.min_notional),
+                    );
+                }
+            }
+            if self.max_num_orders > 0 && open_orders as i64 >= self.max_num_orders {
+                return self.reject(
+                    "MAX_NUM_ORDERS",
+                    format!("{open_orders} orders are already open"),
+                );
+            }
+            Ok(())
+        }
+    }
+}
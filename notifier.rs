@@ -0,0 +1,278 @@
+pub mod notifier {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::OrderUpdate;
+    use crate::stream::stream::UserDataEvent;
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use std::{error::Error, future::Future, pin::Pin, process::Command, thread};
+
+    pub type HookFuture<'b> = Pin<Box<dyn Future<Output = Result<(), Box<dyn Error>>> + Send + 'b>>;
+
+    // One position of a futures `ACCOUNT_UPDATE`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PositionChange {
+        #[serde(rename = "s")]
+        pub symbol: String,
+        #[serde(rename = "ps")]
+        pub position_side: String,
+        #[serde(rename = "pa")]
+        pub position_amt: Decimal,
+        #[serde(rename = "ep")]
+        pub entry_price: Decimal,
+        #[serde(rename = "up")]
+        pub unrealized_profit: Decimal,
+        // Why the account changed, e.g. ORDER or FUNDING_FEE.
+        #[serde(default)]
+        pub reason: String,
+        #[serde(default)]
+        pub event_time: i64,
+    }
+
+    #[derive(Debug, Clone)]
+    pub enum Notification {
+        // An order traded, partly or fully.
+        Fill(OrderUpdate),
+        PositionChange(PositionChange),
+    }
+
+    impl Notification {
+        // The fills and position changes in a user data event.
+        pub fn from_event(event: &Value) -> Vec<Notification> {
+            match event["e"].as_str().unwrap_or_default() {
+                "executionReport" | "ORDER_TRADE_UPDATE" => OrderUpdate::from_event(event)
+                    .filter(|a| a.execution_type == "TRADE")
+                    .map(|a| vec![Notification::Fill(a)])
+                    .unwrap_or_default(),
+                "ACCOUNT_UPDATE" => {
+                    let reason = event["a"]["m"].as_str().unwrap_or_default();
+                    let positions = event["a"]["P"].as_array().cloned().unwrap_or_default();
+                    positions
+                        .into_iter()
+                        .filter_map(|a| serde_json::from_value::<PositionChange>(a).ok())
+                        .map(|mut a| {
+                            a.reason = reason.to_string();
+                            a.event_time = event["E"].as_i64().unwrap_or_default();
+                            Notification::PositionChange(a)
+                        })
+                        .collect()
+                }
+                _ => vec![],
+            }
+        }
+
+        // One line for a chat message.
+        pub fn text(&self) -> String {
+            match self {
+                Notification::Fill(fill) => format!(
+                    "{} {} {} filled {} @ {} ({}/{}, {})",
+                    fill.symbol,
+                    fill.side,
+                    fill.order_type,
+                    fill.last_filled_qty,
+                    fill.last_filled_price,
+                    fill.cum_filled_qty,
+                    fill.qty,
+                    fill.status
+                ),
+                Notification::PositionChange(position) => format!(
+                    "{} {} position {} @ {} (uPnL {}, {})",
+                    position.symbol,
+                    position.position_side,
+                    position.position_amt,
+                    position.entry_price,
+                    position.unrealized_profit,
+                    position.reason
+                ),
+            }
+        }
+
+        pub fn to_json(&self) -> Value {
+            match self {
+                Notification::Fill(fill) => serde_json::json!({
+                    "type": "fill",
+                    "symbol": fill.symbol,
+                    "side": fill.side,
+                    "orderType": fill.order_type,
+                    "orderId": fill.order_id,
+                    "clientOrderId": fill.client_order_id,
+                    "status": fill.status,
+                    "lastFilledQty": fill.last_filled_qty,
+                    "lastFilledPrice": fill.last_filled_price,
+                    "cumFilledQty": fill.cum_filled_qty,
+                    "qty": fill.qty,
+                    "eventTime": fill.event_time,
+                }),
+                Notification::PositionChange(position) => {
+                    let mut json = serde_json::to_value(position).unwrap_or_default();
+                    json["type"] = "position".into();
+                    json
+                }
+            }
+        }
+    }
+
+    // Called by `run_notifier` for every notification, one at a time in order. An error is
+    // logged and doesn't stop the notifier. The future is `Send`, so `Notifier::notify` can run
+    // on a spawned task.
+    pub trait NotificationHook {
+        fn notify<'b>(&'b self, notification: &'b Notification) -> HookFuture<'b>;
+    }
+
+    // POSTs `{"text": <Notification::text>, "event": <Notification::to_json>}`, which a Slack
+    // incoming webhook takes as is. Add fields for other services, e.g. `chat_id` for
+    // Telegram's sendMessage, and rename the text field with `text_key` (`content` on Discord).
+    #[derive(Debug, Clone)]
+    pub struct WebhookHook {
+        url: String,
+        text_key: String,
+        fields: serde_json::Map<String, Value>,
+        client: reqwest::Client,
+    }
+
+    impl WebhookHook {
+        pub fn new(url: &str) -> Self {
+            WebhookHook {
+                url: url.to_string(),
+                text_key: "text".to_string(),
+                fields: serde_json::Map::new(),
+                client: reqwest::Client::new(),
+            }
+        }
+
+        pub fn text_key(mut self, text_key: &str) -> Self {
+            self.text_key = text_key.to_string();
+            self
+        }
+
+        pub fn field(mut self, key: &str, value: impl Into<Value>) -> Self {
+            self.fields.insert(key.to_string(), value.into());
+            self
+        }
+
+        pub fn body(&self, notification: &Notification) -> Value {
+            let mut body = self.fields.clone();
+            body.insert(self.text_key.clone(), notification.text().into());
+            body.insert("event".to_string(), notification.to_json());
+            Value::Object(body)
+        }
+    }
+
+    impl NotificationHook for WebhookHook {
+        fn notify<'b>(&'b self, notification: &'b Notification) -> HookFuture<'b> {
+            Box::pin(async move {
+                let res = self
+                    .client
+                    .post(&self.url)
+                    .header("Content-Type", "application/json")
+                    .body(self.body(notification).to_string())
+                    .send()
+                    .await?;
+                if !res.status().is_success() {
+                    return Err(format!("Webhook answered {}.", res.status()).into());
+                }
+                Ok(())
+            })
+        }
+    }
+
+    // Runs `program` with `args` and the notification text as the last argument, and the JSON in
+    // the BINANCE_NOTIFICATION environment variable. It isn't waited for.
+    #[derive(Debug, Clone)]
+    pub struct CommandHook {
+        program: String,
+        args: Vec<String>,
+    }
+
+    impl CommandHook {
+        pub fn new(program: &str, args: &Vec<&str>) -> Self {
+            CommandHook {
+                program: program.to_string(),
+                args: args.iter().map(|a| a.to_string()).collect(),
+            }
+        }
+    }
+
+    impl NotificationHook for CommandHook {
+        fn notify<'b>(&'b self, notification: &'b Notification) -> HookFuture<'b> {
+            Box::pin(async move {
+                let mut child = Command::new(&self.program)
+                    .args(&self.args)
+                    .arg(notification.text())
+                    .env("BINANCE_NOTIFICATION", notification.to_json().to_string())
+                    .spawn()?;
+                thread::spawn(move || child.wait());
+                Ok(())
+            })
+        }
+    }
+
+    // The hooks `run_notifier` calls, and which notifications they get.
+    pub struct Notifier {
+        hooks: Vec<Box<dyn NotificationHook + Send + Sync>>,
+        pub fills: bool,
+        pub position_changes: bool,
+    }
+
+    impl Notifier {
+        pub fn new() -> Self {
+            Notifier {
+                hooks: vec![],
+                fills: true,
+                position_changes: true,
+            }
+        }
+
+        pub fn hook(mut self, hook: impl NotificationHook + Send + Sync + 'static) -> Self {
+            self.hooks.push(Box::new(hook));
+            self
+        }
+
+        pub fn fills(mut self, fills: bool) -> Self {
+            self.fills = fills;
+            self
+        }
+
+        pub fn position_changes(mut self, position_changes: bool) -> Self {
+            self.position_changes = position_changes;
+            self
+        }
+
+        // Calls every hook with `notification` unless its kind is switched off.
+        pub async fn notify(&self, notification: &Notification) {
+            let wanted = match notification {
+                Notification::Fill(_) => self.fills,
+                Notification::PositionChange(_) => self.position_changes,
+            };
+            if !wanted {
+                return;
+            }
+            for hook in &self.hooks {
+                if let Err(e) = hook.notify(notification).await {
+                    tracing::warn!(error = %e, "notification hook failed");
+                }
+            }
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Sends the fills and position changes of the user stream to `notifier`'s hooks until the
+        // stream ends; needs the listen key.
+        pub async fn run_notifier(&self, notifier: &Notifier) -> Result<(), Box<dyn Error>> {
+            let mut events = self.stream_user_data();
+            while let Some(event) = events.recv().await {
+                let event = match event {
+                    UserDataEvent::Event(event) => event,
+                    UserDataEvent::Gap { since, .. } => {
+                        tracing::warn!(since = since, "user stream gap, fills may be missed");
+                        continue;
+                    }
+                };
+                for notification in Notification::from_event(&event) {
+                    notifier.notify(&notification).await;
+                }
+            }
+            Err("User data stream ended.".into())
+        }
+    }
+}
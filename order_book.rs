@@ -0,0 +1,497 @@
+pub mod order_book {
+    #[cfg(feature = "websocket")]
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::{DepthEvent, DepthSnapshot, PriceLevel};
+    use rust_decimal::Decimal;
+    #[cfg(feature = "websocket")]
+    use std::sync::Mutex;
+    use std::{
+        cmp::Reverse,
+        collections::BTreeMap,
+        error::Error,
+        ops::Bound::{Excluded, Included},
+    };
+
+    // Resting quantity within `bps` basis points of the mid price.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct DepthBand {
+        pub bps: Decimal,
+        pub bid_qty: Decimal,
+        pub ask_qty: Decimal,
+        pub bid_notional: Decimal,
+        pub ask_notional: Decimal,
+    }
+
+    impl DepthBand {
+        // (bid - ask) / (bid + ask) of the quantities: 1 when only bids rest, -1 when only asks.
+        pub fn imbalance(&self) -> Decimal {
+            let total = self.bid_qty + self.ask_qty;
+            if total.is_zero() {
+                return Decimal::ZERO;
+            }
+            (self.bid_qty - self.ask_qty) / total
+        }
+    }
+
+    // Average price of a market order of `qty` walking the book; None when the book is thinner.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct FillCost {
+        pub qty: Decimal,
+        pub buy_vwap: Option<Decimal>,
+        pub sell_vwap: Option<Decimal>,
+    }
+
+    // The analytics `OrderBook` keeps for its configured bands and sizes.
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct BookMetrics {
+        pub mid_price: Decimal,
+        pub spread: Decimal,
+        pub bands: Vec<DepthBand>,
+        pub fills: Vec<FillCost>,
+        pub last_update_id: i64,
+    }
+
+    // A local order book synced from a depth snapshot and the diff depth stream, as Binance
+    // describes it; `run_order_book` does both. `metrics` is kept for the bands set with
+    // `depth_bands` and the sizes set with `fill_sizes`: a diff moves the band sums by the levels
+    // it changed and by the levels the mid price moved the band edges over, and the fill costs
+    // walk the top of the book only.
+    #[derive(Debug, Clone)]
+    pub struct OrderBook {
+        pub symbol: String,
+        bids: BTreeMap<Reverse<Decimal>, Decimal>,
+        asks: BTreeMap<Decimal, Decimal>,
+        last_update_id: i64,
+        // A diff was applied on top of the snapshot held.
+        synced: bool,
+        band_bps: Vec<Decimal>,
+        fill_sizes: Vec<Decimal>,
+        metrics: BookMetrics,
+        // The lowest bid and highest ask price in each band of `metrics`; None without a mid.
+        band_edges: Option<Vec<(Decimal, Decimal)>>,
+    }
+
+    impl OrderBook {
+        pub fn new(symbol: &str) -> Self {
+            OrderBook {
+                symbol: symbol.to_uppercase(),
+                bids: BTreeMap::new(),
+                asks: BTreeMap::new(),
+                last_update_id: 0,
+                synced: false,
+                band_bps: vec![],
+                fill_sizes: vec![],
+                metrics: BookMetrics::default(),
+                band_edges: None,
+            }
+        }
+
+        pub fn depth_bands(mut self, bps: &[Decimal]) -> Self {
+            self.band_bps = bps.to_vec();
+            self.recompute();
+            self
+        }
+
+        pub fn fill_sizes(mut self, qtys: &[Decimal]) -> Self {
+            self.fill_sizes = qtys.to_vec();
+            self.recompute();
+            self
+        }
+
+        // Replaces every level; diffs must follow from this snapshot on.
+        pub fn apply_snapshot(&mut self, snapshot: &DepthSnapshot) {
+            self.bids.clear();
+            self.asks.clear();
+            for level in &snapshot.bids {
+                self.set_bid(level);
+            }
+            for level in &snapshot.asks {
+                self.set_ask(level);
+            }
+            self.last_update_id = snapshot.last_update_id;
+            self.synced = false;
+            self.recompute();
+        }
+
+        // Returns false for a diff older than the book. An Err means updates were missed and a new
+        // snapshot is needed.
+        pub fn apply_diff(&mut self, diff: &DepthEvent) -> Result<bool, Box<dyn Error>> {
+            if self.last_update_id == 0 {
+                return Err(format!("No snapshot of `{}` yet.", self.symbol).into());
+            }
+            // Futures diffs carry the previous final id; spot ones follow on by one.
+            let next = match diff.prev_final_update_id {
+                Some(_) if !self.synced => self.last_update_id,
+                Some(prev) => {
+                    if diff.final_update_id <= self.last_update_id {
+                        return Ok(false);
+                    }
+                    if prev != self.last_update_id {
+                        return Err(self.gap(diff));
+                    }
+                    diff.first_update_id
+                }
+                None => self.last_update_id + 1,
+            };
+            if diff.final_update_id < next {
+                return Ok(false);
+            }
+            if diff.first_update_id > next {
+                return Err(self.gap(diff));
+            }
+            // (is a bid, price, change of qty)
+            let mut changes = vec![];
+            for level in &diff.bids {
+                changes.push((true, level.price, self.set_bid(level)));
+            }
+            for level in &diff.asks {
+                changes.push((false, level.price, self.set_ask(level)));
+            }
+            self.last_update_id = diff.final_update_id;
+            self.synced = true;
+            self.update_metrics(&changes);
+            Ok(true)
+        }
+
+        fn gap(&self, diff: &DepthEvent) -> Box<dyn Error> {
+            format!(
+                "Depth of `{}` jumped from {} to {}.",
+                self.symbol, self.last_update_id, diff.first_update_id
+            )
+            .into()
+        }
+
+        // Both return how much the quantity at the level changed.
+        fn set_bid(&mut self, level: &PriceLevel) -> Decimal {
+            let old = if level.qty.is_zero() {
+                self.bids.remove(&Reverse(level.price))
+            } else {
+                self.bids.insert(Reverse(level.price), level.qty)
+            };
+            level.qty - old.unwrap_or_default()
+        }
+
+        fn set_ask(&mut self, level: &PriceLevel) -> Decimal {
+            let old = if level.qty.is_zero() {
+                self.asks.remove(&level.price)
+            } else {
+                self.asks.insert(level.price, level.qty)
+            };
+            level.qty - old.unwrap_or_default()
+        }
+
+        fn band_edges(&self, mid: Decimal) -> Vec<(Decimal, Decimal)> {
+            self.band_bps
+                .iter()
+                .map(|a| {
+                    let offset = mid * a / Decimal::from(10_000);
+                    (mid - offset, mid + offset)
+                })
+                .collect()
+        }
+
+        // Moves the band sums by `changes`, which were counted against the edges held, then
+        // moves the edges to the new mid.
+        fn update_metrics(&mut self, changes: &[(bool, Decimal, Decimal)]) {
+            let (mid, old_edges) = match (self.mid_price(), self.band_edges.take()) {
+                (Some(mid), Some(old_edges)) => (mid, old_edges),
+                _ => return self.recompute(),
+            };
+            let new_edges = self.band_edges(mid);
+            let mut bands = std::mem::take(&mut self.metrics.bands);
+            for (band, (old, new)) in bands.iter_mut().zip(old_edges.iter().zip(&new_edges)) {
+                for (is_bid, price, delta) in changes {
+                    if *is_bid && *price >= old.0 {
+                        band.bid_qty += delta;
+                        band.bid_notional += price * delta;
+                    } else if !is_bid && *price <= old.1 {
+                        band.ask_qty += delta;
+                        band.ask_notional += price * delta;
+                    }
+                }
+                // A band holds the bids at or above its bid edge and the asks at or below its
+                // ask edge.
+                let (bids, bid_sign) = if new.0 < old.0 {
+                    (
+                        self.bids
+                            .range((Excluded(Reverse(old.0)), Included(Reverse(new.0)))),
+                        1,
+                    )
+                } else {
+                    (
+                        self.bids
+                            .range((Excluded(Reverse(new.0)), Included(Reverse(old.0)))),
+                        -1,
+                    )
+                };
+                for (price, qty) in bids.filter(|_| new.0 != old.0) {
+                    band.bid_qty += Decimal::from(bid_sign) * qty;
+                    band.bid_notional += Decimal::from(bid_sign) * price.0 * qty;
+                }
+                let (asks, ask_sign) = if new.1 > old.1 {
+                    (self.asks.range((Excluded(old.1), Included(new.1))), 1)
+                } else {
+                    (self.asks.range((Excluded(new.1), Included(old.1))), -1)
+                };
+                for (price, qty) in asks.filter(|_| new.1 != old.1) {
+                    band.ask_qty += Decimal::from(ask_sign) * qty;
+                    band.ask_notional += Decimal::from(ask_sign) * price * qty;
+                }
+            }
+            self.band_edges = Some(new_edges);
+            self.metrics = BookMetrics {
+                bands: bands,
+                ..self.top_metrics()
+            };
+        }
+
+        // Everything but the bands, from the top of the book.
+        fn top_metrics(&self) -> BookMetrics {
+            BookMetrics {
+                mid_price: self.mid_price().unwrap_or_default(),
+                spread: match (self.best_bid(), self.best_ask()) {
+                    (Some(bid), Some(ask)) => ask.price - bid.price,
+                    _ => Decimal::ZERO,
+                },
+                bands: vec![],
+                fills: self
+                    .fill_sizes
+                    .iter()
+                    .map(|a| FillCost {
+                        qty: *a,
+                        buy_vwap: self.vwap_to_fill("BUY", *a),
+                        sell_vwap: self.vwap_to_fill("SELL", *a),
+                    })
+                    .collect(),
+                last_update_id: self.last_update_id,
+            }
+        }
+
+        // From scratch, after a snapshot or while there is no mid price.
+        fn recompute(&mut self) {
+            self.band_edges = self.mid_price().map(|a| self.band_edges(a));
+            self.metrics = BookMetrics {
+                bands: self
+                    .band_bps
+                    .iter()
+                    .map(|a| self.depth_within(*a))
+                    .collect(),
+                ..self.top_metrics()
+            };
+        }
+
+        pub fn metrics(&self) -> &BookMetrics {
+            &self.metrics
+        }
+
+        pub fn last_update_id(&self) -> i64 {
+            self.last_update_id
+        }
+
+        pub fn best_bid(&self) -> Option<PriceLevel> {
+            self.bids.iter().next().map(|(price, qty)| PriceLevel {
+                price: price.0,
+                qty: *qty,
+            })
+        }
+
+        pub fn best_ask(&self) -> Option<PriceLevel> {
+            self.asks.iter().next().map(|(price, qty)| PriceLevel {
+                price: *price,
+                qty: *qty,
+            })
+        }
+
+        pub fn mid_price(&self) -> Option<Decimal> {
+            Some((self.best_bid()?.price + self.best_ask()?.price) / Decimal::TWO)
+        }
+
+        // The best `levels` of each side, bids then asks.
+        pub fn levels(&self, levels: usize) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+            (
+                self.bids
+                    .iter()
+                    .take(levels)
+                    .map(|(price, qty)| PriceLevel {
+                        price: price.0,
+                        qty: *qty,
+                    })
+                    .collect(),
+                self.asks
+                    .iter()
+                    .take(levels)
+                    .map(|(price, qty)| PriceLevel {
+                        price: *price,
+                        qty: *qty,
+                    })
+                    .collect(),
+            )
+        }
+
+        pub fn depth_within(&self, bps: Decimal) -> DepthBand {
+            let mut band = DepthBand {
+                bps: bps,
+                ..Default::default()
+            };
+            let mid = match self.mid_price() {
+                Some(mid) => mid,
+                None => return band,
+            };
+            let offset = mid * bps / Decimal::from(10_000);
+            for (price, qty) in self.bids.range(..=Reverse(mid - offset)) {
+                band.bid_qty += qty;
+                band.bid_notional += price.0 * qty;
+            }
+            for (price, qty) in self.asks.range(..=mid + offset) {
+                band.ask_qty += qty;
+                band.ask_notional += price * qty;
+            }
+            band
+        }
+
+        pub fn imbalance(&self, bps: Decimal) -> Decimal {
+            self.depth_within(bps).imbalance()
+        }
+
+        // A BUY walks the asks, a SELL the bids.
+        pub fn vwap_to_fill(&self, side: &str, qty: Decimal) -> Option<Decimal> {
+            let levels: Box<dyn Iterator<Item = (Decimal, Decimal)>> =
+                if side.eq_ignore_ascii_case("BUY") {
+                    Box::new(self.asks.iter().map(|(price, qty)| (*price, *qty)))
+                } else {
+                    Box::new(self.bids.iter().map(|(price, qty)| (price.0, *qty)))
+                };
+            if qty <= Decimal::ZERO {
+                return None;
+            }
+            let mut left = qty;
+            let mut notional = Decimal::ZERO;
+            for (price, level_qty) in levels {
+                let take = left.min(level_qty);
+                notional += price * take;
+                left -= take;
+                if left.is_zero() {
+                    return Some(notional / qty);
+                }
+            }
+            None
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    impl<'a> BinanceAPI<'a> {
+        // Keeps `book` synced from the `@depth@<update_ms>ms` stream until it ends, taking a new
+        // snapshot whenever updates were missed. Read the book from another task through the mutex.
+        pub async fn run_order_book(
+            &self,
+            book: &Mutex<OrderBook>,
+            update_ms: u32,
+        ) -> Result<(), Box<dyn Error>> {
+            let symbol = book.lock().unwrap().symbol.clone();
+            // Subscribed before the snapshot so no update in between is missed.
+            let mut diffs = self.stream_depth_diffs(&symbol, update_ms);
+            let mut needs_snapshot = true;
+            while let Some(diff) = diffs.recv().await {
+                if needs_snapshot {
+                    let snapshot = self.get_depth(&symbol, 1000).await?;
+                    book.lock().unwrap().apply_snapshot(&snapshot);
+                    needs_snapshot = false;
+                }
+                if let Err(e) = book.lock().unwrap().apply_diff(&diff) {
+                    tracing::warn!(symbol = %symbol, error = %e, "order book out of sync, resyncing");
+                    needs_snapshot = true;
+                }
+            }
+            Err("Depth stream ended.".into())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn levels(levels: &[(&str, &str)]) -> Vec<PriceLevel> {
+            levels
+                .iter()
+                .map(|(price, qty)| PriceLevel {
+                    price: price.parse().unwrap(),
+                    qty: qty.parse().unwrap(),
+                })
+                .collect()
+        }
+
+        fn diff(update_id: i64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> DepthEvent {
+            DepthEvent {
+                event_time: 0,
+                symbol: "BTCUSDT".to_string(),
+                first_update_id: update_id,
+                final_update_id: update_id,
+                prev_final_update_id: None,
+                bids: levels(bids),
+                asks: levels(asks),
+            }
+        }
+
+        #[test]
+        fn band_sums_follow_diffs_that_move_the_mid() {
+            let bps = ["5", "10", "50", "100", "300"].map(|a| a.parse::<Decimal>().unwrap());
+            let mut book = OrderBook::new("BTCUSDT").depth_bands(&bps);
+            book.apply_snapshot(&DepthSnapshot {
+                last_update_id: 100,
+                event_time: None,
+                transaction_time: None,
+                bids: levels(&[
+                    ("99.95", "1"),
+                    ("99.9", "2"),
+                    ("99.5", "3"),
+                    ("99", "4"),
+                    ("98", "5"),
+                    ("97", "6"),
+                ]),
+                asks: levels(&[
+                    ("100.05", "1"),
+                    ("100.1", "2"),
+                    ("100.5", "3"),
+                    ("101", "4"),
+                    ("102", "5"),
+                    ("103", "6"),
+                ]),
+            });
+            let diffs = [
+                // Up: the best ask is taken and a bid steps in front.
+                diff(101, &[("100.02", "1.5")], &[("100.05", "0")]),
+                // Further up, with a change inside the bands.
+                diff(
+                    102,
+                    &[("100.3", "2"), ("99", "7")],
+                    &[("100.1", "0"), ("100.5", "1")],
+                ),
+                // Down past the snapshot mid.
+                diff(
+                    103,
+                    &[("100.3", "0"), ("100.02", "0"), ("99.95", "0")],
+                    &[("99.97", "2.5"), ("102", "0")],
+                ),
+                // Further down, changing levels on both sides of the old edges.
+                diff(
+                    104,
+                    &[("99.9", "0"), ("99.5", "0.5"), ("96", "8")],
+                    &[("99.97", "0"), ("99.6", "1"), ("101", "9")],
+                ),
+                // Back up to where it started.
+                diff(105, &[("99.95", "1")], &[("99.6", "0"), ("100.05", "1")]),
+            ];
+            for event in &diffs {
+                assert!(book.apply_diff(event).unwrap());
+                for (i, bps) in bps.iter().enumerate() {
+                    assert_eq!(
+                        book.metrics().bands[i],
+                        book.depth_within(*bps),
+                        "{bps}bps after update {}",
+                        event.final_update_id
+                    );
+                }
+            }
+        }
+    }
+}
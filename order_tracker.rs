@@ -0,0 +1,250 @@
+pub mod order_tracker {
+    use crate::binance_api::binance_api::{BinanceAPI, BinanceError};
+    use crate::execution::execution::decimal_field;
+    use crate::models::models::{NewOrder, OrderUpdate};
+    #[cfg(feature = "websocket")]
+    use crate::stream::stream::UserDataEvent;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    #[cfg(feature = "websocket")]
+    use std::time::{Duration, Instant};
+    use std::{collections::HashMap, error::Error};
+    #[cfg(feature = "websocket")]
+    use tokio::sync::mpsc;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TrackedOrder {
+        pub symbol: String,
+        pub client_order_id: String,
+        pub order_id: i64,
+        pub side: String,
+        pub order_type: String,
+        pub price: Decimal,
+        pub orig_qty: Decimal,
+        pub executed_qty: Decimal,
+        pub status: String,
+        // Time of the last update applied, from the exchange.
+        pub update_time: i64,
+    }
+
+    impl TrackedOrder {
+        pub fn is_open(&self) -> bool {
+            ["NEW", "PARTIALLY_FILLED", "PENDING_NEW"].contains(&self.status.as_str())
+        }
+
+        // Fields missing from `order` (an ACK response has no status) are left as they are.
+        fn apply_rest(&mut self, order: &Value) {
+            let update_time = ["updateTime", "transactTime", "time"]
+                .iter()
+                .find_map(|a| order[*a].as_i64())
+                .unwrap_or_default();
+            if update_time < self.update_time {
+                return;
+            }
+            self.update_time = update_time;
+            if let Some(order_id) = order["orderId"].as_i64() {
+                self.order_id = order_id;
+            }
+            if let Some(status) = order["status"].as_str() {
+                self.status = status.to_string();
+            }
+            if let Some(executed_qty) = decimal_field(order, "executedQty") {
+                self.executed_qty = executed_qty;
+            }
+        }
+    }
+
+    // Every order placed through `place_tracked`, kept up to date from user stream execution
+    // updates and REST reconciliation. Updates older than the last one applied are dropped, so
+    // the two sources can arrive in any order.
+    #[derive(Debug, Clone, Default)]
+    pub struct OrderTracker {
+        orders: HashMap<String, TrackedOrder>,
+        next_id: u64,
+    }
+
+    impl OrderTracker {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        pub fn track(&mut self, order: &NewOrder, response: &Value) -> &TrackedOrder {
+            let client_order_id = response["clientOrderId"]
+                .as_str()
+                .unwrap_or(&order.new_client_order_id)
+                .to_string();
+            let mut tracked = TrackedOrder {
+                symbol: order.symbol.clone(),
+                client_order_id: client_order_id.clone(),
+                order_id: 0,
+                side: order.side.clone(),
+                order_type: order.order_type.clone(),
+                price: order.price.unwrap_or_default(),
+                orig_qty: order.quantity.unwrap_or_default(),
+                executed_qty: Decimal::ZERO,
+                status: "NEW".to_string(),
+                update_time: 0,
+            };
+            tracked.apply_rest(response);
+            self.orders.insert(client_order_id.clone(), tracked);
+            &self.orders[&client_order_id]
+        }
+
+        // Returns whether the update was for a tracked order and newer than its state.
+        pub fn apply_update(&mut self, update: &OrderUpdate) -> bool {
+            match self.orders.get_mut(&update.client_order_id) {
+                Some(order) if update.event_time >= order.update_time => {
+                    order.update_time = update.event_time;
+                    order.order_id = update.order_id;
+                    order.status = update.status.clone();
+                    order.executed_qty = update.cum_filled_qty;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        // Applies an order as returned by `get_order` or `get_open_orders`.
+        pub fn apply_rest(&mut self, order: &Value) -> bool {
+            let client_order_id = order["clientOrderId"].as_str().unwrap_or_default();
+            match self.orders.get_mut(client_order_id) {
+                Some(tracked) => {
+                    let before = tracked.clone();
+                    tracked.apply_rest(order);
+                    *tracked != before
+                }
+                None => false,
+            }
+        }
+
+        pub fn get(&self, client_order_id: &str) -> Option<&TrackedOrder> {
+            self.orders.get(client_order_id)
+        }
+
+        pub fn open(&self) -> Vec<&TrackedOrder> {
+            self.orders.values().filter(|a| a.is_open()).collect()
+        }
+
+        pub fn partially_filled(&self) -> Vec<&TrackedOrder> {
+            self.orders
+                .values()
+                .filter(|a| a.status == "PARTIALLY_FILLED")
+                .collect()
+        }
+
+        pub fn done(&self) -> Vec<&TrackedOrder> {
+            self.orders.values().filter(|a| !a.is_open()).collect()
+        }
+
+        // Forgets finished orders so a long running tracker doesn't grow without bound.
+        pub fn clear_done(&mut self) {
+            self.orders.retain(|_, a| a.is_open());
+        }
+
+        fn next_client_order_id(&mut self) -> String {
+            self.next_id += 1;
+            format!("trk_{}_{}", Utc::now().timestamp_millis(), self.next_id)
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Places `order` and starts tracking it; orders without a client order id get one.
+        pub async fn place_tracked(
+            &self,
+            tracker: &mut OrderTracker,
+            order: &NewOrder,
+        ) -> Result<Value, Box<dyn Error>> {
+            let order = if order.new_client_order_id.is_empty() {
+                order
+                    .clone()
+                    .client_order_id(&tracker.next_client_order_id())
+            } else {
+                order.clone()
+            };
+            let response = self.place_order(&order).await?;
+            tracker.track(&order, &response);
+            Ok(response)
+        }
+
+        // Refreshes every open tracked order from `get_open_orders`, and the ones no longer open
+        // on the exchange from `get_order`. Returns how many orders changed.
+        pub async fn reconcile_orders(
+            &self,
+            tracker: &mut OrderTracker,
+        ) -> Result<usize, Box<dyn Error>> {
+            let mut symbols = tracker
+                .open()
+                .iter()
+                .map(|a| a.symbol.clone())
+                .collect::<Vec<_>>();
+            symbols.sort();
+            symbols.dedup();
+            let mut changed = 0;
+            for symbol in symbols {
+                let open_orders = self.get_open_orders(&symbol).await?;
+                let open_orders = open_orders.as_array().cloned().unwrap_or_default();
+                for order in &open_orders {
+                    changed += tracker.apply_rest(order) as usize;
+                }
+                let missing = tracker
+                    .open()
+                    .iter()
+                    .filter(|a| {
+                        a.symbol == symbol
+                            && !open_orders.iter().any(|b| {
+                                b["clientOrderId"].as_str() == Some(a.client_order_id.as_str())
+                            })
+                    })
+                    .map(|a| a.client_order_id.clone())
+                    .collect::<Vec<_>>();
+                for client_order_id in missing {
+                    match self.get_order(&symbol, "", &client_order_id).await {
+                        Ok(order) => changed += tracker.apply_rest(&order) as usize,
+                        // Unknown to the exchange, e.g. rejected before it was recorded.
+                        Err(e)
+                            if e.downcast_ref::<BinanceError>().map(|a| a.code) == Some(-2013) =>
+                        {
+                            let order = tracker.orders.get_mut(&client_order_id).unwrap();
+                            order.status = "REJECTED".to_string();
+                            changed += 1;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            Ok(changed)
+        }
+
+        // Applies `stream_user_data` updates to the tracker until the channel closes, reconciling
+        // every `reconcile_every` and right after a stream gap.
+        #[cfg(feature = "websocket")]
+        pub async fn run_order_tracker(
+            &self,
+            tracker: &mut OrderTracker,
+            user_data: &mut mpsc::Receiver<UserDataEvent>,
+            reconcile_every: Duration,
+        ) -> Result<(), Box<dyn Error>> {
+            let mut next_reconcile = Instant::now() + reconcile_every;
+            loop {
+                let wait = next_reconcile.saturating_duration_since(Instant::now());
+                match tokio::time::timeout(wait, user_data.recv()).await {
+                    Ok(Some(UserDataEvent::Event(event))) => {
+                        if let Some(update) = OrderUpdate::from_event(&event) {
+                            tracker.apply_update(&update);
+                        }
+                    }
+                    Ok(Some(UserDataEvent::Gap { .. })) => {
+                        self.reconcile_orders(tracker).await?;
+                        next_reconcile = Instant::now() + reconcile_every;
+                    }
+                    Ok(None) => return Ok(()),
+                    Err(_) => {
+                        self.reconcile_orders(tracker).await?;
+                        next_reconcile = Instant::now() + reconcile_every;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,526 @@
+pub mod paper {
+    use crate::binance_api::binance_api::{BinanceAPI, BinanceError};
+    #[cfg(feature = "usdm-futures")]
+    use crate::models::models::Position;
+    use crate::models::models::{AssetBalance, NewOrder};
+    use crate::symbol_registry::symbol_registry::SymbolInfo;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use serde::Serialize;
+    use std::{collections::HashMap, error::Error};
+
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PaperOrder {
+        pub symbol: String,
+        pub order_id: i64,
+        pub client_order_id: String,
+        pub price: Decimal,
+        pub avg_price: Decimal,
+        pub orig_qty: Decimal,
+        pub executed_qty: Decimal,
+        pub status: String,
+        pub time_in_force: String,
+        #[serde(rename = "type")]
+        pub order_type: String,
+        pub side: String,
+        pub reduce_only: bool,
+        pub update_time: i64,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PaperPosition {
+        pub position_amt: Decimal,
+        pub entry_price: Decimal,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct PaperAccount {
+        pub balances: HashMap<String, Decimal>,
+        pub positions: HashMap<String, PaperPosition>,
+        pub open_orders: Vec<PaperOrder>,
+        next_order_id: i64,
+    }
+
+    fn insufficient_balance() -> BinanceError {
+        BinanceError {
+            code: -2010,
+            msg: "Account has insufficient balance for requested action.".to_string(),
+        }
+    }
+
+    impl PaperAccount {
+        pub fn new(balances: HashMap<String, Decimal>) -> Self {
+            PaperAccount {
+                balances: balances,
+                next_order_id: 1,
+                ..Default::default()
+            }
+        }
+
+        pub fn balance(&self, asset: &str) -> Decimal {
+            self.balances.get(asset).cloned().unwrap_or_default()
+        }
+
+        // Like `get_balance`; resting paper orders lock nothing.
+        pub fn asset_balances(&self) -> Vec<AssetBalance> {
+            let mut balances = self
+                .balances
+                .iter()
+                .map(|(asset, amount)| AssetBalance {
+                    asset: asset.clone(),
+                    free: *amount,
+                    balance: *amount,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+            balances
+        }
+
+        fn fill(
+            &mut self,
+            account_type: &str,
+            info: &SymbolInfo,
+            side: &str,
+            qty: Decimal,
+            price: Decimal,
+        ) -> Result<(), BinanceError> {
+            let signed_qty = if side == "BUY" { qty } else { -qty };
+            if account_type == "spot" {
+                let (base, quote) = (
+                    self.balance(&info.base_asset),
+                    self.balance(&info.quote_asset),
+                );
+                let (base, quote) = (base + signed_qty, quote - signed_qty * price);
+                if base < Decimal::ZERO || quote < Decimal::ZERO {
+                    return Err(insufficient_balance());
+                }
+                self.balances.insert(info.base_asset.clone(), base);
+                self.balances.insert(info.quote_asset.clone(), quote);
+                return Ok(());
+            }
+            let position = self.positions.entry(info.symbol.clone()).or_default();
+            let amount = position.position_amt;
+            let realized = if amount.is_zero()
+                || amount.is_sign_positive() == signed_qty.is_sign_positive()
+            {
+                let new_amount = amount + signed_qty;
+                position.entry_price =
+                    (position.entry_price * amount.abs() + price * qty) / new_amount.abs();
+                position.position_amt = new_amount;
+                Decimal::ZERO
+            } else {
+                let closed = if amount.is_sign_positive() {
+                    qty.min(amount)
+                } else {
+                    -qty.min(-amount)
+                };
+                let pnl = (price - position.entry_price) * closed;
+                position.position_amt = amount + signed_qty;
+                if position.position_amt.is_zero() {
+                    position.entry_price = Decimal::ZERO;
+                } else if position.position_amt.is_sign_positive() != amount.is_sign_positive() {
+                    position.entry_price = price;
+                }
+                pnl
+            };
+            let quote = self.balance(&info.quote_asset) + realized;
+            self.balances.insert(info.quote_asset.clone(), quote);
+            Ok(())
+        }
+
+        fn marketable_price(order: &PaperOrder, bid: Decimal, ask: Decimal) -> Option<Decimal> {
+            match (order.order_type.as_str(), order.side.as_str()) {
+                ("MARKET", "BUY") => Some(ask),
+                ("MARKET", _) => Some(bid),
+                (_, "BUY") if order.price >= ask => Some(ask.min(order.price)),
+                (_, "SELL") if order.price <= bid => Some(bid.max(order.price)),
+                _ => None,
+            }
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        pub fn enable_paper_trading(&self, balances: HashMap<String, Decimal>) {
+            *self.paper.lock().unwrap() = Some(PaperAccount::new(balances));
+        }
+
+        pub fn disable_paper_trading(&self) {
+            *self.paper.lock().unwrap() = None;
+        }
+
+        pub fn is_paper_trading(&self) -> bool {
+            self.paper.lock().unwrap().is_some()
+        }
+
+        pub fn paper_account(&self) -> Option<PaperAccount> {
+            self.paper.lock().unwrap().clone()
+        }
+
+        // For the order methods paper trading doesn't simulate, so they fail instead of reaching
+        // the exchange.
+        pub(crate) fn ensure_not_paper(&self, method: &str) -> Result<(), Box<dyn Error>> {
+            if self.is_paper_trading() {
+                return Err(format!("`{method}` is not available in paper trading.").into());
+            }
+            Ok(())
+        }
+
+        // `pull_account` shaped like the spot account or the futures account.
+        pub(crate) fn paper_pull_account(&self, account: &PaperAccount) -> serde_json::Value {
+            let update_time = Utc::now().timestamp_millis();
+            let balances = account.asset_balances();
+            if self.account_type == "spot" {
+                let balances = balances
+                    .iter()
+                    .map(|a| {
+                        serde_json::json!({
+                            "asset": a.asset,
+                            "free": a.free.to_string(),
+                            "locked": "0",
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                return serde_json::json!({ "balances": balances, "updateTime": update_time });
+            }
+            let assets = balances
+                .iter()
+                .map(|a| {
+                    serde_json::json!({
+                        "asset": a.asset,
+                        "walletBalance": a.balance.to_string(),
+                        "availableBalance": a.free.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut positions = account
+                .positions
+                .iter()
+                .filter(|(_, position)| !position.position_amt.is_zero())
+                .map(|(symbol, position)| {
+                    serde_json::json!({
+                        "symbol": symbol,
+                        "positionSide": "BOTH",
+                        "positionAmt": position.position_amt.to_string(),
+                        "entryPrice": position.entry_price.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            positions.sort_by(|a, b| a["symbol"].as_str().cmp(&b["symbol"].as_str()));
+            serde_json::json!({
+                "assets": assets,
+                "positions": positions,
+                "updateTime": update_time,
+            })
+        }
+
+        // Paper positions as `get_position` lists them, marked at the live mark price.
+        #[cfg(feature = "usdm-futures")]
+        pub(crate) async fn paper_positions(
+            &self,
+            account: &PaperAccount,
+            symbol: &str,
+        ) -> Result<Vec<Position>, Box<dyn Error>> {
+            let mut positions = vec![];
+            for (name, position) in &account.positions {
+                if position.position_amt.is_zero() || !(symbol.is_empty() || name == symbol) {
+                    continue;
+                }
+                let mark_price = self.get_mark_price(name).await?;
+                let mark_price: Decimal =
+                    mark_price["markPrice"].as_str().unwrap_or("0").parse()?;
+                positions.push(Position {
+                    symbol: name.clone(),
+                    position_side: "BOTH".to_string(),
+                    position_amt: position.position_amt,
+                    entry_price: position.entry_price,
+                    break_even_price: position.entry_price,
+                    mark_price: mark_price,
+                    unrealized_profit: (mark_price - position.entry_price) * position.position_amt,
+                    notional: position.position_amt * mark_price,
+                    update_time: Utc::now().timestamp_millis(),
+                    ..Default::default()
+                });
+            }
+            positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+            Ok(positions)
+        }
+
+        pub(crate) async fn paper_place_order(
+            &self,
+            order: &NewOrder,
+        ) -> Result<serde_json::Value, Box<dyn Error>> {
+            let info = self.get_symbol_info(&order.symbol).await?;
+            let book = self.get_book_ticker(&order.symbol).await?;
+            let (bid, ask) = (book[0].bid_price, book[0].ask_price);
+            let mut guard = self.paper.lock().unwrap();
+            let account = guard.as_mut().ok_or("Paper trading is not enabled.")?;
+            let mut paper_order = PaperOrder {
+                symbol: order.symbol.clone(),
+                order_id: account.next_order_id,
+                client_order_id: if order.new_client_order_id.is_empty() {
+                    format!("paper_{}", account.next_order_id)
+                } else {
+                    order.new_client_order_id.clone()
+                },
+                price: order.price.unwrap_or_default(),
+                avg_price: Decimal::ZERO,
+                orig_qty: order.quantity.unwrap_or_default(),
+                executed_qty: Decimal::ZERO,
+                status: "NEW".to_string(),
+                time_in_force: order.time_in_force.clone(),
+                order_type: order.order_type.clone(),
+                side: order.side.clone(),
+                reduce_only: order.reduce_only,
+                update_time: Utc::now().timestamp_millis(),
+            };
+            account.next_order_id += 1;
+            match PaperAccount::marketable_price(&paper_order, bid, ask) {
+                Some(fill_price) => {
+                    account.fill(
+                        self.account_type,
+                        &info,
+                        &paper_order.side,
+                        paper_order.orig_qty,
+                        fill_price,
+                    )?;
+                    paper_order.avg_price = fill_price;
+                    paper_order.executed_qty = paper_order.orig_qty;
+                    paper_order.status = "FILLED".to_string();
+                }
+                None if ["IOC", "FOK"].contains(&paper_order.time_in_force.as_str()) => {
+                    paper_order.status = "EXPIRED".to_string();
+                }
+                None => account.open_orders.push(paper_order.clone()),
+            }
+            Ok(serde_json::to_value(paper_order)?)
+        }
+
+        pub(crate) fn paper_cancel_order(
+            &self,
+            symbol: &str,
+            order_id: &str,
+            all: bool,
+        ) -> Result<serde_json::Value, Box<dyn Error>> {
+            let mut guard = self.paper.lock().unwrap();
+            let account = guard.as_mut().ok_or("Paper trading is not enabled.")?;
+            let (mut canceled, open): (Vec<_>, Vec<_>) = account
+                .open_orders
+                .drain(..)
+                .partition(|a| a.symbol == symbol && (all || a.order_id.to_string() == order_id));
+            account.open_orders = open;
+            if !all && canceled.is_empty() {
+                return Err(Box::new(BinanceError {
+                    code: -2011,
+                    msg: "Unknown order sent.".to_string(),
+                }));
+            }
+            for a in canceled.iter_mut() {
+                a.status = "CANCELED".to_string();
+            }
+            if all {
+                Ok(serde_json::to_value(canceled)?)
+            } else {
+                Ok(serde_json::to_value(&canceled[0])?)
+            }
+        }
+
+        pub(crate) fn paper_open_orders(&self, symbol: &str) -> serde_json::Value {
+            let guard = self.paper.lock().unwrap();
+            let orders = guard
+                .iter()
+                .flat_map(|a| a.open_orders.iter())
+                .filter(|a| symbol.is_empty() || a.symbol == symbol)
+                .cloned()
+                .collect::<Vec<_>>();
+            serde_json::to_value(orders).unwrap_or_default()
+        }
+
+        pub async fn paper_match(&self) -> Result<Vec<serde_json::Value>, Box<dyn Error>> {
+            let mut symbols = match self.paper.lock().unwrap().as_ref() {
+                Some(account) => account
+                    .open_orders
+                    .iter()
+                    .map(|a| a.symbol.clone())
+                    .collect::<Vec<_>>(),
+                None => return Err("Paper trading is not enabled.".into()),
+            };
+            symbols.sort();
+            symbols.dedup();
+            let mut filled = vec![];
+            for symbol in symbols {
+                let info = self.get_symbol_info(&symbol).await?;
+                let book = self.get_book_ticker(&symbol).await?;
+                let (bid, ask) = (book[0].bid_price, book[0].ask_price);
+                let mut guard = self.paper.lock().unwrap();
+                let account = guard.as_mut().ok_or("Paper trading is not enabled.")?;
+                let mut open = vec![];
+                for mut order in account.open_orders.drain(..).collect::<Vec<_>>() {
+                    match PaperAccount::marketable_price(&order, bid, ask) {
+                        Some(_) if order.symbol == symbol => {
+                            if account
+                                .fill(
+                                    self.account_type,
+                                    &info,
+                                    &order.side,
+                                    order.orig_qty,
+                                    order.price,
+                                )
+                                .is_err()
+                            {
+                                order.status = "REJECTED".to_string();
+                            } else {
+                                order.avg_price = order.price;
+                                order.executed_qty = order.orig_qty;
+                                order.status = "FILLED".to_string();
+                            }
+                            order.update_time = Utc::now().timestamp_millis();
+                            filled.push(serde_json::to_value(order)?);
+                        }
+                        _ => open.push(order),
+                    }
+                }
+                account.open_orders = open;
+            }
+            Ok(filled)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn btcusdt() -> SymbolInfo {
+            SymbolInfo::from_exchange_info(&serde_json::json!({
+                "symbol": "BTCUSDT",
+                "status": "TRADING",
+                "baseAsset": "BTC",
+                "quoteAsset": "USDT",
+            }))
+            .unwrap()
+        }
+
+        fn usdt(amount: i64) -> PaperAccount {
+            PaperAccount::new(HashMap::from([("USDT".to_string(), Decimal::from(amount))]))
+        }
+
+        fn order(order_type: &str, side: &str, price: i64) -> PaperOrder {
+            PaperOrder {
+                symbol: "BTCUSDT".to_string(),
+                order_id: 1,
+                client_order_id: "paper_1".to_string(),
+                price: Decimal::from(price),
+                avg_price: Decimal::ZERO,
+                orig_qty: Decimal::ONE,
+                executed_qty: Decimal::ZERO,
+                status: "NEW".to_string(),
+                time_in_force: "GTC".to_string(),
+                order_type: order_type.to_string(),
+                side: side.to_string(),
+                reduce_only: false,
+                update_time: 0,
+            }
+        }
+
+        #[test]
+        fn spot_fill_moves_the_quote_into_the_base_asset() {
+            let mut account = usdt(1000);
+            let info = btcusdt();
+            account
+                .fill(
+                    "spot",
+                    &info,
+                    "BUY",
+                    Decimal::new(2, 2),
+                    Decimal::from(40000),
+                )
+                .unwrap();
+            assert_eq!(account.balance("BTC"), Decimal::new(2, 2));
+            assert_eq!(account.balance("USDT"), Decimal::from(200));
+            account
+                .fill(
+                    "spot",
+                    &info,
+                    "SELL",
+                    Decimal::new(1, 2),
+                    Decimal::from(50000),
+                )
+                .unwrap();
+            assert_eq!(account.balance("BTC"), Decimal::new(1, 2));
+            assert_eq!(account.balance("USDT"), Decimal::from(700));
+        }
+
+        #[test]
+        fn spot_fill_without_the_balance_is_rejected() {
+            let mut account = usdt(1000);
+            let info = btcusdt();
+            let error = account
+                .fill("spot", &info, "BUY", Decimal::ONE, Decimal::from(40000))
+                .unwrap_err();
+            assert_eq!(error.code, -2010);
+            let error = account
+                .fill("spot", &info, "SELL", Decimal::ONE, Decimal::from(40000))
+                .unwrap_err();
+            assert_eq!(error.code, -2010);
+            assert_eq!(account.balance("BTC"), Decimal::ZERO);
+            assert_eq!(account.balance("USDT"), Decimal::from(1000));
+        }
+
+        #[test]
+        fn futures_fill_averages_the_entry_and_realizes_a_partial_close() {
+            let mut account = usdt(1000);
+            let info = btcusdt();
+            account
+                .fill("swap", &info, "BUY", Decimal::ONE, Decimal::from(100))
+                .unwrap();
+            account
+                .fill("swap", &info, "BUY", Decimal::ONE, Decimal::from(110))
+                .unwrap();
+            assert_eq!(account.positions["BTCUSDT"].position_amt, Decimal::from(2));
+            assert_eq!(account.positions["BTCUSDT"].entry_price, Decimal::from(105));
+            account
+                .fill("swap", &info, "SELL", Decimal::ONE, Decimal::from(120))
+                .unwrap();
+            assert_eq!(account.positions["BTCUSDT"].position_amt, Decimal::ONE);
+            assert_eq!(account.positions["BTCUSDT"].entry_price, Decimal::from(105));
+            assert_eq!(account.balance("USDT"), Decimal::from(1015));
+        }
+
+        #[test]
+        fn futures_fill_through_zero_flips_the_position_at_the_fill_price() {
+            let mut account = usdt(1000);
+            let info = btcusdt();
+            account
+                .fill("swap", &info, "BUY", Decimal::ONE, Decimal::from(100))
+                .unwrap();
+            account
+                .fill("swap", &info, "SELL", Decimal::from(3), Decimal::from(90))
+                .unwrap();
+            assert_eq!(account.positions["BTCUSDT"].position_amt, Decimal::from(-2));
+            assert_eq!(account.positions["BTCUSDT"].entry_price, Decimal::from(90));
+            assert_eq!(account.balance("USDT"), Decimal::from(990));
+            account
+                .fill("swap", &info, "BUY", Decimal::from(2), Decimal::from(80))
+                .unwrap();
+            assert!(account.positions["BTCUSDT"].position_amt.is_zero());
+            assert!(account.positions["BTCUSDT"].entry_price.is_zero());
+            assert_eq!(account.balance("USDT"), Decimal::from(1010));
+        }
+
+        #[test]
+        fn market_orders_take_the_touch_and_limits_fill_only_when_crossed() {
+            let (bid, ask) = (Decimal::from(99), Decimal::from(101));
+            let price = |order_type, side, price| {
+                PaperAccount::marketable_price(&order(order_type, side, price), bid, ask)
+            };
+            assert_eq!(price("MARKET", "BUY", 0), Some(ask));
+            assert_eq!(price("MARKET", "SELL", 0), Some(bid));
+            assert_eq!(price("LIMIT", "BUY", 105), Some(ask));
+            assert_eq!(price("LIMIT", "BUY", 100), None);
+            assert_eq!(price("LIMIT", "SELL", 95), Some(bid));
+            assert_eq!(price("LIMIT", "SELL", 100), None);
+        }
+    }
+}
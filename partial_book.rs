@@ -0,0 +1,197 @@
+pub mod partial_book {
+    #[cfg(feature = "websocket")]
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::PartialDepth;
+    #[cfg(feature = "websocket")]
+    use crate::stream::stream::StreamMessage;
+    use rust_decimal::Decimal;
+    #[cfg(feature = "websocket")]
+    use std::sync::Arc;
+    use std::sync::{
+        atomic::{fence, AtomicU64, Ordering},
+        RwLock,
+    };
+
+    // Best bid and ask; a side with no levels has a zero price and qty.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct TopOfBook {
+        pub bid_price: Decimal,
+        pub bid_qty: Decimal,
+        pub ask_price: Decimal,
+        pub ask_qty: Decimal,
+        pub last_update_id: i64,
+    }
+
+    impl TopOfBook {
+        pub fn mid_price(&self) -> Decimal {
+            (self.bid_price + self.ask_price) / Decimal::TWO
+        }
+
+        pub fn spread(&self) -> Decimal {
+            self.ask_price - self.bid_price
+        }
+    }
+
+    fn to_words(value: Decimal) -> [u64; 2] {
+        let bytes = value.serialize();
+        [
+            u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            u64::from_le_bytes(bytes[8..].try_into().unwrap()),
+        ]
+    }
+
+    fn from_words(low: u64, high: u64) -> Decimal {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&low.to_le_bytes());
+        bytes[8..].copy_from_slice(&high.to_le_bytes());
+        Decimal::deserialize(bytes)
+    }
+
+    // A seqlock: readers retry while the sequence is odd or changed under them, so reading never
+    // waits on a lock. There must be only one writer at a time.
+    #[derive(Debug, Default)]
+    struct TopCell {
+        seq: AtomicU64,
+        words: [AtomicU64; 9],
+    }
+
+    impl TopCell {
+        fn store(&self, top: &TopOfBook) {
+            let seq = self.seq.load(Ordering::Relaxed);
+            self.seq.store(seq + 1, Ordering::Relaxed);
+            fence(Ordering::Release);
+            let words = [
+                to_words(top.bid_price),
+                to_words(top.bid_qty),
+                to_words(top.ask_price),
+                to_words(top.ask_qty),
+            ];
+            for (i, word) in words.iter().flatten().enumerate() {
+                self.words[i].store(*word, Ordering::Relaxed);
+            }
+            self.words[8].store(top.last_update_id as u64, Ordering::Relaxed);
+            self.seq.store(seq + 2, Ordering::Release);
+        }
+
+        fn load(&self) -> Option<TopOfBook> {
+            loop {
+                let seq = self.seq.load(Ordering::Acquire);
+                if seq % 2 == 1 {
+                    std::hint::spin_loop();
+                    continue;
+                }
+                let words = self.words.each_ref().map(|a| a.load(Ordering::Relaxed));
+                fence(Ordering::Acquire);
+                if self.seq.load(Ordering::Relaxed) != seq {
+                    continue;
+                }
+                if seq == 0 {
+                    return None;
+                }
+                return Some(TopOfBook {
+                    bid_price: from_words(words[0], words[1]),
+                    bid_qty: from_words(words[2], words[3]),
+                    ask_price: from_words(words[4], words[5]),
+                    ask_qty: from_words(words[6], words[7]),
+                    last_update_id: words[8] as i64,
+                });
+            }
+        }
+    }
+
+    // The top levels of one symbol from `@depth<levels>` snapshots. `top` reads the best bid and
+    // ask without locking; `depth` copies all levels. Snapshots older than the one held are
+    // dropped.
+    #[derive(Debug)]
+    pub struct PartialBook {
+        pub symbol: String,
+        pub levels: u32,
+        top: TopCell,
+        depth: RwLock<Option<PartialDepth>>,
+    }
+
+    impl PartialBook {
+        pub fn new(symbol: &str, levels: u32) -> Self {
+            PartialBook {
+                symbol: symbol.to_uppercase(),
+                levels: levels,
+                top: Default::default(),
+                depth: RwLock::new(None),
+            }
+        }
+
+        // Returns whether `depth` replaced the levels held.
+        pub fn apply(&self, depth: &PartialDepth) -> bool {
+            // The write lock also keeps `top` to a single writer.
+            let mut held = self.depth.write().unwrap();
+            if let Some(held) = held.as_ref() {
+                if depth.last_update_id < held.last_update_id {
+                    return false;
+                }
+            }
+            let mut depth = depth.clone();
+            depth.bids.truncate(self.levels as usize);
+            depth.asks.truncate(self.levels as usize);
+            let bid = depth.bids.first();
+            let ask = depth.asks.first();
+            self.top.store(&TopOfBook {
+                bid_price: bid.map(|a| a.price).unwrap_or_default(),
+                bid_qty: bid.map(|a| a.qty).unwrap_or_default(),
+                ask_price: ask.map(|a| a.price).unwrap_or_default(),
+                ask_qty: ask.map(|a| a.qty).unwrap_or_default(),
+                last_update_id: depth.last_update_id,
+            });
+            *held = Some(depth);
+            true
+        }
+
+        // None until the first snapshot arrives.
+        pub fn top(&self) -> Option<TopOfBook> {
+            self.top.load()
+        }
+
+        pub fn best_bid(&self) -> Option<Decimal> {
+            self.top().map(|a| a.bid_price)
+        }
+
+        pub fn best_ask(&self) -> Option<Decimal> {
+            self.top().map(|a| a.ask_price)
+        }
+
+        pub fn depth(&self) -> Option<PartialDepth> {
+            self.depth.read().unwrap().clone()
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    impl<'a> BinanceAPI<'a> {
+        // A `PartialBook` kept up to date from the `@depth<levels>` stream in the background
+        // until every clone of the returned `Arc` is dropped.
+        pub fn maintain_partial_book(
+            &self,
+            symbol: &str,
+            levels: u32,
+            update_ms: u32,
+        ) -> Arc<PartialBook> {
+            let book = Arc::new(PartialBook::new(symbol, levels));
+            let url = self.stream_url(&format!(
+                "{}@{}",
+                symbol.to_lowercase(),
+                self.partial_depth_type(levels, update_ms)
+            ));
+            let shared = book.clone();
+            self.streams.spawn(url, move |message| {
+                if let StreamMessage::Text(text) = message {
+                    match serde_json::from_str::<PartialDepth>(&text) {
+                        Ok(depth) => {
+                            shared.apply(&depth);
+                        }
+                        Err(e) => tracing::warn!(error = %e, "can't parse partial depth"),
+                    }
+                }
+                Arc::strong_count(&shared) > 1
+            });
+            book
+        }
+    }
+}
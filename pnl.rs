@@ -0,0 +1,518 @@
+pub mod pnl {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::execution::execution::decimal_field;
+    use crate::models::models::{AccountTrade, CsvRecord, TimeInput};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    use std::{
+        collections::{BTreeMap, HashMap},
+        error::Error,
+    };
+
+    // Realized PnL of one symbol (one leg per position side in hedge mode), at average cost and
+    // before commissions. A round trip ends when the position goes back to zero or flips.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct SymbolPnl {
+        pub symbol: String,
+        pub position_side: String,
+        pub position_qty: Decimal,
+        pub avg_entry: Decimal,
+        pub realized_pnl: Decimal,
+        pub volume: Decimal,
+        pub trades: u64,
+        pub round_trips: u64,
+        pub winning_trips: u64,
+        pub losing_trips: u64,
+        pub gross_profit: Decimal,
+        pub gross_loss: Decimal,
+        trip_pnl: Decimal,
+    }
+
+    impl SymbolPnl {
+        pub fn win_rate(&self) -> Decimal {
+            if self.round_trips == 0 {
+                Decimal::ZERO
+            } else {
+                Decimal::from(self.winning_trips) / Decimal::from(self.round_trips)
+            }
+        }
+
+        fn finish_trip(&mut self) {
+            self.round_trips += 1;
+            if self.trip_pnl > Decimal::ZERO {
+                self.winning_trips += 1;
+                self.gross_profit += self.trip_pnl;
+            } else if self.trip_pnl < Decimal::ZERO {
+                self.losing_trips += 1;
+                self.gross_loss -= self.trip_pnl;
+            }
+            self.trip_pnl = Decimal::ZERO;
+        }
+
+        // Returns the PnL the fill realized.
+        fn apply(&mut self, is_buyer: bool, qty: Decimal, price: Decimal) -> Decimal {
+            let fill = if is_buyer { qty } else { -qty };
+            self.trades += 1;
+            self.volume += qty * price;
+            if self.position_qty.is_zero() || self.position_qty.is_sign_positive() == is_buyer {
+                let held = self.position_qty.abs();
+                self.avg_entry = (held * self.avg_entry + qty * price) / (held + qty);
+                self.position_qty += fill;
+                return Decimal::ZERO;
+            }
+            let held = self.position_qty.abs();
+            let closed = qty.min(held);
+            let direction = if self.position_qty.is_sign_positive() {
+                Decimal::ONE
+            } else {
+                Decimal::NEGATIVE_ONE
+            };
+            let pnl = closed * (price - self.avg_entry) * direction;
+            self.realized_pnl += pnl;
+            self.trip_pnl += pnl;
+            self.position_qty += fill;
+            if qty >= held {
+                self.finish_trip();
+                self.avg_entry = if qty > held { price } else { Decimal::ZERO };
+            }
+            pnl
+        }
+    }
+
+    // Feed fills oldest first. A date range that starts while a position is open sees only part
+    // of it, so the first closing fills are measured against the fills inside the range.
+    #[derive(Debug, Clone, Default)]
+    pub struct PnlCalculator {
+        legs: BTreeMap<(String, String), SymbolPnl>,
+    }
+
+    impl PnlCalculator {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        fn leg(&mut self, symbol: &str, position_side: &str) -> &mut SymbolPnl {
+            self.legs
+                .entry((symbol.to_string(), position_side.to_string()))
+                .or_insert_with(|| SymbolPnl {
+                    symbol: symbol.to_string(),
+                    position_side: position_side.to_string(),
+                    ..Default::default()
+                })
+        }
+
+        // Returns the PnL this fill realized.
+        pub fn add_trade(&mut self, trade: &AccountTrade) -> Decimal {
+            let position_side = trade.position_side.clone().unwrap_or("BOTH".to_string());
+            self.leg(&trade.symbol, &position_side)
+                .apply(trade.is_buyer, trade.qty, trade.price)
+        }
+
+        pub fn add_trades(&mut self, trades: &[AccountTrade]) {
+            let mut trades = trades.iter().collect::<Vec<_>>();
+            trades.sort_by_key(|a| (a.time, a.id));
+            for trade in trades {
+                self.add_trade(trade);
+            }
+        }
+
+        // Adds a futures `REALIZED_PNL` income record; other income types are ignored. Use either
+        // this or `add_trade` for a symbol, not both, or the PnL is counted twice.
+        pub fn add_income(&mut self, income: &Value) {
+            if income["incomeType"].as_str() != Some("REALIZED_PNL") {
+                return;
+            }
+            let symbol = income["symbol"].as_str().unwrap_or_default().to_string();
+            let pnl = decimal_field(income, "income").unwrap_or_default();
+            self.leg(&symbol, "BOTH").realized_pnl += pnl;
+        }
+
+        pub fn get(&self, symbol: &str) -> Vec<SymbolPnl> {
+            self.legs
+                .values()
+                .filter(|a| a.symbol == symbol)
+                .cloned()
+                .collect()
+        }
+
+        pub fn symbols(&self) -> Vec<SymbolPnl> {
+            self.legs.values().cloned().collect()
+        }
+
+        pub fn total_realized_pnl(&self) -> Decimal {
+            self.legs.values().map(|a| a.realized_pnl).sum()
+        }
+    }
+
+    // One day of one symbol; amounts are in the symbol's quote asset and funding is positive
+    // when received.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct PerformanceRow {
+        pub date: String,
+        pub symbol: String,
+        pub trades: u64,
+        pub volume: Decimal,
+        pub gross_pnl: Decimal,
+        pub fees: Decimal,
+        pub funding: Decimal,
+        pub net_pnl: Decimal,
+    }
+
+    impl CsvRecord for PerformanceRow {
+        const HEADER: &'static str = "date,symbol,trades,volume,gross_pnl,fees,funding,net_pnl";
+
+        fn to_csv_row(&self) -> String {
+            format!(
+                "{},{},{},{},{},{},{},{}",
+                self.date,
+                self.symbol,
+                self.trades,
+                self.volume,
+                self.gross_pnl,
+                self.fees,
+                self.funding,
+                self.net_pnl
+            )
+        }
+
+        fn from_csv_row(row: &[&str]) -> Result<Self, Box<dyn Error>> {
+            if row.len() < 8 {
+                return Err(format!("Performance row `{}` is too short.", row.join(",")).into());
+            }
+            Ok(PerformanceRow {
+                date: row[0].to_string(),
+                symbol: row[1].to_string(),
+                trades: row[2].parse()?,
+                volume: row[3].parse()?,
+                gross_pnl: row[4].parse()?,
+                fees: row[5].parse()?,
+                funding: row[6].parse()?,
+                net_pnl: row[7].parse()?,
+            })
+        }
+    }
+
+    fn utc_date(time: i64) -> String {
+        Utc.timestamp_millis_opt(time)
+            .unwrap()
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    fn report_row<'r>(
+        rows: &'r mut BTreeMap<(String, String), PerformanceRow>,
+        time: i64,
+        symbol: &str,
+    ) -> &'r mut PerformanceRow {
+        let date = utc_date(time);
+        rows.entry((date.clone(), symbol.to_string()))
+            .or_insert_with(|| PerformanceRow {
+                date: date,
+                symbol: symbol.to_string(),
+                ..Default::default()
+            })
+    }
+
+    // The commission of a fill in the quote asset. Commissions in the base asset are valued at the
+    // fill price, any other asset (e.g. BNB) by `fee_prices`, which maps it to its quote price.
+    fn quote_commission(
+        trade: &AccountTrade,
+        fee_prices: &HashMap<String, Decimal>,
+    ) -> Result<Decimal, Box<dyn Error>> {
+        if trade.commission.is_zero() || trade.symbol.ends_with(&trade.commission_asset) {
+            Ok(trade.commission)
+        } else if trade.symbol.starts_with(&trade.commission_asset) {
+            Ok(trade.commission * trade.price)
+        } else {
+            match fee_prices.get(&trade.commission_asset) {
+                Some(price) => Ok(trade.commission * price),
+                None => Err(format!(
+                    "No price to value the `{}` commission of {} trade {}.",
+                    trade.commission_asset, trade.symbol, trade.id
+                )
+                .into()),
+            }
+        }
+    }
+
+    // Per day and symbol: realized PnL from `trades` (see `PnlCalculator`), their commissions and
+    // the `FUNDING_FEE` records among `incomes`. Rows are sorted by date, then symbol.
+    pub fn performance_report(
+        trades: &[AccountTrade],
+        incomes: &[Value],
+        fee_prices: &HashMap<String, Decimal>,
+    ) -> Result<Vec<PerformanceRow>, Box<dyn Error>> {
+        let mut rows = BTreeMap::new();
+        let mut calculator = PnlCalculator::new();
+        let mut trades = trades.iter().collect::<Vec<_>>();
+        trades.sort_by_key(|a| (a.time, a.id));
+        for trade in trades {
+            let gross_pnl = calculator.add_trade(trade);
+            let fees = quote_commission(trade, fee_prices)?;
+            let row = report_row(&mut rows, trade.time, &trade.symbol);
+            row.trades += 1;
+            row.volume += trade.qty * trade.price;
+            row.gross_pnl += gross_pnl;
+            row.fees += fees;
+        }
+        for income in incomes {
+            if income["incomeType"].as_str() != Some("FUNDING_FEE") {
+                continue;
+            }
+            let time = income["time"].as_i64().unwrap_or_default();
+            let symbol = income["symbol"].as_str().unwrap_or_default();
+            let row = report_row(&mut rows, time, symbol);
+            row.funding += decimal_field(income, "income").unwrap_or_default();
+        }
+        Ok(rows
+            .into_values()
+            .map(|a| PerformanceRow {
+                net_pnl: a.gross_pnl - a.fees + a.funding,
+                ..a
+            })
+            .collect())
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        pub async fn get_account_trades(
+            &self,
+            symbol: &str,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<AccountTrade>, Box<dyn Error>> {
+            let trades = self
+                .get_my_trades(symbol, start_time_utc, end_time_utc)
+                .await?;
+            Ok(serde_json::from_value(Value::Array(trades))?)
+        }
+
+        // `performance_report` over the range, fetching the trades, (futures) funding fees and the
+        // prices of commission assets other than the base and quote.
+        pub async fn get_performance_report(
+            &self,
+            symbols: &Vec<&str>,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<Vec<PerformanceRow>, Box<dyn Error>> {
+            let start_time_utc: &TimeInput = &start_time_utc.into();
+            let end_time_utc: &TimeInput = &end_time_utc.into();
+            let mut trades = Vec::new();
+            #[cfg_attr(not(feature = "usdm-futures"), allow(unused_mut))]
+            let mut incomes = Vec::new();
+            let mut fee_prices = HashMap::new();
+            for symbol in symbols {
+                let symbol_trades = self
+                    .get_account_trades(symbol, start_time_utc, end_time_utc)
+                    .await?;
+                let info = self.get_symbol_info(symbol).await?;
+                for trade in &symbol_trades {
+                    let asset = &trade.commission_asset;
+                    if trade.commission.is_zero()
+                        || *asset == info.quote_asset
+                        || *asset == info.base_asset
+                        || fee_prices.contains_key(asset)
+                    {
+                        continue;
+                    }
+                    let price = self
+                        .get_price(&format!("{}{}", asset, info.quote_asset))
+                        .await?;
+                    let price = decimal_field(&price, "price")
+                        .ok_or_else(|| format!("Can't value commission asset `{asset}`."))?;
+                    fee_prices.insert(asset.clone(), price);
+                }
+                trades.extend(symbol_trades);
+                #[cfg(feature = "usdm-futures")]
+                if self.account_type == "swap" {
+                    incomes.extend(
+                        self.get_income_history(
+                            symbol,
+                            "FUNDING_FEE",
+                            start_time_utc,
+                            end_time_utc,
+                        )
+                        .await?,
+                    );
+                }
+            }
+            performance_report(&trades, &incomes, &fee_prices)
+        }
+
+        // Realized PnL per symbol from the account's fills in the range.
+        pub async fn realized_pnl(
+            &self,
+            symbols: &Vec<&str>,
+            start_time_utc: impl Into<TimeInput>,
+            end_time_utc: impl Into<TimeInput>,
+        ) -> Result<PnlCalculator, Box<dyn Error>> {
+            let start_time_utc: &TimeInput = &start_time_utc.into();
+            let end_time_utc: &TimeInput = &end_time_utc.into();
+            let mut calculator = PnlCalculator::new();
+            for symbol in symbols {
+                let trades = self
+                    .get_account_trades(symbol, start_time_utc, end_time_utc)
+                    .await?;
+                calculator.add_trades(&trades);
+            }
+            Ok(calculator)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn trade(id: i64, is_buyer: bool, qty: i64, price: i64) -> AccountTrade {
+            AccountTrade {
+                symbol: "BTCUSDT".to_string(),
+                id: id,
+                order_id: id,
+                price: Decimal::from(price),
+                qty: Decimal::from(qty),
+                quote_qty: Decimal::from(qty * price),
+                commission: Decimal::ZERO,
+                commission_asset: "USDT".to_string(),
+                time: 1_704_067_200_000 + id,
+                is_buyer: is_buyer,
+                is_maker: false,
+                realized_pnl: None,
+                position_side: None,
+            }
+        }
+
+        fn hedged(trade: AccountTrade, position_side: &str) -> AccountTrade {
+            AccountTrade {
+                position_side: Some(position_side.to_string()),
+                ..trade
+            }
+        }
+
+        #[test]
+        fn partial_close_realizes_at_the_average_cost() {
+            let mut calculator = PnlCalculator::new();
+            calculator.add_trade(&trade(1, true, 1, 100));
+            calculator.add_trade(&trade(2, true, 1, 110));
+            assert_eq!(
+                calculator.add_trade(&trade(3, false, 1, 125)),
+                Decimal::from(20)
+            );
+            let leg = &calculator.get("BTCUSDT")[0];
+            assert_eq!(leg.position_qty, Decimal::ONE);
+            assert_eq!(leg.avg_entry, Decimal::from(105));
+            assert_eq!(leg.round_trips, 0);
+            assert_eq!(
+                calculator.add_trade(&trade(4, false, 1, 95)),
+                Decimal::from(-10)
+            );
+            let leg = &calculator.get("BTCUSDT")[0];
+            assert!(leg.position_qty.is_zero());
+            assert!(leg.avg_entry.is_zero());
+            assert_eq!(leg.realized_pnl, Decimal::from(10));
+            assert_eq!((leg.round_trips, leg.winning_trips), (1, 1));
+            assert_eq!(leg.gross_profit, Decimal::from(10));
+        }
+
+        #[test]
+        fn flip_through_zero_ends_the_trip_and_reenters_at_the_fill() {
+            let mut calculator = PnlCalculator::new();
+            calculator.add_trade(&trade(1, true, 1, 100));
+            assert_eq!(
+                calculator.add_trade(&trade(2, false, 3, 90)),
+                Decimal::from(-10)
+            );
+            let leg = &calculator.get("BTCUSDT")[0];
+            assert_eq!(leg.position_qty, Decimal::from(-2));
+            assert_eq!(leg.avg_entry, Decimal::from(90));
+            assert_eq!((leg.round_trips, leg.losing_trips), (1, 1));
+            assert_eq!(
+                calculator.add_trade(&trade(3, true, 2, 80)),
+                Decimal::from(20)
+            );
+            let leg = &calculator.get("BTCUSDT")[0];
+            assert_eq!(leg.round_trips, 2);
+            assert_eq!(leg.win_rate(), Decimal::new(5, 1));
+            assert_eq!(leg.gross_profit, Decimal::from(20));
+            assert_eq!(leg.gross_loss, Decimal::from(10));
+            assert_eq!(leg.volume, Decimal::from(530));
+        }
+
+        #[test]
+        fn hedge_mode_legs_are_kept_apart() {
+            let mut calculator = PnlCalculator::new();
+            calculator.add_trades(&[
+                hedged(trade(1, true, 1, 100), "LONG"),
+                hedged(trade(2, false, 1, 100), "SHORT"),
+                hedged(trade(3, false, 1, 110), "LONG"),
+                hedged(trade(4, true, 1, 110), "SHORT"),
+            ]);
+            let legs = calculator.get("BTCUSDT");
+            assert_eq!(legs.len(), 2);
+            assert_eq!(legs[0].position_side, "LONG");
+            assert_eq!(legs[0].realized_pnl, Decimal::from(10));
+            assert_eq!(legs[1].position_side, "SHORT");
+            assert_eq!(legs[1].realized_pnl, Decimal::from(-10));
+            assert!(calculator.total_realized_pnl().is_zero());
+        }
+
+        fn paying(trade: AccountTrade, commission: Decimal, asset: &str) -> AccountTrade {
+            AccountTrade {
+                commission: commission,
+                commission_asset: asset.to_string(),
+                ..trade
+            }
+        }
+
+        #[test]
+        fn report_values_commissions_in_the_quote_asset() {
+            let trades = [
+                paying(trade(1, true, 2, 100), Decimal::new(2, 1), "USDT"),
+                paying(trade(2, false, 1, 110), Decimal::new(1, 2), "BTC"),
+                paying(trade(3, false, 1, 120), Decimal::new(5, 2), "BNB"),
+            ];
+            let incomes = [
+                serde_json::json!({"incomeType": "FUNDING_FEE", "symbol": "BTCUSDT", "income": "-0.5", "time": 1_704_067_200_000i64}),
+                serde_json::json!({"incomeType": "COMMISSION", "symbol": "BTCUSDT", "income": "-9", "time": 1_704_067_200_000i64}),
+            ];
+            let fee_prices = HashMap::from([("BNB".to_string(), Decimal::from(4))]);
+            let rows = performance_report(&trades, &incomes, &fee_prices).unwrap();
+            assert_eq!(
+                rows,
+                vec![PerformanceRow {
+                    date: "2024-01-01".to_string(),
+                    symbol: "BTCUSDT".to_string(),
+                    trades: 3,
+                    volume: Decimal::from(430),
+                    gross_pnl: Decimal::from(30),
+                    // 0.2 USDT, 0.01 BTC at 110 and 0.05 BNB at 4.
+                    fees: Decimal::new(15, 1),
+                    funding: Decimal::new(-5, 1),
+                    net_pnl: Decimal::from(28),
+                }]
+            );
+        }
+
+        #[test]
+        fn report_without_the_price_of_a_commission_asset_is_an_error() {
+            let trades = [paying(trade(1, true, 1, 100), Decimal::ONE, "BNB")];
+            assert!(performance_report(&trades, &[], &HashMap::new()).is_err());
+        }
+
+        #[test]
+        fn report_rows_survive_a_csv_round_trip() {
+            let row = PerformanceRow {
+                date: "2024-01-01".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                trades: 3,
+                volume: Decimal::from(430),
+                gross_pnl: Decimal::from(30),
+                fees: Decimal::new(15, 1),
+                funding: Decimal::new(-5, 1),
+                net_pnl: Decimal::from(28),
+            };
+            let csv = row.to_csv_row();
+            let fields = csv.split(',').collect::<Vec<_>>();
+            assert_eq!(PerformanceRow::from_csv_row(&fields).unwrap(), row);
+        }
+    }
+}
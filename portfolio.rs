@@ -0,0 +1,139 @@
+pub mod portfolio {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::execution::execution::decimal_field;
+    use crate::models::models::AssetBalance;
+    use rust_decimal::Decimal;
+    use std::{collections::HashMap, error::Error};
+
+    // Assets tried, in order, to price an asset that has no pair with the quote asset.
+    const BRIDGE_ASSETS: [&str; 6] = ["USDT", "BTC", "BNB", "ETH", "FDUSD", "USDC"];
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct AssetValue {
+        pub asset: String,
+        // Free plus locked.
+        pub amount: Decimal,
+        pub price: Decimal,
+        pub value: Decimal,
+        // Percent of the total value.
+        pub weight: Decimal,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PortfolioValue {
+        pub quote_asset: String,
+        pub total: Decimal,
+        // Largest value first.
+        pub assets: Vec<AssetValue>,
+        // Held assets no pair could price; they are left out of `total`.
+        pub unpriced: Vec<String>,
+    }
+
+    // The price of `asset` in `quote` from a symbol -> price map: the direct pair, the inverse
+    // pair, or through one of the `BRIDGE_ASSETS`.
+    pub fn convert_price(
+        prices: &HashMap<String, Decimal>,
+        asset: &str,
+        quote: &str,
+    ) -> Option<Decimal> {
+        direct_price(prices, asset, quote).or_else(|| {
+            BRIDGE_ASSETS
+                .iter()
+                .filter(|a| **a != asset && **a != quote)
+                .find_map(|bridge| {
+                    Some(
+                        direct_price(prices, asset, bridge)? * direct_price(prices, bridge, quote)?,
+                    )
+                })
+        })
+    }
+
+    fn direct_price(
+        prices: &HashMap<String, Decimal>,
+        asset: &str,
+        quote: &str,
+    ) -> Option<Decimal> {
+        if asset == quote {
+            return Some(Decimal::ONE);
+        }
+        if let Some(price) = prices.get(&format!("{asset}{quote}")) {
+            return Some(*price);
+        }
+        prices
+            .get(&format!("{quote}{asset}"))
+            .filter(|a| !a.is_zero())
+            .map(|a| Decimal::ONE / a)
+    }
+
+    // Values `balances` in `quote_asset`; zero balances are skipped.
+    pub fn value_balances(
+        balances: &Vec<AssetBalance>,
+        prices: &HashMap<String, Decimal>,
+        quote_asset: &str,
+    ) -> PortfolioValue {
+        let mut assets = vec![];
+        let mut unpriced = vec![];
+        for balance in balances {
+            let amount = balance.free + balance.locked;
+            if amount.is_zero() {
+                continue;
+            }
+            match convert_price(prices, &balance.asset, quote_asset) {
+                Some(price) => assets.push(AssetValue {
+                    asset: balance.asset.clone(),
+                    amount: amount,
+                    price: price,
+                    value: amount * price,
+                    weight: Decimal::ZERO,
+                }),
+                None => unpriced.push(balance.asset.clone()),
+            }
+        }
+        let total: Decimal = assets.iter().map(|a| a.value).sum();
+        if !total.is_zero() {
+            for asset in assets.iter_mut() {
+                asset.weight = asset.value / total * Decimal::ONE_HUNDRED;
+            }
+        }
+        assets.sort_by(|a, b| b.value.cmp(&a.value));
+        PortfolioValue {
+            quote_asset: quote_asset.to_string(),
+            total: total,
+            assets: assets,
+            unpriced: unpriced,
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // The spot account valued in `quote_asset` (e.g. USDT or BTC) at the last prices.
+        pub async fn portfolio_value(
+            &self,
+            quote_asset: &str,
+        ) -> Result<PortfolioValue, Box<dyn Error>> {
+            assert!(
+                self.account_type == "spot",
+                "only `spot` can get portfolio value."
+            );
+            let balances = self.get_balance().await?;
+            let prices = self
+                .get_price("")
+                .await?
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|a| {
+                    Some((
+                        a["symbol"].as_str()?.to_string(),
+                        decimal_field(a, "price")?,
+                    ))
+                })
+                .collect();
+            Ok(value_balances(
+                &balances,
+                &prices,
+                &quote_asset.to_uppercase(),
+            ))
+        }
+    }
+}
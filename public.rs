@@ -0,0 +1,193 @@
+pub mod public {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::builder::builder::BinanceAPIBuilder;
+    #[cfg(feature = "spot")]
+    use crate::models::models::AvgPrice;
+    #[cfg(feature = "websocket")]
+    use crate::models::models::PartialDepth;
+    #[cfg(feature = "wallet")]
+    use crate::models::models::SystemStatus;
+    use crate::models::models::{
+        AggTrade, BookTicker, DepthSnapshot, Kline, KlineQuery, SymbolFilters, TimeInput, Trade,
+    };
+    #[cfg(feature = "usdm-futures")]
+    use crate::models::models::{AssetIndex, ContractType, IndexInfo};
+    #[cfg(all(feature = "websocket", feature = "usdm-futures"))]
+    use crate::models::models::{LiquidationEvent, MarkPriceEvent};
+    #[cfg(feature = "websocket")]
+    use crate::partial_book::partial_book::PartialBook;
+    #[cfg(feature = "websocket")]
+    use crate::stream::stream::EventHandler;
+    use crate::symbol_registry::symbol_registry::SymbolInfo;
+    use rust_decimal::Decimal;
+    use std::{collections::HashMap, error::Error};
+    #[cfg(feature = "websocket")]
+    use std::{net::TcpStream, sync::Arc};
+    #[cfg(feature = "websocket")]
+    use tokio::sync::mpsc;
+    #[cfg(feature = "websocket")]
+    use tungstenite::{protocol::WebSocket, stream::MaybeTlsStream};
+
+    type SendRequestRe = serde_json::Value;
+
+    macro_rules! public_methods {
+        ($($(#[$meta:meta])* $name:ident($($arg:ident: $type:ty),*) -> $re:ty;)*) => {
+            $(
+                $(#[$meta])*
+                pub async fn $name(&self, $($arg: $type),*) -> Result<$re, Box<dyn Error>> {
+                    self.inner.$name($($arg),*).await
+                }
+            )*
+        };
+    }
+
+    // A client without keys: only unauthenticated endpoints and market streams are reachable,
+    // so a signed call is a compile error instead of a rejected request.
+    #[derive(Debug)]
+    pub struct PublicBinanceAPI<'a> {
+        inner: BinanceAPI<'a>,
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        pub async fn public(account_type: &'a str) -> Result<PublicBinanceAPI<'a>, Box<dyn Error>> {
+            BinanceAPI::builder()
+                .account_type(account_type)
+                .build_public()
+                .await
+        }
+    }
+
+    impl<'a> BinanceAPIBuilder<'a> {
+        // Keys, signer and listen key set on the builder are dropped.
+        pub async fn build_public(mut self) -> Result<PublicBinanceAPI<'a>, Box<dyn Error>> {
+            self.api_key = "".into();
+            self.secret_key = "".into();
+            self.signer = None;
+            self.auto_listen_key = false;
+            Ok(PublicBinanceAPI {
+                inner: self.build().await?,
+            })
+        }
+    }
+
+    impl<'a> PublicBinanceAPI<'a> {
+        #[cfg(feature = "websocket")]
+        pub(crate) fn inner(&self) -> &BinanceAPI<'a> {
+            &self.inner
+        }
+
+        pub fn account_type(&self) -> &'a str {
+            self.inner.account_type
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn generate_websocket(&self) -> WebSocket<MaybeTlsStream<TcpStream>> {
+            self.inner.generate_websocket("market")
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn websocket_read_once(&self, ws: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> String {
+            self.inner.websocket_read_once(ws)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn close_websocket(&self, ws: &mut WebSocket<MaybeTlsStream<TcpStream>>) {
+            self.inner.close_websocket(ws)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn partial_depth_type(&self, levels: u32, update_ms: u32) -> String {
+            self.inner.partial_depth_type(levels, update_ms)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn stream_klines(&self, symbol: &str, interval: &str) -> mpsc::Receiver<Kline> {
+            self.inner.stream_klines(symbol, interval)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn stream_partial_depth(
+            &self,
+            symbol: &str,
+            levels: u32,
+            update_ms: u32,
+        ) -> mpsc::Receiver<PartialDepth> {
+            self.inner.stream_partial_depth(symbol, levels, update_ms)
+        }
+
+        #[cfg(all(feature = "websocket", feature = "usdm-futures"))]
+        pub fn stream_liquidations(&self, symbol: &str) -> mpsc::Receiver<LiquidationEvent> {
+            self.inner.stream_liquidations(symbol)
+        }
+
+        #[cfg(all(feature = "websocket", feature = "usdm-futures"))]
+        pub fn stream_mark_price(&self, symbol: &str) -> mpsc::Receiver<MarkPriceEvent> {
+            self.inner.stream_mark_price(symbol)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn maintain_partial_book(
+            &self,
+            symbol: &str,
+            levels: u32,
+            update_ms: u32,
+        ) -> Arc<PartialBook> {
+            self.inner.maintain_partial_book(symbol, levels, update_ms)
+        }
+
+        #[cfg(feature = "websocket")]
+        pub fn run_handler(&self, streams: &Vec<&str>, handler: impl EventHandler + 'static) {
+            self.inner.run_handler(streams, handler)
+        }
+
+        public_methods! {
+            ping() -> ();
+            server_time() -> i64;
+            #[cfg(feature = "wallet")]
+            system_status() -> SystemStatus;
+            #[cfg(feature = "websocket")]
+            subscribe_websocket(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, symbols: &Vec<&str>, sub_type: &str) -> ();
+            #[cfg(feature = "websocket")]
+            subscribe_partial_depth(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, symbols: &Vec<&str>, levels: u32, update_ms: u32) -> ();
+            #[cfg(feature = "websocket")]
+            unsubscribe_websocket(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, symbols: &Vec<&str>, sub_type: &str) -> ();
+            #[cfg(feature = "websocket")]
+            set_websocket_property(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, property: &str, value: SendRequestRe) -> ();
+            #[cfg(feature = "websocket")]
+            get_websocket_property(ws: &mut WebSocket<MaybeTlsStream<TcpStream>>, property: &str) -> SendRequestRe;
+            #[cfg(feature = "websocket")]
+            shutdown() -> ();
+            history_klines(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            history_klines_with(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>, query: &KlineQuery) -> Vec<Kline>;
+            history_klines_many(symbols: &Vec<&str>, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>, concurrency: usize) -> HashMap<String, Vec<Kline>>;
+            #[cfg(feature = "spot")]
+            get_ui_klines(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>, time_zone: &str) -> Vec<Kline>;
+            #[cfg(feature = "usdm-futures")]
+            get_mark_price_klines(symbol: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            #[cfg(feature = "usdm-futures")]
+            get_index_price_klines(pair: &str, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            #[cfg(feature = "usdm-futures")]
+            get_continuous_klines(pair: &str, contract_type: ContractType, interval: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<Kline>;
+            get_exchange_info() -> SendRequestRe;
+            refresh_symbols() -> ();
+            get_symbol_info(symbol: &str) -> SymbolInfo;
+            get_symbol_filters(symbol: &str) -> SymbolFilters;
+            round_price(symbol: &str, price: Decimal) -> Decimal;
+            round_qty(symbol: &str, qty: Decimal) -> Decimal;
+            get_price(symbol: &str) -> SendRequestRe;
+            #[cfg(feature = "spot")]
+            get_avg_price(symbol: &str) -> AvgPrice;
+            #[cfg(feature = "usdm-futures")]
+            get_mark_price(symbol: &str) -> SendRequestRe;
+            get_ticker(symbol: &str) -> SendRequestRe;
+            get_depth(symbol: &str, limit: u32) -> DepthSnapshot;
+            get_recent_trades(symbol: &str, limit: usize) -> Vec<Trade>;
+            get_agg_trades(symbol: &str, start_time_utc: impl Into<TimeInput>, end_time_utc: impl Into<TimeInput>) -> Vec<AggTrade>;
+            get_book_ticker(symbol: &str) -> Vec<BookTicker>;
+            #[cfg(feature = "usdm-futures")]
+            get_index_info(symbol: &str) -> Vec<IndexInfo>;
+            #[cfg(feature = "usdm-futures")]
+            get_asset_index() -> Vec<AssetIndex>;
+        }
+    }
+}
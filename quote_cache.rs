@@ -0,0 +1,163 @@
+pub mod quote_cache {
+    #[cfg(feature = "websocket")]
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::BookTickerEvent;
+    #[cfg(feature = "websocket")]
+    use crate::stream::stream::StreamMessage;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    #[cfg(feature = "websocket")]
+    use std::sync::Arc;
+    use std::{collections::HashMap, sync::RwLock, time::Duration};
+
+    // One side of a quote and when it was last updated.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct QuoteLevel {
+        pub price: Decimal,
+        pub qty: Decimal,
+        // In ms: the event time on futures, when it was received on spot.
+        pub time: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Quote {
+        pub symbol: String,
+        pub bid: QuoteLevel,
+        pub ask: QuoteLevel,
+        pub update_id: i64,
+        // In ms, local time.
+        pub received: i64,
+    }
+
+    impl Quote {
+        pub fn mid_price(&self) -> Decimal {
+            (self.bid.price + self.ask.price) / Decimal::TWO
+        }
+
+        pub fn spread(&self) -> Decimal {
+            self.ask.price - self.bid.price
+        }
+
+        // Older than `max_age` at `now` (ms).
+        pub fn is_stale(&self, max_age: Duration, now: i64) -> bool {
+            now - self.received > max_age.as_millis() as i64
+        }
+    }
+
+    // The best bid and ask per symbol from bookTicker events; `maintain_quote_cache` feeds it.
+    // Events older than the quote held are dropped.
+    #[derive(Debug, Default)]
+    pub struct QuoteCache {
+        quotes: RwLock<HashMap<String, Quote>>,
+    }
+
+    impl QuoteCache {
+        pub fn new() -> Self {
+            QuoteCache::default()
+        }
+
+        // Returns whether the event replaced the quote held.
+        pub fn apply(&self, event: &BookTickerEvent) -> bool {
+            let received = Utc::now().timestamp_millis();
+            let time = event.event_time.unwrap_or(received);
+            let mut quotes = self.quotes.write().unwrap();
+            if let Some(held) = quotes.get(&event.symbol) {
+                if event.update_id < held.update_id {
+                    return false;
+                }
+            }
+            quotes.insert(
+                event.symbol.clone(),
+                Quote {
+                    symbol: event.symbol.clone(),
+                    bid: QuoteLevel {
+                        price: event.bid_price,
+                        qty: event.bid_qty,
+                        time: time,
+                    },
+                    ask: QuoteLevel {
+                        price: event.ask_price,
+                        qty: event.ask_qty,
+                        time: time,
+                    },
+                    update_id: event.update_id,
+                    received: received,
+                },
+            );
+            true
+        }
+
+        pub fn quote(&self, symbol: &str) -> Option<Quote> {
+            self.quotes
+                .read()
+                .unwrap()
+                .get(&symbol.to_uppercase())
+                .cloned()
+        }
+
+        pub fn best_bid(&self, symbol: &str) -> Option<QuoteLevel> {
+            self.quote(symbol).map(|a| a.bid)
+        }
+
+        pub fn best_ask(&self, symbol: &str) -> Option<QuoteLevel> {
+            self.quote(symbol).map(|a| a.ask)
+        }
+
+        // None as well when the quote is older than `max_age`, e.g. while the stream reconnects.
+        pub fn fresh_quote(&self, symbol: &str, max_age: Duration) -> Option<Quote> {
+            self.quote(symbol)
+                .filter(|a| !a.is_stale(max_age, Utc::now().timestamp_millis()))
+        }
+
+        pub fn symbols(&self) -> Vec<String> {
+            let mut symbols: Vec<_> = self.quotes.read().unwrap().keys().cloned().collect();
+            symbols.sort();
+            symbols
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    impl<'a> BinanceAPI<'a> {
+        // A `QuoteCache` kept up to date from the bookTicker streams of `symbols`, or of every
+        // symbol (`!bookTicker`) if it is empty, in the background until every clone of the
+        // returned `Arc` is dropped.
+        pub fn maintain_quote_cache(&self, symbols: &Vec<&str>) -> Arc<QuoteCache> {
+            let cache = Arc::new(QuoteCache::new());
+            let url = if symbols.is_empty() {
+                self.stream_url("!bookTicker")
+            } else {
+                let streams: Vec<String> = symbols
+                    .iter()
+                    .map(|a| format!("{}@bookTicker", a.to_lowercase()))
+                    .collect();
+                self.with_time_unit(format!(
+                    "{}/stream?streams={}",
+                    self.wss_url.trim_end_matches("/ws"),
+                    streams.join("/")
+                ))
+            };
+            let shared = cache.clone();
+            self.streams.spawn(url, move |message| {
+                if let StreamMessage::Text(text) = message {
+                    let parsed = serde_json::from_str::<serde_json::Value>(&text).and_then(|a| {
+                        // Combined streams wrap the event in `data`.
+                        let data = if a.get("data").is_some() {
+                            a["data"].clone()
+                        } else {
+                            a
+                        };
+                        serde_json::from_value::<BookTickerEvent>(data)
+                    });
+                    match parsed {
+                        Ok(event) => {
+                            shared.apply(&event);
+                        }
+                        Err(e) => tracing::warn!(error = %e, "can't parse bookTicker event"),
+                    }
+                }
+                Arc::strong_count(&shared) > 1
+            });
+            cache
+        }
+    }
+}
@@ -0,0 +1,377 @@
+pub mod rate_limiter {
+    use chrono::Utc;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum RequestPriority {
+        // Backfills and paged history; kept out of the reserved headroom.
+        Low,
+        Normal,
+        // Order placement and cancellation; never waits behind the others.
+        High,
+    }
+
+    impl RequestPriority {
+        pub fn for_request(method: &str, path: &str) -> Self {
+            let endpoint = path.rsplit('/').next().unwrap_or_default();
+            let lower = path.to_lowercase();
+            if method != "GET"
+                && (lower.contains("order")
+                    || lower.contains("cancel")
+                    || lower.contains("listenkey"))
+            {
+                RequestPriority::High
+            } else if [
+                "klines",
+                "uiKlines",
+                "markPriceKlines",
+                "indexPriceKlines",
+                "continuousKlines",
+                "aggTrades",
+                "historicalTrades",
+                "allOrders",
+                "myTrades",
+                "income",
+            ]
+            .contains(&endpoint)
+            {
+                RequestPriority::Low
+            } else {
+                RequestPriority::Normal
+            }
+        }
+    }
+
+    // The class a budget is set on: the endpoint name, with every kline flavour as `klines`.
+    pub fn endpoint_class(path: &str) -> &str {
+        match path.rsplit('/').next().unwrap_or_default() {
+            "uiKlines" | "markPriceKlines" | "indexPriceKlines" | "continuousKlines"
+            | "premiumIndexKlines" => "klines",
+            endpoint => endpoint,
+        }
+    }
+
+    #[derive(Debug)]
+    struct WeightState {
+        minute: i64,
+        used: u64,
+    }
+
+    #[derive(Debug, Default)]
+    struct ClassState {
+        minute: i64,
+        used: HashMap<String, u64>,
+        // The last X-MBX-USED-WEIGHT-1M seen, to charge a class what its request really cost.
+        reported: u64,
+    }
+
+    // Tracks request weight per calendar minute, the window Binance's X-MBX-USED-WEIGHT-1M counts in.
+    #[derive(Debug)]
+    pub struct RateLimiter {
+        limit: Mutex<u64>,
+        reserved: Mutex<Option<u64>>,
+        state: Mutex<WeightState>,
+        high_waiting: AtomicUsize,
+        // Percent of the limit each endpoint class may use per minute.
+        budgets: Mutex<HashMap<String, u64>>,
+        classes: Mutex<ClassState>,
+    }
+
+    // Marks a high priority request as waiting for weight while it is alive.
+    pub(crate) struct QueueGuard<'b>(Option<&'b AtomicUsize>);
+
+    impl Drop for QueueGuard<'_> {
+        fn drop(&mut self) {
+            if let Some(waiting) = self.0 {
+                waiting.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    impl Default for RateLimiter {
+        fn default() -> Self {
+            RateLimiter::new(6000)
+        }
+    }
+
+    impl RateLimiter {
+        pub fn new(limit: u64) -> Self {
+            RateLimiter {
+                limit: Mutex::new(limit),
+                reserved: Mutex::new(None),
+                state: Mutex::new(WeightState { minute: 0, used: 0 }),
+                high_waiting: AtomicUsize::new(0),
+                budgets: Default::default(),
+                classes: Default::default(),
+            }
+        }
+
+        pub fn set_limit(&self, limit: u64) {
+            *self.limit.lock().unwrap() = limit;
+        }
+
+        // Weight per minute that low priority requests leave for the rest; a fifth of the limit by default.
+        pub fn set_reserved(&self, reserved: u64) {
+            *self.reserved.lock().unwrap() = Some(reserved);
+        }
+
+        pub fn reserved(&self) -> u64 {
+            let limit = *self.limit.lock().unwrap();
+            self.reserved
+                .lock()
+                .unwrap()
+                .unwrap_or(limit / 5)
+                .min(limit)
+        }
+
+        // Caps the weight `class` (see `endpoint_class`) may use per minute at `percent` of the
+        // limit, e.g. `set_budget("klines", 30)` so a backfill sharing the key with a live bot
+        // can't starve it. The budget is soft: a request's real cost is only known from the
+        // used weight header after it returns, and concurrent requests share that count.
+        pub fn set_budget(&self, class: &str, percent: u64) {
+            assert!(
+                percent <= 100,
+                "weight budget must be a percent of the limit."
+            );
+            self.budgets
+                .lock()
+                .unwrap()
+                .insert(class.to_string(), percent);
+        }
+
+        pub fn remove_budget(&self, class: &str) {
+            self.budgets.lock().unwrap().remove(class);
+        }
+
+        // Weight charged to `class` this minute.
+        pub fn class_used_weight(&self, class: &str) -> u64 {
+            let classes = self.classes.lock().unwrap();
+            if classes.minute == Self::minute() {
+                classes.used.get(class).copied().unwrap_or_default()
+            } else {
+                0
+            }
+        }
+
+        pub fn used_weight(&self) -> u64 {
+            let state = self.state.lock().unwrap();
+            if state.minute == Self::minute() {
+                state.used
+            } else {
+                0
+            }
+        }
+
+        fn minute() -> i64 {
+            Utc::now().timestamp_millis() / 60000
+        }
+
+        // Books `weight` in the current minute, or says how long to wait for the next one.
+        pub fn reserve(&self, weight: u64) -> Option<Duration> {
+            self.reserve_with(weight, RequestPriority::Normal)
+        }
+
+        // Lower priorities give way while a high priority request waits, and low ones stop short of the reserve.
+        pub fn reserve_with(&self, weight: u64, priority: RequestPriority) -> Option<Duration> {
+            if priority < RequestPriority::High && self.high_waiting.load(Ordering::SeqCst) > 0 {
+                return Some(Duration::from_millis(50));
+            }
+            let limit = *self.limit.lock().unwrap();
+            let limit = match priority {
+                RequestPriority::Low => limit - self.reserved(),
+                _ => limit,
+            };
+            let mut state = self.state.lock().unwrap();
+            let now = Utc::now().timestamp_millis();
+            if state.minute != now / 60000 {
+                state.minute = now / 60000;
+                state.used = 0;
+            }
+            if state.used + weight > limit && state.used > 0 {
+                return Some(Duration::from_millis((60000 - now % 60000) as u64 + 10));
+            }
+            state.used += weight;
+            None
+        }
+
+        // Like `reserve_with`, also keeping the endpoint class of `path` within its budget.
+        pub fn reserve_for(
+            &self,
+            weight: u64,
+            priority: RequestPriority,
+            path: &str,
+        ) -> Option<Duration> {
+            let class = endpoint_class(path);
+            let budget = self.budgets.lock().unwrap().get(class).copied();
+            if let Some(percent) = budget {
+                let budget = *self.limit.lock().unwrap() * percent / 100;
+                let used = self.class_used_weight(class);
+                if used + weight > budget && used > 0 {
+                    let now = Utc::now().timestamp_millis();
+                    return Some(Duration::from_millis((60000 - now % 60000) as u64 + 10));
+                }
+            }
+            if let Some(wait) = self.reserve_with(weight, priority) {
+                return Some(wait);
+            }
+            Self::charge(&mut self.classes.lock().unwrap(), class, weight);
+            None
+        }
+
+        // Charges the class of `path` whatever its request cost beyond the `booked` weight, then
+        // records `used_weight` as `record` does.
+        pub fn record_for(&self, path: &str, booked: u64, used_weight: u64) {
+            let mut classes = self.classes.lock().unwrap();
+            Self::charge(&mut classes, endpoint_class(path), 0);
+            let extra = used_weight.saturating_sub(classes.reported + booked);
+            Self::charge(&mut classes, endpoint_class(path), extra);
+            classes.reported = classes.reported.max(used_weight);
+            drop(classes);
+            self.record(used_weight);
+        }
+
+        fn charge(classes: &mut ClassState, class: &str, weight: u64) {
+            let minute = Self::minute();
+            if classes.minute != minute {
+                classes.minute = minute;
+                classes.used.clear();
+                classes.reported = 0;
+            }
+            *classes.used.entry(class.to_string()).or_default() += weight;
+        }
+
+        // The exchange's own count wins over ours.
+        pub fn record(&self, used_weight: u64) {
+            let mut state = self.state.lock().unwrap();
+            let minute = Self::minute();
+            if state.minute != minute {
+                state.minute = minute;
+                state.used = used_weight;
+            } else {
+                state.used = state.used.max(used_weight);
+            }
+        }
+
+        pub(crate) fn queue(&self, priority: RequestPriority) -> QueueGuard<'_> {
+            if priority == RequestPriority::High {
+                self.high_waiting.fetch_add(1, Ordering::SeqCst);
+                QueueGuard(Some(&self.high_waiting))
+            } else {
+                QueueGuard(None)
+            }
+        }
+    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // Weight is counted per calendar minute; start far enough from its end that a test
+        // doesn't straddle two of them.
+        fn limiter(limit: u64) -> RateLimiter {
+            let left = 60000 - Utc::now().timestamp_millis() % 60000;
+            if left < 2000 {
+                std::thread::sleep(Duration::from_millis(left as u64 + 10));
+            }
+            RateLimiter::new(limit)
+        }
+
+        #[test]
+        fn low_priority_stops_short_of_the_reserve() {
+            let limiter = limiter(100);
+            assert_eq!(limiter.reserved(), 20);
+            assert_eq!(limiter.reserve_with(80, RequestPriority::Low), None);
+            assert!(limiter.reserve_with(1, RequestPriority::Low).is_some());
+            assert_eq!(limiter.reserve_with(20, RequestPriority::Normal), None);
+            assert!(limiter.reserve_with(1, RequestPriority::High).is_some());
+            assert_eq!(limiter.used_weight(), 100);
+        }
+
+        #[test]
+        fn reserve_can_be_set() {
+            let limiter = limiter(100);
+            limiter.set_reserved(50);
+            assert_eq!(limiter.reserve_with(40, RequestPriority::Low), None);
+            assert!(limiter.reserve_with(20, RequestPriority::Low).is_some());
+            assert_eq!(limiter.reserve_with(20, RequestPriority::Normal), None);
+        }
+
+        #[test]
+        fn first_request_of_a_minute_always_goes_through() {
+            let limiter = limiter(100);
+            assert_eq!(limiter.reserve(150), None);
+            let wait = limiter.reserve(1).unwrap();
+            assert!(wait <= Duration::from_millis(60010));
+        }
+
+        #[test]
+        fn lower_priorities_give_way_to_a_waiting_high_one() {
+            let limiter = limiter(100);
+            let guard = limiter.queue(RequestPriority::High);
+            assert_eq!(
+                limiter.reserve_with(1, RequestPriority::Normal),
+                Some(Duration::from_millis(50))
+            );
+            assert_eq!(limiter.reserve_with(1, RequestPriority::High), None);
+            drop(guard);
+            assert_eq!(limiter.reserve_with(1, RequestPriority::Normal), None);
+        }
+
+        #[test]
+        fn budget_caps_a_class_but_not_the_others() {
+            let limiter = limiter(100);
+            limiter.set_budget("klines", 30);
+            assert_eq!(
+                limiter.reserve_for(20, RequestPriority::Low, "/api/v3/klines"),
+                None
+            );
+            // Every kline flavour shares the budget.
+            assert!(limiter
+                .reserve_for(20, RequestPriority::Low, "/fapi/v1/continuousKlines")
+                .is_some());
+            assert_eq!(limiter.class_used_weight("klines"), 20);
+            assert_eq!(
+                limiter.reserve_for(20, RequestPriority::Normal, "/api/v3/depth"),
+                None
+            );
+            assert_eq!(limiter.used_weight(), 40);
+            limiter.remove_budget("klines");
+            assert_eq!(
+                limiter.reserve_for(20, RequestPriority::Low, "/api/v3/klines"),
+                None
+            );
+            assert_eq!(limiter.class_used_weight("klines"), 40);
+        }
+
+        #[test]
+        fn budget_is_not_charged_when_the_limit_refuses() {
+            let limiter = limiter(100);
+            limiter.set_budget("klines", 100);
+            assert_eq!(limiter.reserve_with(70, RequestPriority::Normal), None);
+            // Within the budget, but past the reserve low priority requests leave.
+            assert!(limiter
+                .reserve_for(20, RequestPriority::Low, "/api/v3/klines")
+                .is_some());
+            assert_eq!(limiter.class_used_weight("klines"), 0);
+            assert_eq!(limiter.used_weight(), 70);
+        }
+
+        #[test]
+        fn record_for_charges_what_a_request_really_cost() {
+            let limiter = limiter(100);
+            assert_eq!(
+                limiter.reserve_for(5, RequestPriority::Low, "/api/v3/klines"),
+                None
+            );
+            limiter.record_for("/api/v3/klines", 5, 12);
+            assert_eq!(limiter.class_used_weight("klines"), 12);
+            assert_eq!(limiter.used_weight(), 12);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+pub mod recorder {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::stream::stream::{dispatch, EventHandler};
+    use chrono::{TimeZone, Utc};
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use std::{
+        collections::HashMap,
+        error::Error,
+        fs::{self, File, OpenOptions},
+        io::{BufRead, BufReader, LineWriter, Write},
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+    };
+
+    // What a user data stream is recorded as, instead of its listen key.
+    const USER_DATA: &str = "userData";
+
+    // One line of a recording.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RecordedMessage {
+        // When the message was read, in ms.
+        pub received: i64,
+        // e.g. `btcusdt@trade`, `btcusdt@trade/ethusdt@trade` for combined streams or `userData`.
+        pub stream: String,
+        // The message as sent; a text that isn't JSON is kept as a string.
+        pub message: Value,
+    }
+
+    // The stream name in a websocket url, without the listen key.
+    pub(crate) fn stream_name(url: &str) -> String {
+        if let Some((_, streams)) = url.split_once("streams=") {
+            return streams.split('&').next().unwrap_or_default().to_string();
+        }
+        let name = url
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        // Market stream names all have an `@` or start with `!`; anything else is a listen key.
+        if name.contains('@') || name.starts_with('!') {
+            name.to_string()
+        } else {
+            USER_DATA.to_string()
+        }
+    }
+
+    // Writes every message read by this client's streams to `<dir>/<stream>-<UTC date>.jsonl`,
+    // one `RecordedMessage` per line, appending to files already there. Lines are flushed as they
+    // are written so a crash loses nothing.
+    #[derive(Debug)]
+    pub struct StreamRecorder {
+        dir: PathBuf,
+        // Stream name -> the file it is written to today.
+        files: Mutex<HashMap<String, (PathBuf, LineWriter<File>)>>,
+    }
+
+    impl StreamRecorder {
+        pub fn new(dir: &str) -> Result<Self, Box<dyn Error>> {
+            fs::create_dir_all(dir)?;
+            Ok(StreamRecorder {
+                dir: Path::new(dir).to_path_buf(),
+                files: Mutex::new(HashMap::new()),
+            })
+        }
+
+        pub(crate) fn record(&self, url: &str, text: &str) {
+            let received = Utc::now().timestamp_millis();
+            let stream = stream_name(url);
+            let line = RecordedMessage {
+                received: received,
+                message: serde_json::from_str(text).unwrap_or_else(|_| text.into()),
+                stream: stream.clone(),
+            };
+            if let Err(e) = self.write(line) {
+                tracing::warn!(stream = %stream, error = %e, "recording failed");
+            }
+        }
+
+        fn write(&self, line: RecordedMessage) -> Result<(), Box<dyn Error>> {
+            let file_name: String = line
+                .stream
+                .chars()
+                .map(|a| match a {
+                    'a'..='z' | 'A'..='Z' | '0'..='9' | '@' | '!' | '_' | '-' => a,
+                    _ => '_',
+                })
+                .collect();
+            let date = Utc
+                .timestamp_millis_opt(line.received)
+                .unwrap()
+                .format("%Y-%m-%d");
+            let path = self.dir.join(format!("{file_name}-{date}.jsonl"));
+            let mut files = self.files.lock().unwrap();
+            // A new day starts a new file.
+            if files.get(&line.stream).map(|a| &a.0) != Some(&path) {
+                let file = OpenOptions::new().create(true).append(true).open(&path)?;
+                files.insert(line.stream.clone(), (path, LineWriter::new(file)));
+            }
+            let writer = &mut files.get_mut(&line.stream).unwrap().1;
+            writeln!(writer, "{}", serde_json::to_string(&line)?)?;
+            Ok(())
+        }
+    }
+
+    pub fn read_recording(path: &str) -> Result<Vec<RecordedMessage>, Box<dyn Error>> {
+        let mut messages = vec![];
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                messages.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(messages)
+    }
+
+    // Feeds a recording to `handler` in order, parsed the way `run_handler` parses live
+    // messages, and returns how many were replayed. Messages that don't parse go to `on_error`.
+    pub fn replay_recording(
+        path: &str,
+        handler: &mut dyn EventHandler,
+    ) -> Result<usize, Box<dyn Error>> {
+        let messages = read_recording(path)?;
+        for message in &messages {
+            // Single streams are wrapped like combined ones so partial depth is told apart the
+            // same way.
+            let text = match &message.message {
+                Value::String(text) => text.clone(),
+                Value::Object(data) if data.contains_key("stream") => message.message.to_string(),
+                data => serde_json::json!({"stream": message.stream, "data": data}).to_string(),
+            };
+            if let Err(e) = dispatch(handler, &text) {
+                handler.on_error(e.to_string());
+            }
+        }
+        Ok(messages.len())
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Records every stream of this client, running or started later, to JSONL files in
+        // `dir`; replay them with `replay_recording`.
+        pub fn record_streams(&self, dir: &str) -> Result<(), Box<dyn Error>> {
+            let recorder = StreamRecorder::new(dir)?;
+            *self.streams.recorder.lock().unwrap() = Some(Arc::new(recorder));
+            Ok(())
+        }
+
+        pub fn stop_recording(&self) {
+            *self.streams.recorder.lock().unwrap() = None;
+        }
+    }
+}
@@ -0,0 +1,538 @@
+pub mod risk {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::execution::execution::decimal_field;
+    use crate::models::models::NewOrder;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use std::{collections::HashMap, error::Error, fmt};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum RiskLimit {
+        OrderQty,
+        OrderNotional,
+        PositionNotional,
+        OpenOrders,
+        DailyLoss,
+        Balance,
+    }
+
+    // `value` is what the order would reach, `max` what the limit allows.
+    #[derive(Debug, Clone)]
+    pub struct RiskLimitError {
+        pub symbol: String,
+        pub limit: RiskLimit,
+        pub value: Decimal,
+        pub max: Decimal,
+    }
+
+    impl fmt::Display for RiskLimitError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} order rejected by risk limit {:?}: {} exceeds {}",
+                self.symbol, self.limit, self.value, self.max
+            )
+        }
+    }
+
+    impl Error for RiskLimitError {}
+
+    // Every limit is off until set. Notionals are in the quote asset.
+    #[derive(Debug, Clone, Default)]
+    pub struct RiskLimits {
+        pub max_order_qty: HashMap<String, Decimal>,
+        pub max_order_notional: Option<Decimal>,
+        pub max_position_notional: HashMap<String, Decimal>,
+        pub max_open_orders: Option<usize>,
+        pub max_daily_loss: Option<Decimal>,
+        pub check_balance: bool,
+    }
+
+    impl RiskLimits {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        pub fn max_order_qty(mut self, symbol: &str, max_order_qty: Decimal) -> Self {
+            self.max_order_qty.insert(symbol.to_string(), max_order_qty);
+            self
+        }
+
+        pub fn max_order_notional(mut self, max_order_notional: Decimal) -> Self {
+            self.max_order_notional = Some(max_order_notional);
+            self
+        }
+
+        pub fn max_position_notional(
+            mut self,
+            symbol: &str,
+            max_position_notional: Decimal,
+        ) -> Self {
+            self.max_position_notional
+                .insert(symbol.to_string(), max_position_notional);
+            self
+        }
+
+        // Open orders across all symbols, including the new one.
+        pub fn max_open_orders(mut self, max_open_orders: usize) -> Self {
+            self.max_open_orders = Some(max_open_orders);
+            self
+        }
+
+        // Once the realized loss of the UTC day reaches this, only reduce-only orders pass.
+        pub fn max_daily_loss(mut self, max_daily_loss: Decimal) -> Self {
+            self.max_daily_loss = Some(max_daily_loss);
+            self
+        }
+
+        pub fn check_balance(mut self, check_balance: bool) -> Self {
+            self.check_balance = check_balance;
+            self
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct RiskState {
+        pub limits: RiskLimits,
+        day: String,
+        realized_pnl: Decimal,
+    }
+
+    impl RiskState {
+        fn today() -> String {
+            Utc::now().format("%Y-%m-%d").to_string()
+        }
+
+        // Realized PnL since 00:00 UTC.
+        pub fn daily_pnl(&self) -> Decimal {
+            if self.day == Self::today() {
+                self.realized_pnl
+            } else {
+                Decimal::ZERO
+            }
+        }
+
+        fn record(&mut self, pnl: Decimal) {
+            let today = Self::today();
+            if self.day != today {
+                self.day = today;
+                self.realized_pnl = Decimal::ZERO;
+            }
+            self.realized_pnl += pnl;
+        }
+    }
+
+    // What `panic_close_all` did, or in a dry run would have done.
+    #[derive(Debug, Clone, Default)]
+    pub struct CloseAllReport {
+        pub dry_run: bool,
+        // Symbols whose open orders were canceled.
+        pub canceled_symbols: Vec<String>,
+        pub close_orders: Vec<NewOrder>,
+        pub responses: Vec<serde_json::Value>,
+        // Failures don't stop the rest of the kill switch.
+        pub errors: Vec<String>,
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Checks every order `place_order` sends against `limits` from now on.
+        pub fn enable_risk_limits(&self, limits: RiskLimits) {
+            let mut risk = self.risk.lock().unwrap();
+            let state = risk.get_or_insert_with(Default::default);
+            state.limits = limits;
+        }
+
+        pub fn disable_risk_limits(&self) {
+            *self.risk.lock().unwrap() = None;
+        }
+
+        pub fn risk_state(&self) -> Option<RiskState> {
+            self.risk.lock().unwrap().clone()
+        }
+
+        // Feeds the daily loss limit, e.g. with `PnlCalculator::add_trade` results or the
+        // realized PnL of futures ORDER_TRADE_UPDATE events.
+        pub fn record_realized_pnl(&self, pnl: Decimal) {
+            if let Some(state) = self.risk.lock().unwrap().as_mut() {
+                state.record(pnl);
+            }
+        }
+
+        async fn available_balance(&self, asset: &str) -> Result<Decimal, Box<dyn Error>> {
+            if let Some(account) = self.paper_account() {
+                return Ok(account.balance(asset));
+            }
+            Ok(self
+                .get_balance()
+                .await?
+                .iter()
+                .find(|a| a.asset == asset)
+                .map(|a| a.free)
+                .unwrap_or_default())
+        }
+
+        // Signed size held on `symbol`: the base asset balance on spot, the net position on
+        // futures.
+        async fn position_qty(&self, symbol: &str) -> Result<Decimal, Box<dyn Error>> {
+            if self.account_type == "spot" {
+                let info = self.get_symbol_info(symbol).await?;
+                return self.account_equity(&info.base_asset).await;
+            }
+            if let Some(account) = self.paper_account() {
+                return Ok(account
+                    .positions
+                    .get(symbol)
+                    .map(|a| a.position_amt)
+                    .unwrap_or_default());
+            }
+            #[cfg(feature = "usdm-futures")]
+            {
+                Ok(self
+                    .get_position(symbol)
+                    .await?
+                    .iter()
+                    .map(|a| a.position_amt)
+                    .sum())
+            }
+            #[cfg(not(feature = "usdm-futures"))]
+            Ok(Decimal::ZERO)
+        }
+
+        pub(crate) async fn check_risk(&self, order: &NewOrder) -> Result<(), Box<dyn Error>> {
+            let state = match self.risk_state() {
+                Some(state) => state,
+                None => return Ok(()),
+            };
+            let limits = &state.limits;
+            let reject = |limit: RiskLimit, value: Decimal, max: Decimal| {
+                Err(Box::new(RiskLimitError {
+                    symbol: order.symbol.clone(),
+                    limit: limit,
+                    value: value,
+                    max: max,
+                }) as Box<dyn Error>)
+            };
+            if let Some(max) = limits.max_daily_loss {
+                let loss = -state.daily_pnl();
+                if loss >= max && !order.reduce_only {
+                    return reject(RiskLimit::DailyLoss, loss, max);
+                }
+            }
+            let qty = order.quantity.unwrap_or_default();
+            if let Some(max) = limits.max_order_qty.get(&order.symbol) {
+                if qty > *max {
+                    return reject(RiskLimit::OrderQty, qty, *max);
+                }
+            }
+            let price = match order.price.or(order.stop_price) {
+                Some(price) => price,
+                None if qty.is_zero() => Decimal::ZERO,
+                None => {
+                    let price = self.get_price(&order.symbol).await?;
+                    decimal_field(&price, "price").unwrap_or_default()
+                }
+            };
+            let notional = order.quote_order_qty.unwrap_or(qty * price);
+            if let Some(max) = limits.max_order_notional {
+                if notional > max {
+                    return reject(RiskLimit::OrderNotional, notional, max);
+                }
+            }
+            if let Some(max) = limits.max_open_orders {
+                let open = self.get_open_orders("").await?;
+                let count = open.as_array().map(|a| a.len()).unwrap_or_default() + 1;
+                if count > max {
+                    return reject(
+                        RiskLimit::OpenOrders,
+                        Decimal::from(count),
+                        Decimal::from(max),
+                    );
+                }
+            }
+            if let Some(max) = limits.max_position_notional.get(&order.symbol) {
+                let held = self.position_qty(&order.symbol).await?;
+                let fill = if order.side == "BUY" { qty } else { -qty };
+                let after = ((held + fill) * price).abs();
+                // An order that shrinks the position is always allowed.
+                if after > *max && after > (held * price).abs() {
+                    return reject(RiskLimit::PositionNotional, after, *max);
+                }
+            }
+            if limits.check_balance && !order.reduce_only {
+                let info = self.get_symbol_info(&order.symbol).await?;
+                let (asset, required) = if self.account_type == "spot" && order.side == "SELL" {
+                    (info.base_asset.clone(), qty)
+                } else if self.account_type == "spot" {
+                    (info.quote_asset.clone(), notional)
+                } else {
+                    #[cfg(feature = "usdm-futures")]
+                    let leverage = match self.is_paper_trading() {
+                        true => 1,
+                        false => self
+                            .get_symbol_config(&order.symbol)
+                            .await?
+                            .first()
+                            .map_or(1, |a| a.leverage.max(1)),
+                    };
+                    #[cfg(not(feature = "usdm-futures"))]
+                    let leverage = 1;
+                    (info.quote_asset.clone(), notional / Decimal::from(leverage))
+                };
+                let available = self.available_balance(&asset).await?;
+                if required > available {
+                    return reject(RiskLimit::Balance, required, available);
+                }
+            }
+            Ok(())
+        }
+
+        async fn close_all_orders(
+            &self,
+            sell_spot_to: &str,
+        ) -> Result<Vec<NewOrder>, Box<dyn Error>> {
+            let mut orders = Vec::new();
+            if self.account_type == "spot" {
+                if sell_spot_to.is_empty() {
+                    return Ok(orders);
+                }
+                let balances = match self.paper_account() {
+                    Some(account) => account.asset_balances(),
+                    None => self.get_balance().await?,
+                };
+                for balance in balances {
+                    let symbol = format!("{}{sell_spot_to}", balance.asset);
+                    if balance.asset == sell_spot_to || balance.free.is_zero() {
+                        continue;
+                    }
+                    // Assets without a market against `sell_spot_to` are left alone.
+                    let filters = match self.get_symbol_filters(&symbol).await {
+                        Ok(filters) => filters,
+                        Err(_) => continue,
+                    };
+                    let qty = filters.round_qty(balance.free);
+                    if qty.is_zero() || qty < filters.min_qty {
+                        continue;
+                    }
+                    orders.push(NewOrder::new(&symbol, "SELL", "MARKET").quantity(qty));
+                }
+                return Ok(orders);
+            }
+            #[cfg(feature = "usdm-futures")]
+            {
+                let positions = match self.paper_account() {
+                    Some(account) => account
+                        .positions
+                        .into_iter()
+                        .map(|(symbol, a)| (symbol, "BOTH".to_string(), a.position_amt))
+                        .collect::<Vec<_>>(),
+                    None => self
+                        .get_position("")
+                        .await?
+                        .into_iter()
+                        .map(|a| (a.symbol, a.position_side, a.position_amt))
+                        .collect(),
+                };
+                for (symbol, position_side, position_amt) in positions {
+                    if position_amt.is_zero() {
+                        continue;
+                    }
+                    orders.push(NewOrder::close_position(
+                        &symbol,
+                        &position_side,
+                        position_amt,
+                        position_amt.abs(),
+                    ));
+                }
+            }
+            Ok(orders)
+        }
+
+        // Kill switch: cancels the open orders of every symbol, then closes every futures
+        // position with MARKET orders, or on spot sells every free balance with a market against
+        // `sell_spot_to` (empty keeps spot holdings). Risk limits are not applied to these
+        // orders. With `dry_run` nothing is sent and the report lists what would be.
+        pub async fn panic_close_all(
+            &self,
+            dry_run: bool,
+            sell_spot_to: &str,
+        ) -> Result<CloseAllReport, Box<dyn Error>> {
+            let mut report = CloseAllReport {
+                dry_run: dry_run,
+                ..Default::default()
+            };
+            let open_orders = self.get_open_orders("").await?;
+            let mut symbols = open_orders
+                .as_array()
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|a| a["symbol"].as_str().map(|a| a.to_string()))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            symbols.sort();
+            symbols.dedup();
+            for symbol in symbols {
+                if !dry_run {
+                    let canceled = self.cancel_order(&symbol, "", true).await;
+                    if let Err(e) = canceled {
+                        report.errors.push(format!("cancel {symbol}: {e}"));
+                        continue;
+                    }
+                }
+                report.canceled_symbols.push(symbol);
+            }
+            // Balances and positions are read after the cancels released what they locked.
+            report.close_orders = self.close_all_orders(sell_spot_to).await?;
+            if dry_run {
+                return Ok(report);
+            }
+            for order in &report.close_orders {
+                let order = NewOrder {
+                    skip_risk: true,
+                    ..order.clone().auto_round(true)
+                };
+                // `place_order` turns a rejection into an error, so it lands in `errors`.
+                match self.place_order(&order).await {
+                    Ok(response) => report.responses.push(response),
+                    Err(e) => report.errors.push(format!("close {}: {e}", order.symbol)),
+                }
+            }
+            tracing::warn!(
+                canceled = report.canceled_symbols.len(),
+                closed = report.responses.len(),
+                errors = report.errors.len(),
+                "kill switch ran"
+            );
+            Ok(report)
+        }
+    }
+
+    // The client defaults to spot, which needs the `spot` feature.
+    #[cfg(all(test, feature = "spot"))]
+    mod tests {
+        use super::*;
+        use crate::transport::transport::MockTransport;
+        use std::sync::Arc;
+
+        // 2 BTC and 500 USDT held, BTCUSDT at 100 and two open orders.
+        async fn client(limits: RiskLimits) -> BinanceAPI<'static> {
+            let transport = MockTransport::new()
+                .with_fixture("/api/v3/ticker/price", r#"{"symbol":"BTCUSDT","price":"100"}"#)
+                .with_fixture(
+                    "/api/v3/openOrders",
+                    r#"[{"symbol":"BTCUSDT"},{"symbol":"ETHUSDT"}]"#,
+                )
+                .with_fixture(
+                    "/api/v3/account",
+                    r#"{"balances":[{"asset":"BTC","free":"2","locked":"0"},{"asset":"USDT","free":"500","locked":"0"}]}"#,
+                )
+                .with_fixture(
+                    "/api/v3/exchangeInfo",
+                    r#"{"symbols":[{"symbol":"BTCUSDT","status":"TRADING","baseAsset":"BTC","quoteAsset":"USDT"}]}"#,
+                );
+            let bn_api = BinanceAPI::builder()
+                .keys("key", "secret")
+                .transport(Arc::new(transport))
+                .build()
+                .await
+                .unwrap();
+            bn_api.enable_risk_limits(limits);
+            bn_api
+        }
+
+        fn market(side: &str, qty: i64) -> NewOrder {
+            NewOrder::new("BTCUSDT", side, "MARKET").quantity(Decimal::from(qty))
+        }
+
+        async fn rejected_by(bn_api: &BinanceAPI<'_>, order: &NewOrder) -> Option<RiskLimit> {
+            match bn_api.check_risk(order).await {
+                Ok(()) => None,
+                Err(e) => Some(e.downcast_ref::<RiskLimitError>().unwrap().limit),
+            }
+        }
+
+        #[tokio::test]
+        async fn order_qty_and_notional_limits() {
+            let limits = RiskLimits::new()
+                .max_order_qty("BTCUSDT", Decimal::from(2))
+                .max_order_notional(Decimal::from(150));
+            let bn_api = client(limits).await;
+            assert_eq!(
+                rejected_by(&bn_api, &market("BUY", 3)).await,
+                Some(RiskLimit::OrderQty)
+            );
+            // Priced at the last price without a limit price.
+            assert_eq!(
+                rejected_by(&bn_api, &market("BUY", 2)).await,
+                Some(RiskLimit::OrderNotional)
+            );
+            assert_eq!(rejected_by(&bn_api, &market("BUY", 1)).await, None);
+            let limit = NewOrder::new("BTCUSDT", "BUY", "LIMIT")
+                .quantity(Decimal::ONE)
+                .price(Decimal::from(160));
+            assert_eq!(
+                rejected_by(&bn_api, &limit).await,
+                Some(RiskLimit::OrderNotional)
+            );
+        }
+
+        #[tokio::test]
+        async fn open_orders_limit_counts_the_new_order() {
+            let bn_api = client(RiskLimits::new().max_open_orders(2)).await;
+            assert_eq!(
+                rejected_by(&bn_api, &market("BUY", 1)).await,
+                Some(RiskLimit::OpenOrders)
+            );
+            bn_api.enable_risk_limits(RiskLimits::new().max_open_orders(3));
+            assert_eq!(rejected_by(&bn_api, &market("BUY", 1)).await, None);
+        }
+
+        #[tokio::test]
+        async fn shrinking_a_position_always_passes_its_notional_limit() {
+            // 2 BTC at 100 is already above the limit.
+            let bn_api =
+                client(RiskLimits::new().max_position_notional("BTCUSDT", Decimal::from(50))).await;
+            assert_eq!(
+                rejected_by(&bn_api, &market("BUY", 1)).await,
+                Some(RiskLimit::PositionNotional)
+            );
+            assert_eq!(rejected_by(&bn_api, &market("SELL", 1)).await, None);
+            // Selling through zero to -3 BTC grows it again.
+            assert_eq!(
+                rejected_by(&bn_api, &market("SELL", 5)).await,
+                Some(RiskLimit::PositionNotional)
+            );
+        }
+
+        #[tokio::test]
+        async fn daily_loss_limit_lets_only_reduce_only_orders_through() {
+            let bn_api = client(RiskLimits::new().max_daily_loss(Decimal::from(100))).await;
+            bn_api.record_realized_pnl(Decimal::from(-60));
+            assert_eq!(rejected_by(&bn_api, &market("BUY", 1)).await, None);
+            bn_api.record_realized_pnl(Decimal::from(-60));
+            assert_eq!(
+                rejected_by(&bn_api, &market("BUY", 1)).await,
+                Some(RiskLimit::DailyLoss)
+            );
+            assert_eq!(
+                rejected_by(&bn_api, &market("SELL", 1).reduce_only(true)).await,
+                None
+            );
+        }
+
+        #[tokio::test]
+        async fn balance_check_needs_the_quote_to_buy_and_the_base_to_sell() {
+            let bn_api = client(RiskLimits::new().check_balance(true)).await;
+            assert_eq!(rejected_by(&bn_api, &market("BUY", 5)).await, None);
+            assert_eq!(
+                rejected_by(&bn_api, &market("BUY", 6)).await,
+                Some(RiskLimit::Balance)
+            );
+            assert_eq!(rejected_by(&bn_api, &market("SELL", 2)).await, None);
+            assert_eq!(
+                rejected_by(&bn_api, &market("SELL", 3)).await,
+                Some(RiskLimit::Balance)
+            );
+        }
+    }
+}
@@ -0,0 +1,267 @@
+pub mod rollover {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::{ContractType, NewOrder};
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    #[cfg(feature = "websocket")]
+    use std::net::TcpStream;
+    use std::{error::Error, time::Duration};
+    #[cfg(feature = "websocket")]
+    use tungstenite::{protocol::WebSocket, stream::MaybeTlsStream};
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct QuarterlyContract {
+        // e.g. BTCUSDT_250328.
+        pub symbol: String,
+        pub pair: String,
+        pub contract_type: ContractType,
+        // In ms.
+        pub delivery_date: i64,
+        pub onboard_date: i64,
+    }
+
+    impl QuarterlyContract {
+        // Time left until delivery at `now` (ms); zero once delivered.
+        pub fn time_to_delivery(&self, now: i64) -> Duration {
+            Duration::from_millis((self.delivery_date - now).max(0) as u64)
+        }
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct QuarterlyContracts {
+        pub current: Option<QuarterlyContract>,
+        pub next: Option<QuarterlyContract>,
+    }
+
+    // The trading quarterly contracts of `pair` (e.g. BTCUSDT) in a futures exchangeInfo.
+    pub fn quarterly_contracts(exchange_info: &Value, pair: &str) -> QuarterlyContracts {
+        let mut contracts = QuarterlyContracts::default();
+        let symbols = exchange_info["symbols"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        for symbol in &symbols {
+            if !symbol["pair"]
+                .as_str()
+                .unwrap_or_default()
+                .eq_ignore_ascii_case(pair)
+                || symbol["status"].as_str() != Some("TRADING")
+            {
+                continue;
+            }
+            let contract_type = match serde_json::from_value(symbol["contractType"].clone()) {
+                Ok(ContractType::Perpetual) | Err(_) => continue,
+                Ok(contract_type) => contract_type,
+            };
+            let contract = QuarterlyContract {
+                symbol: symbol["symbol"].as_str().unwrap_or_default().to_string(),
+                pair: symbol["pair"].as_str().unwrap_or_default().to_string(),
+                contract_type: contract_type,
+                delivery_date: symbol["deliveryDate"].as_i64().unwrap_or_default(),
+                onboard_date: symbol["onboardDate"].as_i64().unwrap_or_default(),
+            };
+            match contract_type {
+                ContractType::CurrentQuarter => contracts.current = Some(contract),
+                _ => contracts.next = Some(contract),
+            }
+        }
+        contracts
+    }
+
+    // One position moved from the expiring contract to the next.
+    #[derive(Debug, Clone)]
+    pub struct RolledPosition {
+        // "BOTH" in one-way mode, else LONG or SHORT.
+        pub position_side: String,
+        // Signed: negative for shorts.
+        pub position_amt: Decimal,
+        pub close_order: Value,
+        pub open_order: Value,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Rollover {
+        pub from: QuarterlyContract,
+        pub to: QuarterlyContract,
+        // Empty when nothing was open in `from`.
+        pub positions: Vec<RolledPosition>,
+    }
+
+    // Rolls the positions in the current quarter contract of `pair` into the next quarter once
+    // delivery is less than `roll_before` away, through `check_rollover` or `wait_rollover`. Each
+    // contract is rolled once.
+    #[derive(Debug, Clone)]
+    pub struct ContractRoller {
+        pub pair: String,
+        pub roll_before: Duration,
+        // The contract rolled out of last.
+        rolled: String,
+    }
+
+    impl ContractRoller {
+        pub fn new(pair: &str, roll_before: Duration) -> Self {
+            ContractRoller {
+                pair: pair.to_uppercase(),
+                roll_before: roll_before,
+                rolled: String::new(),
+            }
+        }
+    }
+
+    // Marks `from` as rolled, since positions may have moved already, and says how far it got.
+    fn partial_rollover(
+        roller: &mut ContractRoller,
+        from: &QuarterlyContract,
+        to: &QuarterlyContract,
+        rolled: &[RolledPosition],
+        error: &str,
+    ) -> Box<dyn Error> {
+        roller.rolled = from.symbol.clone();
+        let rolled = rolled
+            .iter()
+            .map(|a| format!("{} {}", a.position_side, a.position_amt))
+            .collect::<Vec<_>>();
+        format!(
+            "Partial rollover from `{}` to `{}`, rolled [{}] before: {}",
+            from.symbol,
+            to.symbol,
+            rolled.join(", "),
+            error
+        )
+        .into()
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        pub async fn get_quarterly_contracts(
+            &self,
+            pair: &str,
+        ) -> Result<QuarterlyContracts, Box<dyn Error>> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can get quarterly contracts."
+            );
+            Ok(quarterly_contracts(&self.get_exchange_info().await?, pair))
+        }
+
+        // Rolls when the current quarter is due, closing each position with a market order and
+        // opening the same amount on the next quarter; None when it is not due yet. A roll that
+        // stops after an order went through is an error that still marks the contract rolled.
+        pub async fn check_rollover(
+            &self,
+            roller: &mut ContractRoller,
+        ) -> Result<Option<Rollover>, Box<dyn Error>> {
+            let contracts = self.get_quarterly_contracts(&roller.pair).await?;
+            let (from, to) = match (contracts.current, contracts.next) {
+                (Some(from), Some(to)) => (from, to),
+                _ => {
+                    return Err(
+                        format!("No quarter contracts to roll `{}` with.", roller.pair).into(),
+                    )
+                }
+            };
+            if from.symbol == roller.rolled
+                || from.time_to_delivery(Utc::now().timestamp_millis()) > roller.roll_before
+            {
+                return Ok(None);
+            }
+            let mut positions = vec![];
+            let from_positions = self.get_position(&from.symbol).await?;
+            for position in from_positions {
+                if position.position_amt.is_zero() {
+                    continue;
+                }
+                let open_side = if position.position_amt.is_sign_positive() {
+                    "BUY"
+                } else {
+                    "SELL"
+                };
+                let qty = position.position_amt.abs();
+                // RESULT, so the response says whether the close filled.
+                let close = NewOrder::close_position(
+                    &from.symbol,
+                    &position.position_side,
+                    position.position_amt,
+                    qty,
+                )
+                .response_type("RESULT")
+                .auto_round(true);
+                let mut open = NewOrder::new(&to.symbol, open_side, "MARKET")
+                    .quantity(qty)
+                    .auto_round(true);
+                if position.position_side != "BOTH" {
+                    open = open.position_side(&position.position_side);
+                }
+                tracing::info!(from = %from.symbol, to = %to.symbol, qty = %position.position_amt, "rolling position");
+                // Closed first: if opening fails the position is flat rather than doubled.
+                let close_order = match self.place_order(&close).await {
+                    Ok(order) if order["status"].as_str() == Some("FILLED") => order,
+                    // Nothing has moved yet, so a later call can simply try again.
+                    Err(e) if positions.is_empty() => return Err(e),
+                    Ok(order) => {
+                        let error = format!("close order ended {}", order["status"]);
+                        return Err(partial_rollover(roller, &from, &to, &positions, &error));
+                    }
+                    Err(e) => {
+                        let error = format!("closing {} failed: {e}", position.position_side);
+                        return Err(partial_rollover(roller, &from, &to, &positions, &error));
+                    }
+                };
+                let open_order = match self.place_order(&open).await {
+                    Ok(order) => order,
+                    Err(e) => {
+                        let error = format!(
+                            "closed {} {} but opening it failed: {e}",
+                            position.position_side, position.position_amt
+                        );
+                        return Err(partial_rollover(roller, &from, &to, &positions, &error));
+                    }
+                };
+                positions.push(RolledPosition {
+                    position_side: position.position_side.clone(),
+                    position_amt: position.position_amt,
+                    close_order: close_order,
+                    open_order: open_order,
+                });
+            }
+            roller.rolled = from.symbol.clone();
+            Ok(Some(Rollover {
+                from: from,
+                to: to,
+                positions: positions,
+            }))
+        }
+
+        // Checks every `check_every` until the next rollover and returns it.
+        pub async fn wait_rollover(
+            &self,
+            roller: &mut ContractRoller,
+            check_every: Duration,
+        ) -> Result<Rollover, Box<dyn Error>> {
+            loop {
+                if let Some(rollover) = self.check_rollover(roller).await? {
+                    return Ok(rollover);
+                }
+                self.transport.0.sleep(check_every).await;
+            }
+        }
+
+        // Moves `sub_types` (e.g. `aggTrade`, `markPrice`) of `ws` from the rolled contract
+        // to the next.
+        #[cfg(feature = "websocket")]
+        pub async fn resubscribe_rollover(
+            &self,
+            ws: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+            rollover: &Rollover,
+            sub_types: &Vec<&str>,
+        ) -> Result<(), Box<dyn Error>> {
+            for sub_type in sub_types {
+                self.unsubscribe_websocket(ws, &vec![rollover.from.symbol.as_str()], sub_type)
+                    .await?;
+                self.subscribe_websocket(ws, &vec![rollover.to.symbol.as_str()], sub_type)
+                    .await?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,409 @@
+pub mod scheduler {
+    use crate::binance_api::binance_api::{BinanceAPI, BinanceError};
+    use crate::models::models::NewOrder;
+    use chrono::{NaiveDateTime, TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+    use std::{
+        error::Error,
+        fs,
+        path::{Path, PathBuf},
+        time::Duration,
+    };
+
+    // Binance takes client order ids of up to 36 of `.A-Za-z:/0-9_-`, and the name is put between
+    // `dca_` and `_<13 digit slot>`.
+    const MAX_NAME_LEN: usize = 36 - 18;
+
+    fn check_name(name: &str) -> Result<(), String> {
+        let allowed = |a: char| a.is_ascii_alphanumeric() || ".:/_-".contains(a);
+        if name.is_empty() || name.len() > MAX_NAME_LEN || !name.chars().all(allowed) {
+            return Err(format!(
+                "Schedule name `{name}` must be 1 to {MAX_NAME_LEN} of `.A-Za-z:/0-9_-`."
+            ));
+        }
+        Ok(())
+    }
+
+    // A recurring order placed at `start_time + n * every_ms`. `last_slot` is the slot that was
+    // last placed and `pending_slot` one whose order was sent but not yet confirmed.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ScheduledOrder {
+        pub name: String,
+        pub symbol: String,
+        pub side: String,
+        pub quantity: Decimal,
+        pub price: Option<Decimal>,
+        pub start_time: i64,
+        pub every_ms: i64,
+        #[serde(default)]
+        pub last_slot: Option<i64>,
+        #[serde(default)]
+        pub pending_slot: Option<i64>,
+    }
+
+    impl ScheduledOrder {
+        // A MARKET order from `start_time_utc` ("%Y-%m-%d %H:%M:%S") on, every `every`.
+        pub fn new(
+            name: &str,
+            symbol: &str,
+            side: &str,
+            quantity: Decimal,
+            start_time_utc: &str,
+            every: Duration,
+        ) -> Self {
+            assert!(
+                every.as_millis() > 0,
+                "schedule interval must be at least 1ms."
+            );
+            if let Err(e) = check_name(name) {
+                panic!("{}", e);
+            }
+            let start_time = NaiveDateTime::parse_from_str(start_time_utc, "%Y-%m-%d %H:%M:%S")
+                .map(|a| Utc.from_utc_datetime(&a).timestamp_millis())
+                .unwrap_or_else(|_| Self::panic_not_define("start_time_utc", start_time_utc));
+            ScheduledOrder {
+                name: name.to_string(),
+                symbol: symbol.to_string(),
+                side: side.to_string(),
+                quantity: quantity,
+                price: None,
+                start_time: start_time,
+                every_ms: every.as_millis() as i64,
+                last_slot: None,
+                pending_slot: None,
+            }
+        }
+
+        fn panic_not_define(type_name: &str, content: &str) -> ! {
+            panic!(
+                "{} `{}` is not defined, use a format like `2024-01-01 00:00:00`.",
+                type_name, content
+            )
+        }
+
+        // Places a GTC LIMIT order at `price` instead of a MARKET order.
+        pub fn limit(mut self, price: Decimal) -> Self {
+            self.price = Some(price);
+            self
+        }
+
+        // The latest slot at or before `now`, placed or not.
+        fn slot_at(&self, now: i64) -> Option<i64> {
+            if now < self.start_time {
+                None
+            } else {
+                Some(now - (now - self.start_time) % self.every_ms)
+            }
+        }
+
+        // The latest slot at or before `now` that hasn't been placed; missed slots are not
+        // caught up, so a scheduler that was down for three days places one order, not three.
+        pub fn due_slot(&self, now: i64) -> Option<i64> {
+            let slot = self.slot_at(now)?;
+            match self.last_slot {
+                Some(last) if last >= slot => None,
+                _ => Some(slot),
+            }
+        }
+
+        pub fn next_slot(&self, now: i64) -> i64 {
+            if now < self.start_time {
+                self.start_time
+            } else {
+                now - (now - self.start_time) % self.every_ms + self.every_ms
+            }
+        }
+
+        // Derived from the slot so a retried placement can be recognised on the exchange.
+        pub fn client_order_id(&self, slot: i64) -> String {
+            format!("dca_{}_{}", self.name, slot)
+        }
+
+        fn order(&self, slot: i64) -> NewOrder {
+            let order = match self.price {
+                Some(price) => NewOrder::new(&self.symbol, &self.side, "LIMIT")
+                    .price(price)
+                    .time_in_force("GTC"),
+                None => NewOrder::new(&self.symbol, &self.side, "MARKET"),
+            };
+            order
+                .quantity(self.quantity)
+                .client_order_id(&self.client_order_id(slot))
+                .auto_round(true)
+        }
+    }
+
+    // Schedules kept in a JSON file, written after every change of state so a restart neither
+    // repeats nor loses a slot.
+    #[derive(Debug)]
+    pub struct DcaScheduler {
+        path: PathBuf,
+        schedules: Vec<ScheduledOrder>,
+    }
+
+    impl DcaScheduler {
+        // Loads the schedules at `path`, or starts empty if the file doesn't exist yet.
+        pub fn open(path: &str) -> Result<Self, Box<dyn Error>> {
+            let path = Path::new(path).to_path_buf();
+            let schedules = if path.exists() {
+                serde_json::from_str(&fs::read_to_string(&path)?)?
+            } else {
+                vec![]
+            };
+            Ok(DcaScheduler {
+                path: path,
+                schedules: schedules,
+            })
+        }
+
+        pub fn schedules(&self) -> &Vec<ScheduledOrder> {
+            &self.schedules
+        }
+
+        // Adds or replaces the schedule with the same name; a replaced schedule keeps its state.
+        pub fn add(&mut self, schedule: ScheduledOrder) -> Result<(), Box<dyn Error>> {
+            check_name(&schedule.name)?;
+            match self.schedules.iter_mut().find(|a| a.name == schedule.name) {
+                Some(a) => {
+                    *a = ScheduledOrder {
+                        last_slot: a.last_slot,
+                        pending_slot: a.pending_slot,
+                        ..schedule
+                    }
+                }
+                None => self.schedules.push(schedule),
+            }
+            self.save()
+        }
+
+        pub fn remove(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+            self.schedules.retain(|a| a.name != name);
+            self.save()
+        }
+
+        // Writes to a temporary file first so a crash never leaves a half written schedule.
+        pub fn save(&self) -> Result<(), Box<dyn Error>> {
+            let tmp = self.path.with_extension("tmp");
+            fs::write(&tmp, serde_json::to_string_pretty(&self.schedules)?)?;
+            fs::rename(tmp, &self.path)?;
+            Ok(())
+        }
+
+        pub fn next_slot(&self, now: i64) -> Option<i64> {
+            self.schedules.iter().map(|a| a.next_slot(now)).min()
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Whether the order of `slot` reached the exchange before the scheduler went down.
+        async fn slot_was_placed(
+            &self,
+            schedule: &ScheduledOrder,
+            slot: i64,
+        ) -> Result<bool, Box<dyn Error>> {
+            let since = Utc
+                .timestamp_millis_opt(slot)
+                .unwrap()
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string();
+            let client_order_id = schedule.client_order_id(slot);
+            Ok(self
+                .get_all_orders(&schedule.symbol, &since, "")
+                .await?
+                .iter()
+                .any(|a| a["clientOrderId"].as_str() == Some(client_order_id.as_str())))
+        }
+
+        // Places the orders of every due schedule once and returns their responses. A rejected
+        // order returns the error with its slot still pending, so the next run looks it up on
+        // the exchange and places it again rather than skipping it, until a later slot is due:
+        // a pending slot older than the due one is dropped like any missed slot.
+        pub async fn run_due_schedules(
+            &self,
+            scheduler: &mut DcaScheduler,
+        ) -> Result<Vec<Value>, Box<dyn Error>> {
+            let now = Utc::now().timestamp_millis();
+            let mut responses = Vec::new();
+            for i in 0..scheduler.schedules.len() {
+                // A slot left pending by a crash is only resolved against the exchange.
+                if let Some(slot) = scheduler.schedules[i].pending_slot {
+                    if scheduler.schedules[i].slot_at(now) > Some(slot) {
+                        tracing::warn!(
+                            schedule = %scheduler.schedules[i].name,
+                            slot = slot,
+                            "dropping a pending slot older than the due one"
+                        );
+                    } else {
+                        if !self.slot_was_placed(&scheduler.schedules[i], slot).await? {
+                            let order = scheduler.schedules[i].order(slot);
+                            responses.push(self.place_order(&order).await?);
+                        }
+                        scheduler.schedules[i].last_slot = Some(slot);
+                    }
+                    scheduler.schedules[i].pending_slot = None;
+                    scheduler.save()?;
+                }
+                let slot = match scheduler.schedules[i].due_slot(now) {
+                    Some(slot) => slot,
+                    None => continue,
+                };
+                scheduler.schedules[i].pending_slot = Some(slot);
+                scheduler.save()?;
+                let order = scheduler.schedules[i].order(slot);
+                // `place_order` fails on a rejection, before the slot counts as placed.
+                responses.push(self.place_order(&order).await?);
+                scheduler.schedules[i].last_slot = Some(slot);
+                scheduler.schedules[i].pending_slot = None;
+                scheduler.save()?;
+            }
+            Ok(responses)
+        }
+
+        // Runs the schedules, sleeping until the next slot in between. An order the exchange
+        // rejects is logged and tried again on the next wake up until its slot is dropped; any
+        // other error stops the scheduler.
+        pub async fn run_scheduler(
+            &self,
+            scheduler: &mut DcaScheduler,
+        ) -> Result<(), Box<dyn Error>> {
+            loop {
+                if let Err(e) = self.run_due_schedules(scheduler).await {
+                    if e.downcast_ref::<BinanceError>().is_none() {
+                        return Err(e);
+                    }
+                    tracing::warn!(error = %e, "scheduled order rejected");
+                }
+                let now = Utc::now().timestamp_millis();
+                let wait = match scheduler.next_slot(now) {
+                    Some(next) => Duration::from_millis((next - now).clamp(1, 60_000) as u64),
+                    None => Duration::from_secs(60),
+                };
+                self.transport.0.sleep(wait).await;
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        #[cfg(feature = "spot")]
+        use crate::transport::transport::MockTransport;
+        #[cfg(feature = "spot")]
+        use std::sync::Arc;
+
+        const DAY: i64 = 86_400_000;
+        // 2024-01-01 00:00:00 UTC.
+        const START: i64 = 1_704_067_200_000;
+
+        fn daily(name: &str) -> ScheduledOrder {
+            ScheduledOrder::new(
+                name,
+                "BTCUSDT",
+                "BUY",
+                Decimal::ONE,
+                "2024-01-01 00:00:00",
+                Duration::from_secs(86_400),
+            )
+        }
+
+        #[test]
+        fn due_slot_is_the_latest_slot_not_yet_placed() {
+            let mut schedule = daily("daily");
+            assert_eq!(schedule.due_slot(START - 1), None);
+            assert_eq!(schedule.due_slot(START), Some(START));
+            assert_eq!(schedule.due_slot(START + DAY + DAY / 2), Some(START + DAY));
+            schedule.last_slot = Some(START + DAY);
+            assert_eq!(schedule.due_slot(START + DAY + DAY / 2), None);
+            // Three missed days place only the latest slot.
+            assert_eq!(
+                schedule.due_slot(START + 4 * DAY + 1),
+                Some(START + 4 * DAY)
+            );
+        }
+
+        #[test]
+        fn next_slot_is_the_first_slot_after_now() {
+            let schedule = daily("daily");
+            assert_eq!(schedule.next_slot(START - DAY), START);
+            assert_eq!(schedule.next_slot(START), START + DAY);
+            assert_eq!(schedule.next_slot(START + DAY / 2), START + DAY);
+            assert_eq!(schedule.client_order_id(START), "dca_daily_1704067200000");
+        }
+
+        #[cfg(feature = "spot")]
+        async fn client(transport: Arc<MockTransport>) -> BinanceAPI<'static> {
+            BinanceAPI::builder()
+                .keys("key", "secret")
+                .transport(transport)
+                .build()
+                .await
+                .unwrap()
+        }
+
+        #[cfg(feature = "spot")]
+        fn scheduler_with(schedule: ScheduledOrder) -> DcaScheduler {
+            let path = std::env::temp_dir().join(format!(
+                "dca_{}_{}.json",
+                schedule.name,
+                std::process::id()
+            ));
+            // A file left by an earlier failed run would keep its slots.
+            let _ = fs::remove_file(&path);
+            let mut scheduler = DcaScheduler::open(path.to_str().unwrap()).unwrap();
+            scheduler.add(schedule).unwrap();
+            scheduler
+        }
+
+        #[cfg(feature = "spot")]
+        #[tokio::test]
+        async fn pending_slot_older_than_the_due_one_is_dropped() {
+            let transport = Arc::new(
+                MockTransport::new()
+                    .with_fixture(
+                        "/api/v3/exchangeInfo",
+                        r#"{"symbols":[{"symbol":"BTCUSDT","status":"TRADING","baseAsset":"BTC","quoteAsset":"USDT"}]}"#,
+                    )
+                    .with_fixture("/api/v3/order", r#"{"symbol":"BTCUSDT","orderId":1}"#),
+            );
+            let bn_api = client(transport.clone()).await;
+            let mut schedule = daily("stale");
+            let due = schedule.due_slot(Utc::now().timestamp_millis()).unwrap();
+            schedule.pending_slot = Some(due - 3 * DAY);
+            let mut scheduler = scheduler_with(schedule);
+            let responses = bn_api.run_due_schedules(&mut scheduler).await.unwrap();
+            fs::remove_file(&scheduler.path).unwrap();
+            assert_eq!(responses.len(), 1);
+            assert!(transport
+                .requests()
+                .iter()
+                .all(|a| a.path() != "/api/v3/allOrders"));
+            assert_eq!(scheduler.schedules()[0].last_slot, Some(due));
+            assert_eq!(scheduler.schedules()[0].pending_slot, None);
+        }
+
+        #[cfg(feature = "spot")]
+        #[tokio::test]
+        async fn pending_due_slot_found_on_the_exchange_is_not_placed_again() {
+            let mut schedule = daily("placed");
+            let due = schedule.due_slot(Utc::now().timestamp_millis()).unwrap();
+            schedule.pending_slot = Some(due);
+            let transport = Arc::new(MockTransport::new().with_fixture(
+                "/api/v3/allOrders",
+                &format!(
+                    r#"[{{"orderId":1,"clientOrderId":"{}","time":{due}}}]"#,
+                    schedule.client_order_id(due)
+                ),
+            ));
+            let bn_api = client(transport.clone()).await;
+            let mut scheduler = scheduler_with(schedule);
+            let responses = bn_api.run_due_schedules(&mut scheduler).await.unwrap();
+            fs::remove_file(&scheduler.path).unwrap();
+            assert!(responses.is_empty());
+            assert!(transport.requests().iter().all(|a| a.method != "POST"));
+            assert_eq!(scheduler.schedules()[0].last_slot, Some(due));
+            assert_eq!(scheduler.schedules()[0].pending_slot, None);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+pub mod simple_earn {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::{EarnProduct, EarnRedemption, EarnSubscription};
+    use std::error::Error;
+
+    pub struct SimpleEarn<'b, 'a> {
+        bn_api: &'b BinanceAPI<'a>,
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        pub fn simple_earn(&self) -> SimpleEarn<'_, 'a> {
+            SimpleEarn { bn_api: self }
+        }
+    }
+
+    impl<'b, 'a> SimpleEarn<'b, 'a> {
+        fn generate_earn_url(&self, product_type: &str, path: &str) -> String {
+            let product_type = match product_type {
+                "flexible" | "locked" => product_type,
+                _ => BinanceAPI::panic_not_define("Simple Earn product type", product_type, ""),
+            };
+            format!(
+                "{}/sapi/v1/simple-earn/{}{}",
+                self.bn_api.sapi_url, product_type, path
+            )
+        }
+
+        pub async fn get_products(
+            &self,
+            product_type: &str,
+            asset: &str,
+        ) -> Result<Vec<EarnProduct>, Box<dyn Error>> {
+            let url = self.generate_earn_url(product_type, "/list");
+            let mut param_map = std::collections::HashMap::new();
+            if !asset.is_empty() {
+                param_map.insert("asset".to_string(), asset.to_string());
+            }
+            self.bn_api
+                .paginate_rows(url.as_str(), &mut param_map, true)
+                .await
+        }
+
+        pub async fn subscribe(
+            &self,
+            product_type: &str,
+            product_id: &str,
+            amount: &str,
+        ) -> Result<EarnSubscription, Box<dyn Error>> {
+            let url = self.generate_earn_url(product_type, "/subscribe");
+            let id_key = if product_type == "locked" {
+                "projectId"
+            } else {
+                "productId"
+            };
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert(id_key.to_string(), product_id.to_string());
+            param_map.insert("amount".to_string(), amount.to_string());
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+
+        pub async fn redeem(
+            &self,
+            product_type: &str,
+            id: &str,
+            amount: &str,
+        ) -> Result<EarnRedemption, Box<dyn Error>> {
+            let url = self.generate_earn_url(product_type, "/redeem");
+            let mut param_map = std::collections::HashMap::new();
+            if product_type == "locked" {
+                param_map.insert("positionId".to_string(), id.to_string());
+            } else {
+                param_map.insert("productId".to_string(), id.to_string());
+                if amount.is_empty() {
+                    param_map.insert("redeemAll".to_string(), "true".to_string());
+                } else {
+                    param_map.insert("amount".to_string(), amount.to_string());
+                }
+            }
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "POST", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+
+        pub async fn get_positions(
+            &self,
+            product_type: &str,
+            asset: &str,
+        ) -> Result<serde_json::Value, Box<dyn Error>> {
+            let url = self.generate_earn_url(product_type, "/position");
+            let mut param_map = std::collections::HashMap::new();
+            if !asset.is_empty() {
+                param_map.insert("asset".to_string(), asset.to_string());
+            }
+            param_map.insert("size".to_string(), "100".to_string());
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+
+        pub async fn get_history(
+            &self,
+            product_type: &str,
+            record_type: &str,
+            start_time: i64,
+            end_time: i64,
+        ) -> Result<serde_json::Value, Box<dyn Error>> {
+            let record_path = match record_type {
+                "subscription" => "/history/subscriptionRecord",
+                "redemption" => "/history/redemptionRecord",
+                _ => BinanceAPI::panic_not_define("Simple Earn record type", record_type, ""),
+            };
+            let url = self.generate_earn_url(product_type, record_path);
+            let mut param_map = std::collections::HashMap::new();
+            param_map.insert("startTime".to_string(), start_time.to_string());
+            param_map.insert("endTime".to_string(), end_time.to_string());
+            param_map.insert("size".to_string(), "100".to_string());
+            let parsed = self
+                .bn_api
+                .send_request(url.as_str(), "GET", &mut param_map, true)
+                .await?;
+            BinanceAPI::parse_response(parsed)
+        }
+    }
+}
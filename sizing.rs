@@ -0,0 +1,174 @@
+pub mod sizing {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::models::models::SymbolFilters;
+    use rust_decimal::Decimal;
+    use std::error::Error;
+
+    // Fixed-fractional sizing: the quantity that loses `risk_percent` of `equity` if the price
+    // moves from `entry` to `stop`, capped so the notional stays within `equity * leverage`
+    // and rounded down to the lot size. Errors when the result is below the symbol's minimums.
+    pub fn fixed_fractional_qty(
+        equity: Decimal,
+        risk_percent: Decimal,
+        entry: Decimal,
+        stop: Decimal,
+        leverage: u32,
+        filters: &SymbolFilters,
+    ) -> Result<Decimal, Box<dyn Error>> {
+        if leverage == 0 {
+            return Err("Leverage must be at least 1.".into());
+        }
+        if entry <= Decimal::ZERO {
+            return Err(format!("Entry `{entry}` must be positive.").into());
+        }
+        let stop_distance = (entry - stop).abs();
+        if stop_distance.is_zero() {
+            return Err(format!("Stop `{stop}` must differ from entry `{entry}`.").into());
+        }
+        let risk = equity * risk_percent / Decimal::from(100);
+        let max_qty = equity * Decimal::from(leverage) / entry;
+        let qty = filters.round_qty((risk / stop_distance).min(max_qty));
+        let qty = if filters.max_qty.is_zero() {
+            qty
+        } else {
+            qty.min(filters.max_qty)
+        };
+        if qty.is_zero() || qty < filters.min_qty {
+            return Err(format!(
+                "Quantity `{qty}` for {} is below the minimum `{}`.",
+                filters.symbol, filters.min_qty
+            )
+            .into());
+        }
+        if qty * entry < filters.min_notional {
+            return Err(format!(
+                "Notional `{}` for {} is below the minimum `{}`.",
+                qty * entry,
+                filters.symbol,
+                filters.min_notional
+            )
+            .into());
+        }
+        Ok(qty)
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Balance of `asset`: free plus locked on spot, the wallet balance on futures.
+        pub async fn account_equity(&self, asset: &str) -> Result<Decimal, Box<dyn Error>> {
+            if let Some(account) = self.paper_account() {
+                return Ok(account.balance(asset));
+            }
+            Ok(self
+                .get_balance()
+                .await?
+                .iter()
+                .find(|a| a.asset == asset)
+                .map(|a| a.balance)
+                .unwrap_or_default())
+        }
+
+        // Sizes an order on `symbol` from the equity in its quote asset, see
+        // `fixed_fractional_qty`. Spot is always sized with a leverage of 1.
+        pub async fn size_position(
+            &self,
+            symbol: &str,
+            risk_percent: Decimal,
+            entry: Decimal,
+            stop: Decimal,
+            leverage: u32,
+        ) -> Result<Decimal, Box<dyn Error>> {
+            let info = self.get_symbol_info(symbol).await?;
+            let filters = self.get_symbol_filters(symbol).await?;
+            let equity = self.account_equity(&info.quote_asset).await?;
+            let leverage = if self.account_type == "spot" {
+                1
+            } else {
+                leverage
+            };
+            fixed_fractional_qty(equity, risk_percent, entry, stop, leverage, &filters)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn filters(step_size: Decimal, min_qty: Decimal, min_notional: Decimal) -> SymbolFilters {
+            SymbolFilters {
+                symbol: "BTCUSDT".to_string(),
+                step_size: step_size,
+                min_qty: min_qty,
+                min_notional: min_notional,
+                ..Default::default()
+            }
+        }
+
+        fn size(
+            equity: i64,
+            risk_percent: i64,
+            stop: i64,
+            leverage: u32,
+            filters: &SymbolFilters,
+        ) -> Result<Decimal, Box<dyn Error>> {
+            fixed_fractional_qty(
+                Decimal::from(equity),
+                Decimal::from(risk_percent),
+                Decimal::from(100),
+                Decimal::from(stop),
+                leverage,
+                filters,
+            )
+        }
+
+        #[test]
+        fn quantity_loses_the_risked_amount_at_the_stop() {
+            let filters = filters(Decimal::new(1, 3), Decimal::ZERO, Decimal::ZERO);
+            // 1% of 10000 over a 5 stop distance.
+            assert_eq!(size(10000, 1, 95, 1, &filters).unwrap(), Decimal::from(20));
+            assert_eq!(size(10000, 1, 105, 1, &filters).unwrap(), Decimal::from(20));
+        }
+
+        #[test]
+        fn quantity_is_capped_by_the_leverage() {
+            let filters = filters(Decimal::new(1, 3), Decimal::ZERO, Decimal::ZERO);
+            // 10% of 1000 over 1 is 100, but 2x of 1000 only buys 20 at 100.
+            assert_eq!(size(1000, 10, 99, 2, &filters).unwrap(), Decimal::from(20));
+        }
+
+        #[test]
+        fn quantity_is_rounded_down_to_the_lot_size() {
+            let filters = filters(Decimal::new(1, 2), Decimal::ZERO, Decimal::ZERO);
+            assert_eq!(
+                size(1000, 1, 97, 1, &filters).unwrap(),
+                Decimal::new(333, 2)
+            );
+        }
+
+        #[test]
+        fn quantity_below_the_minimums_is_an_error() {
+            let min_qty = filters(Decimal::new(1, 2), Decimal::ONE, Decimal::ZERO);
+            let error = size(50, 1, 99, 1, &min_qty).unwrap_err();
+            assert!(error.to_string().starts_with("Quantity `0.5`"));
+            let min_notional = filters(Decimal::new(1, 2), Decimal::ZERO, Decimal::from(100));
+            let error = size(50, 1, 99, 1, &min_notional).unwrap_err();
+            assert!(error.to_string().starts_with("Notional `50"));
+        }
+
+        #[test]
+        fn bad_inputs_are_errors() {
+            let filters = filters(Decimal::new(1, 3), Decimal::ZERO, Decimal::ZERO);
+            assert!(size(1000, 1, 95, 0, &filters).is_err());
+            assert!(size(1000, 1, 100, 1, &filters).is_err());
+            let error = fixed_fractional_qty(
+                Decimal::from(1000),
+                Decimal::ONE,
+                Decimal::ZERO,
+                Decimal::from(-5),
+                1,
+                &filters,
+            )
+            .unwrap_err();
+            assert_eq!(error.to_string(), "Entry `0` must be positive.");
+        }
+    }
+}
@@ -0,0 +1,339 @@
+pub mod strategy {
+    use crate::binance_api::binance_api::{BinanceAPI, BinanceError};
+    use crate::execution::execution::decimal_field;
+    use crate::models::models::{NewOrder, OrderUpdate};
+    #[cfg(feature = "websocket")]
+    use crate::stream::stream::UserDataEvent;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use serde_json::Value;
+    use std::{collections::HashMap, error::Error};
+    #[cfg(feature = "websocket")]
+    use tokio::sync::mpsc;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct GridOrder {
+        pub level: usize,
+        pub side: String,
+        pub price: Decimal,
+        pub order_id: i64,
+    }
+
+    // `grid_count` equal steps between `lower` and `upper`, i.e. `grid_count + 1` price levels.
+    // Buys sit below the current price and sells above it; the level closest to the price is left
+    // empty so every filled buy at level `i` can be answered with a sell at `i + 1` and vice versa.
+    #[derive(Debug, Clone)]
+    pub struct GridStrategy {
+        pub symbol: String,
+        pub lower: Decimal,
+        pub upper: Decimal,
+        pub grid_count: u32,
+        pub order_qty: Decimal,
+        client_id_prefix: String,
+        next_id: u64,
+        // Open grid orders by client order id.
+        orders: HashMap<String, GridOrder>,
+    }
+
+    impl GridStrategy {
+        pub fn new(
+            symbol: &str,
+            lower: Decimal,
+            upper: Decimal,
+            grid_count: u32,
+            order_qty: Decimal,
+        ) -> Self {
+            assert!(
+                lower < upper,
+                "grid lower bound must be below the upper bound."
+            );
+            assert!(grid_count > 0, "a grid needs at least 1 step.");
+            GridStrategy {
+                symbol: symbol.to_string(),
+                lower: lower,
+                upper: upper,
+                grid_count: grid_count,
+                order_qty: order_qty,
+                client_id_prefix: format!("grid_{}", Utc::now().timestamp_millis()),
+                next_id: 0,
+                orders: HashMap::new(),
+            }
+        }
+
+        // Grid orders are sent as `<prefix>_<n>`, which is how stream updates are matched.
+        pub fn client_id_prefix(mut self, client_id_prefix: &str) -> Self {
+            self.client_id_prefix = client_id_prefix.to_string();
+            self
+        }
+
+        pub fn levels(&self) -> Vec<Decimal> {
+            let step = (self.upper - self.lower) / Decimal::from(self.grid_count);
+            (0..=self.grid_count)
+                .map(|a| self.lower + step * Decimal::from(a))
+                .collect()
+        }
+
+        pub fn open_orders(&self) -> &HashMap<String, GridOrder> {
+            &self.orders
+        }
+
+        // The initial ladder around `price` as (level, side) pairs.
+        pub fn initial_ladder(&self, price: Decimal) -> Vec<(usize, &'static str)> {
+            let levels = self.levels();
+            let skip = (0..levels.len())
+                .min_by_key(|a| (levels[*a] - price).abs())
+                .unwrap_or_default();
+            (0..levels.len())
+                .filter(|a| *a != skip)
+                .map(|a| (a, if levels[a] < price { "BUY" } else { "SELL" }))
+                .collect()
+        }
+
+        // The order that answers a filled grid order: one level up after a buy, one down after a
+        // sell. None at the edges of the grid.
+        pub fn opposite(&self, filled: &GridOrder) -> Option<(usize, &'static str)> {
+            match filled.side.as_str() {
+                "BUY" if filled.level < self.grid_count as usize => {
+                    Some((filled.level + 1, "SELL"))
+                }
+                "SELL" if filled.level > 0 => Some((filled.level - 1, "BUY")),
+                _ => None,
+            }
+        }
+
+        // Removes the grid order an update finishes and returns it when it was filled; partial
+        // fills and updates for other orders return None.
+        pub fn apply_update(&mut self, update: &OrderUpdate) -> Option<GridOrder> {
+            if update.symbol != self.symbol || !self.orders.contains_key(&update.client_order_id) {
+                return None;
+            }
+            match update.status.as_str() {
+                "FILLED" => self.orders.remove(&update.client_order_id),
+                "CANCELED" | "EXPIRED" | "REJECTED" | "EXPIRED_IN_MATCH" => {
+                    self.orders.remove(&update.client_order_id);
+                    None
+                }
+                _ => None,
+            }
+        }
+
+        fn next_client_order_id(&mut self) -> String {
+            self.next_id += 1;
+            format!("{}_{}", self.client_id_prefix, self.next_id)
+        }
+    }
+
+    // What `stop_grid` canceled; the orders in `errors` are still in the grid.
+    #[derive(Debug, Clone, Default)]
+    pub struct StopGridReport {
+        pub responses: Vec<Value>,
+        pub errors: Vec<String>,
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        async fn place_grid_order(
+            &self,
+            grid: &mut GridStrategy,
+            level: usize,
+            side: &str,
+        ) -> Result<Value, Box<dyn Error>> {
+            let price = grid.levels()[level];
+            let client_order_id = grid.next_client_order_id();
+            let order = NewOrder::new(&grid.symbol, side, "LIMIT")
+                .quantity(grid.order_qty)
+                .price(price)
+                .time_in_force("GTC")
+                .client_order_id(&client_order_id)
+                .auto_round(true);
+            // Only an order the exchange took is tracked; one without an id could never be
+            // canceled by `stop_grid`.
+            let response = self.place_order(&order).await?;
+            let order_id = response["orderId"]
+                .as_i64()
+                .ok_or_else(|| format!("No orderId in `{response}`."))?;
+            grid.orders.insert(
+                client_order_id,
+                GridOrder {
+                    level: level,
+                    side: side.to_string(),
+                    price: price,
+                    order_id: order_id,
+                },
+            );
+            Ok(response)
+        }
+
+        // Places the initial ladder of GTC limit orders around the last price.
+        pub async fn start_grid(
+            &self,
+            grid: &mut GridStrategy,
+        ) -> Result<Vec<Value>, Box<dyn Error>> {
+            let price = self.get_price(&grid.symbol).await?;
+            let price = decimal_field(&price, "price").ok_or_else(|| {
+                format!("Can't get the price of `{}` from `{price}`.", grid.symbol)
+            })?;
+            let mut responses = Vec::new();
+            for (level, side) in grid.initial_ladder(price) {
+                responses.push(self.place_grid_order(grid, level, side).await?);
+            }
+            Ok(responses)
+        }
+
+        // Feeds one execution update to the grid and places the opposite order when it completes
+        // a grid order; returns that order's response.
+        pub async fn handle_grid_update(
+            &self,
+            grid: &mut GridStrategy,
+            update: &OrderUpdate,
+        ) -> Result<Option<Value>, Box<dyn Error>> {
+            let filled = match grid.apply_update(update) {
+                Some(filled) => filled,
+                None => return Ok(None),
+            };
+            match grid.opposite(&filled) {
+                Some((level, side)) => Ok(Some(self.place_grid_order(grid, level, side).await?)),
+                None => Ok(None),
+            }
+        }
+
+        // Cancels every open grid order. An order is only dropped from the grid once it is
+        // canceled, or already gone, so calling it again retries the ones in `errors`.
+        pub async fn stop_grid(
+            &self,
+            grid: &mut GridStrategy,
+        ) -> Result<StopGridReport, Box<dyn Error>> {
+            let mut report = StopGridReport::default();
+            let mut client_order_ids: Vec<String> = grid.orders.keys().cloned().collect();
+            client_order_ids.sort();
+            for client_order_id in client_order_ids {
+                let order_id = grid.orders[&client_order_id].order_id.to_string();
+                let response = self.cancel_order(&grid.symbol, &order_id, false).await;
+                match response {
+                    Ok(response) => report.responses.push(response),
+                    Err(e) if e.downcast_ref::<BinanceError>().map(|a| a.code) == Some(-2011) => {}
+                    Err(e) => {
+                        report.errors.push(format!("cancel {order_id}: {e}"));
+                        continue;
+                    }
+                }
+                grid.orders.remove(&client_order_id);
+            }
+            Ok(report)
+        }
+
+        // Keeps the grid running from `stream_user_data` until the channel closes. Fills missed
+        // during a stream gap are not replayed; check `open_orders` against the exchange then.
+        #[cfg(feature = "websocket")]
+        pub async fn run_grid(
+            &self,
+            grid: &mut GridStrategy,
+            user_data: &mut mpsc::Receiver<UserDataEvent>,
+        ) -> Result<(), Box<dyn Error>> {
+            while let Some(event) = user_data.recv().await {
+                if let UserDataEvent::Event(event) = event {
+                    if let Some(update) = OrderUpdate::from_event(&event) {
+                        self.handle_grid_update(grid, &update).await?;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn grid() -> GridStrategy {
+            GridStrategy::new(
+                "BTCUSDT",
+                Decimal::from(100),
+                Decimal::from(200),
+                4,
+                Decimal::ONE,
+            )
+        }
+
+        fn order(level: usize, side: &str) -> GridOrder {
+            GridOrder {
+                level: level,
+                side: side.to_string(),
+                price: Decimal::ZERO,
+                order_id: level as i64,
+            }
+        }
+
+        fn update(symbol: &str, client_order_id: &str, status: &str) -> OrderUpdate {
+            OrderUpdate {
+                event_time: 0,
+                symbol: symbol.to_string(),
+                client_order_id: client_order_id.to_string(),
+                side: "BUY".to_string(),
+                order_type: "LIMIT".to_string(),
+                execution_type: "TRADE".to_string(),
+                status: status.to_string(),
+                order_id: 1,
+                price: Decimal::from(125),
+                qty: Decimal::ONE,
+                last_filled_qty: Decimal::ZERO,
+                cum_filled_qty: Decimal::ZERO,
+                last_filled_price: Decimal::ZERO,
+            }
+        }
+
+        #[test]
+        fn ladder_skips_the_level_nearest_the_price() {
+            let grid = grid();
+            assert_eq!(
+                grid.levels(),
+                [100, 125, 150, 175, 200].map(Decimal::from).to_vec()
+            );
+            assert_eq!(
+                grid.initial_ladder(Decimal::from(160)),
+                vec![(0, "BUY"), (1, "BUY"), (3, "SELL"), (4, "SELL")]
+            );
+            assert_eq!(
+                grid.initial_ladder(Decimal::from(130)),
+                vec![(0, "BUY"), (2, "SELL"), (3, "SELL"), (4, "SELL")]
+            );
+        }
+
+        #[test]
+        fn opposite_order_stays_inside_the_grid() {
+            let grid = grid();
+            assert_eq!(grid.opposite(&order(0, "BUY")), Some((1, "SELL")));
+            assert_eq!(grid.opposite(&order(4, "SELL")), Some((3, "BUY")));
+            assert_eq!(grid.opposite(&order(4, "BUY")), None);
+            assert_eq!(grid.opposite(&order(0, "SELL")), None);
+        }
+
+        #[test]
+        fn only_fills_return_the_order_and_cancels_drop_it() {
+            let mut grid = grid();
+            grid.orders.insert("grid_1".to_string(), order(1, "BUY"));
+            grid.orders.insert("grid_2".to_string(), order(3, "SELL"));
+            assert_eq!(
+                grid.apply_update(&update("BTCUSDT", "grid_1", "PARTIALLY_FILLED")),
+                None
+            );
+            assert!(grid.open_orders().contains_key("grid_1"));
+            assert_eq!(
+                grid.apply_update(&update("ETHUSDT", "grid_1", "FILLED")),
+                None
+            );
+            assert_eq!(
+                grid.apply_update(&update("BTCUSDT", "other", "FILLED")),
+                None
+            );
+            assert_eq!(
+                grid.apply_update(&update("BTCUSDT", "grid_1", "FILLED")),
+                Some(order(1, "BUY"))
+            );
+            assert_eq!(
+                grid.apply_update(&update("BTCUSDT", "grid_2", "CANCELED")),
+                None
+            );
+            assert!(grid.open_orders().is_empty());
+        }
+    }
+}
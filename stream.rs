@@ -0,0 +1,946 @@
+pub mod stream {
+    use crate::bar_builder::bar_builder::BarBuilder;
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::binance_api::binance_api::BinanceError;
+    use crate::builder::builder::TimeUnit;
+    #[cfg(feature = "metrics")]
+    use crate::metrics::metrics;
+    use crate::models::models::{
+        AggTrade, DepthEvent, Kline, KlineEvent, LiquidationEvent, MarginCallEvent, MarkPriceEvent,
+        OrderUpdate, PartialDepth, TradeEvent,
+    };
+    use crate::recorder::recorder::StreamRecorder;
+    use crate::transport::transport::{HttpRequest, Transport};
+    use crate::ws_deflate::ws_deflate::{connect_stream, TcpSocket};
+    use crate::ws_health::ws_health::StreamHealth;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use std::{
+        collections::{HashMap, VecDeque},
+        error::Error,
+        io::{ErrorKind, Read, Write},
+        net::{SocketAddr, TcpStream},
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+    use tokio::{runtime::Handle, sync::mpsc};
+    use tungstenite::{protocol::WebSocket, stream::MaybeTlsStream, Message};
+
+    // How often a blocked read wakes up to look at the stop flag.
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+    // How often a send into a full channel is tried again.
+    const SEND_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+    fn set_read_timeout<S: TcpSocket>(ws: &mut WebSocket<S>, timeout: Option<Duration>) {
+        if let Some(stream) = ws.get_ref().tcp() {
+            let _ = stream.set_read_timeout(timeout);
+        }
+    }
+
+    fn with_time_unit(url: String, time_unit: TimeUnit) -> String {
+        match time_unit {
+            TimeUnit::Millisecond => url,
+            _ if url.contains('?') => format!("{}&timeUnit={}", url, time_unit.as_str()),
+            _ => format!("{}?timeUnit={}", url, time_unit.as_str()),
+        }
+    }
+
+    // Sends a Close frame and reads until the server answers it, so the connection ends cleanly.
+    pub(crate) fn close_gracefully<S: TcpSocket + Read + Write>(ws: &mut WebSocket<S>) {
+        set_read_timeout(ws, Some(POLL_INTERVAL));
+        if ws.close(None).is_err() {
+            return;
+        }
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            match ws.read() {
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if [ErrorKind::WouldBlock, ErrorKind::TimedOut].contains(&e.kind()) => {}
+                Err(_) => break,
+            }
+        }
+    }
+
+    pub(crate) enum StreamMessage {
+        Text(String),
+        Error(String),
+        Reconnected,
+        // Nothing was read for `POLL_INTERVAL`.
+        Idle,
+    }
+
+    // Callbacks for `run_handler`; every method defaults to doing nothing.
+    pub trait EventHandler: Send {
+        fn on_trade(&mut self, _trade: TradeEvent) {}
+        fn on_kline(&mut self, _kline: KlineEvent) {}
+        fn on_depth(&mut self, _depth: DepthEvent) {}
+        fn on_partial_depth(&mut self, _depth: PartialDepth) {}
+        fn on_order_update(&mut self, _update: OrderUpdate) {}
+        fn on_margin_call(&mut self, _margin_call: MarginCallEvent) {}
+        fn on_liquidation(&mut self, _liquidation: LiquidationEvent) {}
+        fn on_mark_price(&mut self, _mark_price: MarkPriceEvent) {}
+        fn on_error(&mut self, _error: String) {}
+        fn on_reconnect(&mut self) {}
+    }
+
+    pub(crate) fn dispatch(
+        handler: &mut dyn EventHandler,
+        text: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let parsed: serde_json::Value = serde_json::from_str(text)?;
+        // Combined streams wrap the event as `{"stream": .., "data": ..}`.
+        let stream_name = parsed["stream"].as_str().unwrap_or_default().to_string();
+        let data = match parsed.get("data") {
+            Some(data) => data.clone(),
+            None => parsed,
+        };
+        // `!markPrice@arr` sends every symbol in one array.
+        if let Some(events) = data.as_array() {
+            for event in events {
+                if event["e"].as_str() == Some("markPriceUpdate") {
+                    handler.on_mark_price(serde_json::from_value(event.clone())?);
+                }
+            }
+            return Ok(());
+        }
+        match data["e"].as_str().unwrap_or_default() {
+            "trade" => handler.on_trade(serde_json::from_value(data)?),
+            "kline" => handler.on_kline(serde_json::from_value(data)?),
+            // Futures partial depth streams are also `depthUpdate`, told apart by the stream name.
+            "depthUpdate"
+                if stream_name.contains("@depth5")
+                    || stream_name.contains("@depth10")
+                    || stream_name.contains("@depth20") =>
+            {
+                handler.on_partial_depth(serde_json::from_value(data)?)
+            }
+            "depthUpdate" => handler.on_depth(serde_json::from_value(data)?),
+            "" if data.get("lastUpdateId").is_some() => {
+                handler.on_partial_depth(serde_json::from_value(data)?)
+            }
+            "executionReport" | "ORDER_TRADE_UPDATE" => match OrderUpdate::from_event(&data) {
+                Some(update) => handler.on_order_update(update),
+                None => return Err(format!("Can't parse order update `{data}`.").into()),
+            },
+            "MARGIN_CALL" => handler.on_margin_call(serde_json::from_value(data)?),
+            "forceOrder" => handler.on_liquidation(serde_json::from_value(data)?),
+            "markPriceUpdate" => handler.on_mark_price(serde_json::from_value(data)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // How long a websocket request waits for the reply carrying its id.
+    const ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn local_addr(ws: &WebSocket<MaybeTlsStream<TcpStream>>) -> Option<SocketAddr> {
+        match ws.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.local_addr().ok(),
+            #[cfg(feature = "native-tls")]
+            MaybeTlsStream::NativeTls(stream) => stream.get_ref().local_addr().ok(),
+            _ => None,
+        }
+    }
+
+    // Token bucket of the messages sent on one connection.
+    #[derive(Debug)]
+    struct MessageBucket {
+        tokens: f64,
+        updated: Instant,
+    }
+
+    #[derive(Debug, Default)]
+    pub(crate) struct WsRequests {
+        next_id: AtomicU64,
+        // Stream data read while waiting for a reply, handed out by `websocket_read_once` first.
+        pub(crate) backlog: Mutex<VecDeque<String>>,
+        // Per connection, by local address.
+        buckets: Mutex<HashMap<Option<SocketAddr>, MessageBucket>>,
+    }
+
+    impl WsRequests {
+        // Waits until another message may go out on `ws` without passing `per_second`; Binance
+        // drops connections that send more.
+        fn throttle(&self, ws: &WebSocket<MaybeTlsStream<TcpStream>>, per_second: f64) {
+            let addr = local_addr(ws);
+            loop {
+                let wait = {
+                    let mut buckets = self.buckets.lock().unwrap();
+                    buckets.retain(|_, a| a.updated.elapsed() < Duration::from_secs(60));
+                    let bucket = buckets.entry(addr).or_insert(MessageBucket {
+                        tokens: per_second,
+                        updated: Instant::now(),
+                    });
+                    let refill = bucket.updated.elapsed().as_secs_f64() * per_second;
+                    bucket.tokens = (bucket.tokens + refill).min(per_second);
+                    bucket.updated = Instant::now();
+                    if bucket.tokens >= 1.0 {
+                        bucket.tokens -= 1.0;
+                        return;
+                    }
+                    Duration::from_secs_f64((1.0 - bucket.tokens) / per_second)
+                };
+                tracing::debug!(
+                    wait_ms = wait.as_millis() as u64,
+                    "websocket message rate, waiting"
+                );
+                thread::sleep(wait);
+            }
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub(crate) struct StreamTasks {
+        stop: Arc<AtomicBool>,
+        handles: Mutex<Vec<thread::JoinHandle<()>>>,
+        // Set once a user stream had to replace an expired listen key, or by `rotate_keys`.
+        renewed_listen_key: Arc<Mutex<String>>,
+        // The api key set by `rotate_keys`, empty until then.
+        rotated_api_key: Arc<Mutex<String>>,
+        // Bumped by `rotate_keys` so running user streams reconnect with the new listen key.
+        key_generation: Arc<AtomicU64>,
+        // Set by `record_streams`.
+        pub(crate) recorder: Arc<Mutex<Option<Arc<StreamRecorder>>>>,
+        // What `stream_health` reports and the watchdog set by `set_stream_watchdog`.
+        pub(crate) health: Arc<StreamHealth>,
+        // Ask for permessage-deflate, set by the builder's `ws_compression`.
+        pub(crate) compression: bool,
+    }
+
+    // What a stream thread sends into: waits while the channel is full like `blocking_send`,
+    // but gives up once the client shuts down, so a reader that stopped draining can't hang
+    // `shutdown`.
+    pub(crate) struct StreamSender<T> {
+        tx: mpsc::Sender<T>,
+        stop: Arc<AtomicBool>,
+    }
+
+    impl<T> StreamSender<T> {
+        // False once the receiver is gone or the client is shutting down.
+        pub(crate) fn send(&self, mut value: T) -> bool {
+            loop {
+                match self.tx.try_send(value) {
+                    Ok(()) => return true,
+                    Err(mpsc::error::TrySendError::Closed(_)) => return false,
+                    Err(mpsc::error::TrySendError::Full(rejected)) => {
+                        if self.stop.load(Ordering::SeqCst) {
+                            return false;
+                        }
+                        value = rejected;
+                        thread::sleep(SEND_RETRY_INTERVAL);
+                    }
+                }
+            }
+        }
+
+        pub(crate) fn is_closed(&self) -> bool {
+            self.tx.is_closed()
+        }
+    }
+
+    pub enum UserDataEvent {
+        Event(serde_json::Value),
+        // The stream was down from `since` (ms) until now, so events may be missing; reconcile
+        // orders and balances over REST.
+        Gap {
+            since: i64,
+            listen_key_expired: bool,
+        },
+    }
+
+    fn renew_listen_key(
+        transport: &Arc<dyn Transport>,
+        runtime: &Option<Handle>,
+        url: &str,
+        api_key: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let request = HttpRequest {
+            method: "POST".to_string(),
+            url: url.to_string(),
+            headers: vec![("X-MBX-APIKEY".to_string(), api_key.to_string())],
+            query: vec![],
+        };
+        let res = match runtime {
+            Some(runtime) => runtime.block_on(transport.send(request)),
+            None => futures::executor::block_on(transport.send(request)),
+        }?;
+        let parsed: serde_json::Value = serde_json::from_str(&res.body)?;
+        match parsed["listenKey"].as_str() {
+            Some(listen_key) => Ok(listen_key.to_string()),
+            None => Err(format!("Can't get `listenKey` from `{}`.", res.body).into()),
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub(crate) enum Flow {
+        Continue,
+        // Close the connection and open it again, asking for the url anew.
+        Reconnect,
+        Stop,
+    }
+
+    impl From<bool> for Flow {
+        fn from(keep_going: bool) -> Self {
+            if keep_going {
+                Flow::Continue
+            } else {
+                Flow::Stop
+            }
+        }
+    }
+
+    impl StreamTasks {
+        pub(crate) fn new(compression: bool) -> Self {
+            StreamTasks {
+                compression: compression,
+                ..Default::default()
+            }
+        }
+
+        pub(crate) fn channel<T>(&self, capacity: usize) -> (StreamSender<T>, mpsc::Receiver<T>) {
+            let (tx, rx) = mpsc::channel(capacity);
+            let tx = StreamSender {
+                tx: tx,
+                stop: self.stop.clone(),
+            };
+            (tx, rx)
+        }
+
+        // Hands the keys set by `rotate_keys` to running user streams.
+        pub(crate) fn rotate(&self, api_key: &str, listen_key: Option<String>) {
+            *self.rotated_api_key.lock().unwrap() = api_key.to_string();
+            if let Some(listen_key) = listen_key {
+                *self.renewed_listen_key.lock().unwrap() = listen_key;
+                self.key_generation.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        // Reads `url` on its own thread, reconnecting with backoff, until `on_message` returns false
+        // or the client shuts down.
+        pub(crate) fn spawn<F, R>(&self, url: String, on_message: F)
+        where
+            F: FnMut(StreamMessage) -> R + Send + 'static,
+            R: Into<Flow>,
+        {
+            self.spawn_with(move || url.clone(), on_message)
+        }
+
+        // Like `spawn`, with the url looked up again before every connect.
+        pub(crate) fn spawn_with<U, F, R>(&self, mut url: U, mut on_message: F)
+        where
+            U: FnMut() -> String + Send + 'static,
+            F: FnMut(StreamMessage) -> R + Send + 'static,
+            R: Into<Flow>,
+        {
+            let stop = self.stop.clone();
+            let recorder = self.recorder.clone();
+            let monitor = self.health.register();
+            let compression = self.compression;
+            let handle = thread::spawn(move || {
+                let mut backoff = 1;
+                let mut connected_once = false;
+                while !stop.load(Ordering::SeqCst) {
+                    let url = url();
+                    match connect_stream(&url, compression) {
+                        Ok(mut ws) => {
+                            let compressed = ws.get_ref().is_compressed();
+                            tracing::info!(url = %url, compressed = compressed, "stream connected");
+                            set_read_timeout(&mut ws, Some(POLL_INTERVAL));
+                            backoff = 1;
+                            monitor.on_connect(&url);
+                            let mut flow = Flow::Continue;
+                            if connected_once {
+                                flow = on_message(StreamMessage::Reconnected).into();
+                            }
+                            connected_once = true;
+                            while flow == Flow::Continue {
+                                if stop.load(Ordering::SeqCst) {
+                                    flow = Flow::Stop;
+                                    break;
+                                }
+                                if monitor.is_stale() {
+                                    flow = Flow::Reconnect;
+                                    break;
+                                }
+                                if let Some(payload) = monitor.ping_due() {
+                                    let _ = ws.send(Message::Ping(payload));
+                                }
+                                match ws.read() {
+                                    Ok(Message::Text(text)) => {
+                                        #[cfg(feature = "metrics")]
+                                        metrics::record_ws_message(&text);
+                                        monitor.on_text(&text);
+                                        if let Some(recorder) = &*recorder.lock().unwrap() {
+                                            recorder.record(&url, &text);
+                                        }
+                                        flow = on_message(StreamMessage::Text(text)).into();
+                                    }
+                                    Ok(Message::Pong(payload)) => monitor.on_pong(&payload),
+                                    Ok(Message::Close(_)) => break,
+                                    Ok(_) => {}
+                                    Err(tungstenite::Error::Io(e))
+                                        if [ErrorKind::WouldBlock, ErrorKind::TimedOut]
+                                            .contains(&e.kind()) =>
+                                    {
+                                        flow = on_message(StreamMessage::Idle).into();
+                                    }
+                                    Err(e) => {
+                                        tracing::warn!(url = %url, error = %e, "stream read failed");
+                                        if on_message(StreamMessage::Error(e.to_string())).into()
+                                            == Flow::Stop
+                                        {
+                                            return;
+                                        }
+                                        break;
+                                    }
+                                }
+                            }
+                            monitor.on_disconnect();
+                            match flow {
+                                Flow::Stop => {
+                                    close_gracefully(&mut ws);
+                                    return;
+                                }
+                                Flow::Reconnect => close_gracefully(&mut ws),
+                                Flow::Continue => {}
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!(url = %url, error = %e, "stream connect failed");
+                            if on_message(StreamMessage::Error(e.to_string())).into() == Flow::Stop
+                            {
+                                return;
+                            }
+                        }
+                    }
+                    #[cfg(feature = "metrics")]
+                    metrics::record_ws_reconnect(&url);
+                    let started = Instant::now();
+                    while started.elapsed() < Duration::from_secs(backoff)
+                        && !stop.load(Ordering::SeqCst)
+                    {
+                        thread::sleep(Duration::from_millis(100));
+                    }
+                    backoff = (backoff * 2).min(60);
+                }
+            });
+            let mut handles = self.handles.lock().unwrap();
+            handles.retain(|a| !a.is_finished());
+            handles.push(handle);
+        }
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        pub(crate) fn stream_url(&self, stream: &str) -> String {
+            self.with_time_unit(format!("{}/{}", self.wss_url, stream))
+        }
+
+        pub(crate) fn with_time_unit(&self, url: String) -> String {
+            with_time_unit(url, self.time_unit)
+        }
+
+        pub fn current_listen_key(&self) -> String {
+            let renewed = self.streams.renewed_listen_key.lock().unwrap();
+            if renewed.is_empty() {
+                self.listen_key.clone()
+            } else {
+                renewed.clone()
+            }
+        }
+
+        // The url of a user stream, reading the listen key set by `rotate_keys` before every
+        // connect.
+        fn user_stream_url(&self) -> impl FnMut() -> String + Send + 'static {
+            let listen_key = self.listen_key.clone();
+            let renewed = self.streams.renewed_listen_key.clone();
+            let wss_url = self.wss_url.to_string();
+            let time_unit = self.time_unit;
+            move || {
+                let renewed = renewed.lock().unwrap();
+                let listen_key = if renewed.is_empty() {
+                    &listen_key
+                } else {
+                    &*renewed
+                };
+                with_time_unit(format!("{}/{}", wss_url, listen_key), time_unit)
+            }
+        }
+
+        // The user data stream as JSON events; when Binance sends `listenKeyExpired` a new key is
+        // requested and the stream reopened, and every reopening is reported as a `Gap`. After
+        // `rotate_keys` the stream reopens with the new listen key the same way.
+        pub fn stream_user_data(&self) -> mpsc::Receiver<UserDataEvent> {
+            let (tx, rx) = self.streams.channel(1024);
+            let listen_key = Arc::new(Mutex::new(self.current_listen_key()));
+            let expired = Arc::new(AtomicBool::new(false));
+            let renewed = self.streams.renewed_listen_key.clone();
+            let rotated_api_key = self.streams.rotated_api_key.clone();
+            let generation = self.streams.key_generation.clone();
+            let mut seen_generation = generation.load(Ordering::SeqCst);
+            let transport = self.transport.0.clone();
+            let runtime = Handle::try_current().ok();
+            let renew_url =
+                self.generate_exchange_url(("/api/v3/userDataStream", "/fapi/v1/listenKey"));
+            let api_key = self.api_keys().api_key.clone();
+            let wss_url = self.wss_url.to_string();
+            let time_unit = self.time_unit;
+            let url = {
+                let listen_key = listen_key.clone();
+                let expired = expired.clone();
+                move || {
+                    let mut listen_key = listen_key.lock().unwrap();
+                    let current = renewed.lock().unwrap().clone();
+                    if !current.is_empty() {
+                        *listen_key = current;
+                    }
+                    if expired.load(Ordering::SeqCst) {
+                        let rotated = rotated_api_key.lock().unwrap().clone();
+                        let api_key = if rotated.is_empty() {
+                            &api_key
+                        } else {
+                            &rotated
+                        };
+                        match renew_listen_key(&transport, &runtime, &renew_url, api_key) {
+                            Ok(new_key) => {
+                                tracing::info!("listen key renewed");
+                                *listen_key = new_key.clone();
+                                *renewed.lock().unwrap() = new_key;
+                                expired.store(false, Ordering::SeqCst);
+                            }
+                            Err(e) => tracing::error!(error = %e, "can't renew listen key"),
+                        }
+                    }
+                    with_time_unit(format!("{}/{}", wss_url, listen_key), time_unit)
+                }
+            };
+            let mut last_seen = Utc::now().timestamp_millis();
+            let mut key_expired = false;
+            self.streams.spawn_with(url, move |message| {
+                if generation.load(Ordering::SeqCst) != seen_generation {
+                    tracing::info!("api keys rotated, reopening user stream");
+                    seen_generation = generation.load(Ordering::SeqCst);
+                    return Flow::Reconnect;
+                }
+                match message {
+                    StreamMessage::Text(text) => {
+                        let parsed: serde_json::Value = match serde_json::from_str(&text) {
+                            Ok(parsed) => parsed,
+                            Err(e) => {
+                                tracing::warn!(error = %e, "can't parse user data event");
+                                return (!tx.is_closed()).into();
+                            }
+                        };
+                        last_seen = Utc::now().timestamp_millis();
+                        if parsed["e"] == "listenKeyExpired" {
+                            tracing::warn!("listen key expired");
+                            expired.store(true, Ordering::SeqCst);
+                            key_expired = true;
+                            return Flow::Reconnect;
+                        }
+                        tx.send(UserDataEvent::Event(parsed)).into()
+                    }
+                    StreamMessage::Reconnected => {
+                        let gap = UserDataEvent::Gap {
+                            since: last_seen,
+                            listen_key_expired: key_expired,
+                        };
+                        key_expired = false;
+                        tx.send(gap).into()
+                    }
+                    StreamMessage::Error(_) | StreamMessage::Idle => (!tx.is_closed()).into(),
+                }
+            });
+            rx
+        }
+
+        pub fn stream_klines(&self, symbol: &str, interval: &str) -> mpsc::Receiver<Kline> {
+            let (tx, rx) = self.streams.channel(1024);
+            let url = self.stream_url(&format!("{}@kline_{}", symbol.to_lowercase(), interval));
+            self.streams.spawn(url, move |message| {
+                let text = match message {
+                    StreamMessage::Text(text) => text,
+                    _ => return !tx.is_closed(),
+                };
+                match serde_json::from_str::<KlineEvent>(&text) {
+                    Ok(event) if event.kline.is_closed => tx.send(event.kline.into()),
+                    Ok(_) => !tx.is_closed(),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "can't parse kline event");
+                        !tx.is_closed()
+                    }
+                }
+            });
+            rx
+        }
+
+        // Messages a connection may send per second: 5 on spot, 10 on futures.
+        fn ws_message_rate(&self) -> f64 {
+            if self.account_type == "spot" {
+                5.0
+            } else {
+                10.0
+            }
+        }
+
+        // Sends `{"method", "params", "id"}` and waits for the reply with the same id; an
+        // `error` reply comes back as a `BinanceError`.
+        pub fn send_ws_request(
+            &self,
+            ws: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+            method: &str,
+            params: serde_json::Value,
+        ) -> Result<serde_json::Value, Box<dyn Error>> {
+            let id = self.ws_requests.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut request = serde_json::json!({"method": method, "id": id});
+            if !params.is_null() {
+                request["params"] = params;
+            }
+            tracing::debug!(method = method, id = id, "websocket request");
+            self.ws_requests.throttle(ws, self.ws_message_rate());
+            ws.send(Message::Text(request.to_string()))?;
+            set_read_timeout(ws, Some(ACK_TIMEOUT));
+            let started = Instant::now();
+            let res = loop {
+                if started.elapsed() > ACK_TIMEOUT {
+                    break Err(format!("No reply to websocket request `{method}` {id}.").into());
+                }
+                let text = match ws.read() {
+                    Ok(Message::Text(text)) => text,
+                    Ok(_) => continue,
+                    Err(e) => break Err(e.into()),
+                };
+                let parsed: serde_json::Value = match serde_json::from_str(&text) {
+                    Ok(parsed) => parsed,
+                    Err(_) => {
+                        self.ws_requests.backlog.lock().unwrap().push_back(text);
+                        continue;
+                    }
+                };
+                if parsed["id"].as_u64() != Some(id) {
+                    self.ws_requests.backlog.lock().unwrap().push_back(text);
+                    continue;
+                }
+                if parsed["error"].is_object() {
+                    break Err(Box::new(BinanceError {
+                        code: parsed["error"]["code"].as_i64().unwrap_or_default(),
+                        msg: parsed["error"]["msg"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    }) as Box<dyn Error>);
+                }
+                break Ok(parsed["result"].clone());
+            };
+            set_read_timeout(ws, None);
+            res
+        }
+
+        // Bars of any `duration` built from the aggTrade stream; see `BarBuilder`.
+        pub fn stream_bars(&self, symbol: &str, duration: Duration) -> mpsc::Receiver<Kline> {
+            let (tx, rx) = self.streams.channel(1024);
+            let url = self.stream_url(&format!("{}@aggTrade", symbol.to_lowercase()));
+            let mut builder = BarBuilder::new(duration);
+            self.streams.spawn(url, move |message| {
+                let text = match message {
+                    StreamMessage::Text(text) => text,
+                    _ => return !tx.is_closed(),
+                };
+                match serde_json::from_str::<AggTrade>(&text) {
+                    Ok(trade) => match builder.push(&trade) {
+                        Some(bar) => tx.send(bar),
+                        None => !tx.is_closed(),
+                    },
+                    Err(e) => {
+                        tracing::warn!(error = %e, "can't parse aggTrade event");
+                        !tx.is_closed()
+                    }
+                }
+            });
+            rx
+        }
+
+        pub fn stream_agg_trades(&self, symbol: &str) -> mpsc::Receiver<AggTrade> {
+            let (tx, rx) = self.streams.channel(1024);
+            let url = self.stream_url(&format!("{}@aggTrade", symbol.to_lowercase()));
+            self.streams.spawn(url, move |message| {
+                let text = match message {
+                    StreamMessage::Text(text) => text,
+                    _ => return !tx.is_closed(),
+                };
+                match serde_json::from_str::<AggTrade>(&text) {
+                    Ok(trade) => tx.send(trade),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "can't parse aggTrade event");
+                        !tx.is_closed()
+                    }
+                }
+            });
+            rx
+        }
+
+        // Last traded price from the aggTrade stream.
+        pub fn stream_last_price(&self, symbol: &str) -> mpsc::Receiver<Decimal> {
+            let (tx, rx) = self.streams.channel(1024);
+            let url = self.stream_url(&format!("{}@aggTrade", symbol.to_lowercase()));
+            self.streams.spawn(url, move |message| {
+                let text = match message {
+                    StreamMessage::Text(text) => text,
+                    _ => return !tx.is_closed(),
+                };
+                match serde_json::from_str::<AggTrade>(&text) {
+                    Ok(trade) => tx.send(trade.price),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "can't parse aggTrade event");
+                        !tx.is_closed()
+                    }
+                }
+            });
+            rx
+        }
+
+        pub fn stream_partial_depth(
+            &self,
+            symbol: &str,
+            levels: u32,
+            update_ms: u32,
+        ) -> mpsc::Receiver<PartialDepth> {
+            let (tx, rx) = mpsc::channel(1024);
+            let url = self.stream_url(&format!(
+                "{}@{}",
+                symbol.to_lowercase(),
+                self.partial_depth_type(levels, update_ms)
+            ));
+            self.streams.spawn(url, move |message| {
+                let text = match message {
+                    StreamMessage::Text(text) => text,
+                    _ => return !tx.is_closed(),
+                };
+                match serde_json::from_str::<PartialDepth>(&text) {
+                    // A slow reader misses snapshots instead of stalling the socket.
+                    Ok(depth) => match tx.try_send(depth) {
+                        Err(mpsc::error::TrySendError::Closed(_)) => false,
+                        _ => true,
+                    },
+                    Err(e) => {
+                        tracing::warn!(error = %e, "can't parse partial depth");
+                        !tx.is_closed()
+                    }
+                }
+            });
+            rx
+        }
+
+        // Diff depth events of `symbol` every `update_ms` (100 or 1000 on spot; 100, 250 or 500
+        // on futures). Unlike partial depth none is dropped, since a local book needs them all.
+        pub fn stream_depth_diffs(
+            &self,
+            symbol: &str,
+            update_ms: u32,
+        ) -> mpsc::Receiver<DepthEvent> {
+            let (tx, rx) = self.streams.channel(1024);
+            let url = self.stream_url(&format!("{}@depth@{}ms", symbol.to_lowercase(), update_ms));
+            self.streams.spawn(url, move |message| {
+                let text = match message {
+                    StreamMessage::Text(text) => text,
+                    _ => return !tx.is_closed(),
+                };
+                match serde_json::from_str::<DepthEvent>(&text) {
+                    Ok(depth) => tx.send(depth),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "can't parse depth update");
+                        !tx.is_closed()
+                    }
+                }
+            });
+            rx
+        }
+
+        // Liquidation orders of `symbol`, or of every symbol if `symbol` is "". The exchange
+        // sends at most one liquidation per symbol every 1000ms, the latest one.
+        #[cfg(feature = "usdm-futures")]
+        pub fn stream_liquidations(&self, symbol: &str) -> mpsc::Receiver<LiquidationEvent> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can stream liquidations."
+            );
+            let (tx, rx) = self.streams.channel(1024);
+            let stream = if symbol.is_empty() {
+                "!forceOrder@arr".to_string()
+            } else {
+                format!("{}@forceOrder", symbol.to_lowercase())
+            };
+            self.streams
+                .spawn(self.stream_url(&stream), move |message| {
+                    let text = match message {
+                        StreamMessage::Text(text) => text,
+                        _ => return !tx.is_closed(),
+                    };
+                    match serde_json::from_str::<LiquidationEvent>(&text) {
+                        Ok(event) => tx.send(event),
+                        Err(e) => {
+                            tracing::warn!(error = %e, "can't parse liquidation event");
+                            !tx.is_closed()
+                        }
+                    }
+                });
+            rx
+        }
+
+        // Mark price, index price and funding rate of `symbol` every second, or of every symbol
+        // if `symbol` is "".
+        #[cfg(feature = "usdm-futures")]
+        pub fn stream_mark_price(&self, symbol: &str) -> mpsc::Receiver<MarkPriceEvent> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can stream mark prices."
+            );
+            let (tx, rx) = self.streams.channel(1024);
+            let stream = if symbol.is_empty() {
+                "!markPrice@arr@1s".to_string()
+            } else {
+                format!("{}@markPrice@1s", symbol.to_lowercase())
+            };
+            self.streams
+                .spawn(self.stream_url(&stream), move |message| {
+                    let text = match message {
+                        StreamMessage::Text(text) => text,
+                        _ => return !tx.is_closed(),
+                    };
+                    let events = if text.starts_with('[') {
+                        serde_json::from_str::<Vec<MarkPriceEvent>>(&text)
+                    } else {
+                        serde_json::from_str::<MarkPriceEvent>(&text).map(|a| vec![a])
+                    };
+                    match events {
+                        Ok(events) => events.into_iter().all(|a| tx.send(a)),
+                        Err(e) => {
+                            tracing::warn!(error = %e, "can't parse mark price event");
+                            !tx.is_closed()
+                        }
+                    }
+                });
+            rx
+        }
+
+        // Margin calls from the futures user stream; needs the listen key.
+        #[cfg(feature = "usdm-futures")]
+        pub fn stream_margin_calls(&self) -> mpsc::Receiver<MarginCallEvent> {
+            assert!(
+                self.account_type == "swap",
+                "only `swap` can stream margin calls."
+            );
+            let (tx, rx) = self.streams.channel(64);
+            let generation = self.streams.key_generation.clone();
+            let mut seen_generation = generation.load(Ordering::SeqCst);
+            self.streams
+                .spawn_with(self.user_stream_url(), move |message| {
+                    if generation.load(Ordering::SeqCst) != seen_generation {
+                        seen_generation = generation.load(Ordering::SeqCst);
+                        return Flow::Reconnect;
+                    }
+                    let text = match message {
+                        StreamMessage::Text(text) => text,
+                        _ => return (!tx.is_closed()).into(),
+                    };
+                    if !text.contains(r#""e":"MARGIN_CALL""#) {
+                        return (!tx.is_closed()).into();
+                    }
+                    match serde_json::from_str::<MarginCallEvent>(&text) {
+                        Ok(event) => {
+                            tracing::warn!(positions = event.positions.len(), "margin call");
+                            tx.send(event).into()
+                        }
+                        Err(e) => {
+                            tracing::warn!(error = %e, "can't parse margin call event");
+                            (!tx.is_closed()).into()
+                        }
+                    }
+                });
+            rx
+        }
+
+        // Runs `handler` on a background thread over the combined `streams`, e.g. `btcusdt@trade`;
+        // pass the listen key as a stream name to get order updates.
+        pub fn run_handler(&self, streams: &Vec<&str>, mut handler: impl EventHandler + 'static) {
+            let url = self.with_time_unit(format!(
+                "{}/stream?streams={}",
+                self.wss_url.trim_end_matches("/ws"),
+                streams.join("/")
+            ));
+            self.streams.spawn(url, move |message| {
+                match message {
+                    StreamMessage::Text(text) => {
+                        if let Err(e) = dispatch(&mut handler, &text) {
+                            handler.on_error(e.to_string());
+                        }
+                    }
+                    StreamMessage::Error(e) => handler.on_error(e),
+                    StreamMessage::Reconnected => handler.on_reconnect(),
+                    StreamMessage::Idle => {}
+                }
+                true
+            });
+        }
+
+        pub fn close_websocket(&self, ws: &mut WebSocket<MaybeTlsStream<TcpStream>>) {
+            close_gracefully(ws);
+        }
+
+        // Stops every stream this client spawned and deletes the listen key; streams can't be
+        // started again afterwards.
+        pub async fn shutdown(&self) -> Result<(), Box<dyn Error>> {
+            self.streams.stop.store(true, Ordering::SeqCst);
+            let handles = std::mem::take(&mut *self.streams.handles.lock().unwrap());
+            while handles.iter().any(|a| !a.is_finished()) {
+                self.transport.0.sleep(Duration::from_millis(50)).await;
+            }
+            for handle in handles {
+                let _ = handle.join();
+            }
+            if !self.current_listen_key().is_empty() {
+                self.listen_key_manager("delete").await?;
+            }
+            tracing::info!("client shut down");
+            Ok(())
+        }
+    }
+
+    // The client defaults to spot, which needs the `spot` feature.
+    #[cfg(all(test, feature = "spot"))]
+    mod tests {
+        use super::*;
+        use crate::transport::transport::MockTransport;
+        use std::net::TcpListener;
+
+        #[tokio::test]
+        async fn shutdown_returns_while_a_receiver_is_full_and_unread() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            thread::spawn(move || {
+                let mut ws = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+                let trade =
+                    r#"{"e":"aggTrade","a":1,"p":"100.0","q":"1.0","f":1,"l":1,"T":1,"m":false}"#;
+                while ws.send(Message::Text(trade.to_string())).is_ok() {}
+            });
+            let bn_api = BinanceAPI::builder()
+                .keys("key", "secret")
+                .wss_url(format!("ws://{addr}/ws"))
+                .transport(Arc::new(MockTransport::new()))
+                .build()
+                .await
+                .unwrap();
+            let _trades = bn_api.stream_agg_trades("BTCUSDT");
+            // Long enough for the stream thread to fill the channel and wait on it.
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            tokio::time::timeout(Duration::from_secs(10), bn_api.shutdown())
+                .await
+                .expect("shutdown hung on the full channel")
+                .unwrap();
+        }
+    }
+}
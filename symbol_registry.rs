@@ -0,0 +1,146 @@
+pub mod symbol_registry {
+    use crate::models::models::SymbolFilters;
+    use std::{
+        collections::HashMap,
+        error::Error,
+        sync::Mutex,
+        time::{Duration, Instant},
+    };
+
+    #[derive(Debug, Clone)]
+    pub struct SymbolInfo {
+        pub symbol: String,
+        pub status: String,
+        pub base_asset: String,
+        pub quote_asset: String,
+        pub price_precision: u32,
+        pub quantity_precision: u32,
+        pub filters: SymbolFilters,
+    }
+
+    impl SymbolInfo {
+        pub fn from_exchange_info(symbol_info: &serde_json::Value) -> Result<Self, Box<dyn Error>> {
+            let text = |key: &str| symbol_info[key].as_str().unwrap_or_default().to_string();
+            let precision = |keys: [&str; 2]| {
+                keys.iter()
+                    .find_map(|a| symbol_info[*a].as_u64())
+                    .unwrap_or_default() as u32
+            };
+            Ok(SymbolInfo {
+                symbol: text("symbol"),
+                status: text("status"),
+                base_asset: text("baseAsset"),
+                quote_asset: text("quoteAsset"),
+                price_precision: precision(["pricePrecision", "quotePrecision"]),
+                quantity_precision: precision(["quantityPrecision", "baseAssetPrecision"]),
+                filters: SymbolFilters::from_exchange_info(symbol_info)?,
+            })
+        }
+
+        pub fn is_trading(&self) -> bool {
+            self.status == "TRADING"
+        }
+    }
+
+    #[derive(Debug)]
+    struct RegistryState {
+        ttl: Duration,
+        loaded_at: Option<Instant>,
+        symbols: HashMap<String, SymbolInfo>,
+    }
+
+    #[derive(Debug)]
+    pub struct SymbolRegistry {
+        state: Mutex<RegistryState>,
+    }
+
+    impl Default for SymbolRegistry {
+        fn default() -> Self {
+            SymbolRegistry::new(Duration::from_secs(3600))
+        }
+    }
+
+    impl SymbolRegistry {
+        pub fn new(ttl: Duration) -> Self {
+            SymbolRegistry {
+                state: Mutex::new(RegistryState {
+                    ttl: ttl,
+                    loaded_at: None,
+                    symbols: HashMap::new(),
+                }),
+            }
+        }
+
+        pub fn set_ttl(&self, ttl: Duration) {
+            self.state.lock().unwrap().ttl = ttl;
+        }
+
+        pub fn invalidate(&self) {
+            self.state.lock().unwrap().loaded_at = None;
+        }
+
+        pub fn is_fresh(&self) -> bool {
+            let state = self.state.lock().unwrap();
+            match state.loaded_at {
+                Some(loaded_at) => loaded_at.elapsed() < state.ttl,
+                None => false,
+            }
+        }
+
+        // An exchangeInfo without symbols, e.g. an error body, leaves the registry as it was.
+        pub fn load(&self, exchange_info: &serde_json::Value) -> Result<(), Box<dyn Error>> {
+            let mut symbols = HashMap::new();
+            for symbol_info in exchange_info["symbols"].as_array().into_iter().flatten() {
+                let info = SymbolInfo::from_exchange_info(symbol_info)?;
+                symbols.insert(info.symbol.clone(), info);
+            }
+            if symbols.is_empty() {
+                return Err(format!("exchangeInfo lists no symbols: `{exchange_info}`").into());
+            }
+            let mut state = self.state.lock().unwrap();
+            state.symbols = symbols;
+            state.loaded_at = Some(Instant::now());
+            Ok(())
+        }
+
+        pub fn get(&self, symbol: &str) -> Option<SymbolInfo> {
+            self.state.lock().unwrap().symbols.get(symbol).cloned()
+        }
+
+        pub fn symbols(&self) -> Vec<SymbolInfo> {
+            self.state
+                .lock()
+                .unwrap()
+                .symbols
+                .values()
+                .cloned()
+                .collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn load_indexes_symbols_and_marks_the_registry_fresh() {
+            let registry = SymbolRegistry::default();
+            registry
+                .load(&serde_json::json!({
+                    "symbols": [{"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT"}],
+                }))
+                .unwrap();
+            assert!(registry.is_fresh());
+            assert_eq!(registry.get("BTCUSDT").unwrap().base_asset, "BTC");
+        }
+
+        #[test]
+        fn load_without_symbols_keeps_the_registry_stale() {
+            let registry = SymbolRegistry::default();
+            assert!(registry
+                .load(&serde_json::json!({"code": -1003, "msg": "Too many requests."}))
+                .is_err());
+            assert!(!registry.is_fresh());
+        }
+    }
+}
@@ -0,0 +1,371 @@
+pub mod transport {
+    use std::{
+        collections::{HashMap, VecDeque},
+        error::Error,
+        fmt::{self, Debug},
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use url::Url;
+
+    // Send, like every future of the client, so calls can run in `tokio::spawn`.
+    pub type TransportFuture<'b> =
+        Pin<Box<dyn Future<Output = Result<HttpResponse, Box<dyn Error>>> + Send + 'b>>;
+
+    pub(crate) fn redact(secret: &str) -> &'static str {
+        if secret.is_empty() {
+            ""
+        } else {
+            "<redacted>"
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct HttpRequest {
+        pub method: String,
+        pub url: String,
+        pub headers: Vec<(String, String)>,
+        pub query: Vec<(String, String)>,
+    }
+
+    impl HttpRequest {
+        pub fn path(&self) -> String {
+            Url::parse(&self.url)
+                .map(|a| a.path().to_string())
+                .unwrap_or_default()
+        }
+
+        // The url as sent, with `query` encoded by `QueryBuilder` so it matches what was signed.
+        pub fn full_url(&self) -> String {
+            let query = QueryBuilder::from_pairs(&self.query).build();
+            match (query.is_empty(), self.url.contains('?')) {
+                (true, _) => self.url.clone(),
+                (false, true) => format!("{}&{}", self.url, query),
+                (false, false) => format!("{}?{}", self.url, query),
+            }
+        }
+    }
+
+    // Query params in a fixed order, form-urlencoded once. The signature is computed over
+    // `build()` and the same string goes on the wire, so what is signed is what is sent.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct QueryBuilder {
+        params: Vec<(String, String)>,
+    }
+
+    impl QueryBuilder {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        // Sorted by key, so a map always gives the same query.
+        pub fn from_map(param_map: &HashMap<String, String>) -> Self {
+            let mut params = param_map
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<Vec<_>>();
+            params.sort();
+            QueryBuilder { params: params }
+        }
+
+        pub fn from_pairs(pairs: &[(String, String)]) -> Self {
+            QueryBuilder {
+                params: pairs.to_vec(),
+            }
+        }
+
+        // Keys may repeat, e.g. one `asset` per asset.
+        pub fn push(mut self, key: &str, value: &str) -> Self {
+            self.params.push((key.to_string(), value.to_string()));
+            self
+        }
+
+        pub fn build(&self) -> String {
+            url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(&self.params)
+                .finish()
+        }
+
+        pub fn into_pairs(self) -> Vec<(String, String)> {
+            self.params
+        }
+    }
+
+    // The api key header, the signature and the listen key are masked.
+    impl fmt::Debug for HttpRequest {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let mask = |pairs: &Vec<(String, String)>| {
+                pairs
+                    .iter()
+                    .map(|(key, value)| match key.to_lowercase().as_str() {
+                        "x-mbx-apikey" | "signature" | "listenkey" => {
+                            (key.clone(), redact(value).to_string())
+                        }
+                        _ => (key.clone(), value.clone()),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            f.debug_struct("HttpRequest")
+                .field("method", &self.method)
+                .field(
+                    "url",
+                    &match self.url.find("signature=") {
+                        Some(i) => format!("{}signature=<redacted>", &self.url[..i]),
+                        None => self.url.clone(),
+                    },
+                )
+                .field("headers", &mask(&self.headers))
+                .field("query", &mask(&self.query))
+                .finish()
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct HttpResponse {
+        pub status: u16,
+        pub headers: HashMap<String, String>,
+        pub body: String,
+    }
+
+    impl HttpResponse {
+        pub fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+
+        pub fn used_weight(&self) -> Option<u64> {
+            self.header("X-MBX-USED-WEIGHT-1M")
+                .and_then(|a| a.parse().ok())
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Response<T> {
+        pub status: u16,
+        pub headers: HashMap<String, String>,
+        pub body: String,
+        pub data: T,
+    }
+
+    impl<T> Response<T> {
+        pub fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    // Send + Sync so stream threads can renew a listen key through the same transport.
+    pub trait Transport: Debug + Send + Sync {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+
+        fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            Box::pin(tokio::time::sleep(duration))
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct ReqwestTransport {
+        client: reqwest::Client,
+    }
+
+    impl ReqwestTransport {
+        pub fn new(client: reqwest::Client) -> Self {
+            ReqwestTransport { client: client }
+        }
+    }
+
+    impl Transport for ReqwestTransport {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async move {
+                let url = request.full_url();
+                let mut res = match request.method.as_str() {
+                    "GET" => self.client.get(&url),
+                    "POST" => self.client.post(&url),
+                    "PUT" => self.client.put(&url),
+                    "DELETE" => self.client.delete(&url),
+                    method => {
+                        return Err(format!("Request method `{method}` is not defined.").into())
+                    }
+                };
+                for (key, value) in &request.headers {
+                    res = res.header(key, value);
+                }
+                let res = res.send().await?;
+                let status = res.status().as_u16();
+                let headers = res
+                    .headers()
+                    .iter()
+                    .map(|(key, value)| {
+                        (
+                            key.to_string(),
+                            value.to_str().unwrap_or_default().to_string(),
+                        )
+                    })
+                    .collect();
+                Ok(HttpResponse {
+                    status: status,
+                    headers: headers,
+                    body: res.text().await?,
+                })
+            })
+        }
+    }
+
+    // Answers requests by url path with canned JSON, and keeps every request it saw.
+    #[derive(Debug, Default)]
+    pub struct MockTransport {
+        fixtures: Mutex<HashMap<String, String>>,
+        // Used up one per request, in order, before the fixture of the same path.
+        responses: Mutex<HashMap<String, VecDeque<HttpResponse>>>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    impl MockTransport {
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        pub fn with_fixture(self, path: &str, body: &str) -> Self {
+            self.set_fixture(path, body);
+            self
+        }
+
+        pub fn set_fixture(&self, path: &str, body: &str) {
+            self.fixtures
+                .lock()
+                .unwrap()
+                .insert(path.to_string(), body.to_string());
+        }
+
+        pub fn push_response(&self, path: &str, status: u16, body: &str) {
+            self.responses
+                .lock()
+                .unwrap()
+                .entry(path.to_string())
+                .or_default()
+                .push_back(HttpResponse {
+                    status: status,
+                    headers: HashMap::new(),
+                    body: body.to_string(),
+                });
+        }
+
+        pub fn requests(&self) -> Vec<HttpRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            Box::pin(async move {
+                let path = request.path();
+                self.requests.lock().unwrap().push(request);
+                let queued = self
+                    .responses
+                    .lock()
+                    .unwrap()
+                    .get_mut(&path)
+                    .and_then(|a| a.pop_front());
+                if let Some(response) = queued {
+                    return Ok(response);
+                }
+                match self.fixtures.lock().unwrap().get(&path) {
+                    Some(body) => Ok(HttpResponse {
+                        status: 200,
+                        headers: HashMap::new(),
+                        body: body.clone(),
+                    }),
+                    None => Err(format!("No fixture for `{path}`.").into()),
+                }
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        }
+
+        #[test]
+        fn query_keeps_the_order_of_the_documented_example() {
+            let query = QueryBuilder::from_pairs(&pairs(&[
+                ("symbol", "LTCBTC"),
+                ("side", "BUY"),
+                ("type", "LIMIT"),
+                ("timeInForce", "GTC"),
+                ("quantity", "1"),
+                ("price", "0.1"),
+                ("recvWindow", "5000"),
+                ("timestamp", "1499827319559"),
+            ]));
+            assert_eq!(
+                query.build(),
+                "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559"
+            );
+        }
+
+        #[test]
+        fn map_query_is_sorted_and_encoded_once() {
+            let param_map: HashMap<String, String> = pairs(&[
+                ("symbols", "[\"BTCUSDT\",\"ETHUSDT\"]"),
+                ("asset", "BTC"),
+                ("note", "a b&c"),
+            ])
+            .into_iter()
+            .collect();
+            assert_eq!(
+                QueryBuilder::from_map(&param_map).build(),
+                "asset=BTC&note=a+b%26c&symbols=%5B%22BTCUSDT%22%2C%22ETHUSDT%22%5D"
+            );
+        }
+
+        #[test]
+        fn full_url_sends_the_signed_query() {
+            let query = QueryBuilder::new()
+                .push("symbols", "[\"BTCUSDT\",\"ETHUSDT\"]")
+                .push("timestamp", "1499827319559");
+            let signed = query.build();
+            let request = HttpRequest {
+                method: "GET".to_string(),
+                url: "https://api.binance.com/api/v3/ticker/price".to_string(),
+                headers: vec![],
+                query: query.push("signature", "abc").into_pairs(),
+            };
+            assert_eq!(
+                request.full_url(),
+                format!("https://api.binance.com/api/v3/ticker/price?{signed}&signature=abc")
+            );
+        }
+    }
+
+    impl<T: Transport + ?Sized> Transport for Arc<T> {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            (**self).send(request)
+        }
+
+        fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            (**self).sleep(duration)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct TransportHandle(pub Arc<dyn Transport>);
+
+    impl Default for TransportHandle {
+        fn default() -> Self {
+            TransportHandle(Arc::new(ReqwestTransport::default()))
+        }
+    }
+}
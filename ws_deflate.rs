@@ -0,0 +1,455 @@
+pub mod ws_deflate {
+    use flate2::{Decompress, FlushDecompress, Status};
+    use std::{
+        error::Error,
+        io::{self, Read, Write},
+        net::TcpStream,
+    };
+    use tungstenite::{
+        client::IntoClientRequest, handshake::HandshakeError, http::HeaderValue,
+        protocol::WebSocket, stream::MaybeTlsStream,
+    };
+    use url::Url;
+
+    // What a stream thread reads: tungstenite over `DeflateStream`.
+    pub(crate) type StreamSocket = WebSocket<DeflateStream<MaybeTlsStream<TcpStream>>>;
+
+    // The TCP socket under a websocket stream, to set its read timeout.
+    pub(crate) trait TcpSocket {
+        fn tcp(&self) -> Option<&TcpStream>;
+    }
+
+    impl TcpSocket for MaybeTlsStream<TcpStream> {
+        fn tcp(&self) -> Option<&TcpStream> {
+            match self {
+                MaybeTlsStream::Plain(stream) => Some(stream),
+                #[cfg(feature = "native-tls")]
+                MaybeTlsStream::NativeTls(stream) => Some(stream.get_ref()),
+                _ => None,
+            }
+        }
+    }
+
+    impl<S: TcpSocket> TcpSocket for DeflateStream<S> {
+        fn tcp(&self) -> Option<&TcpStream> {
+            self.inner.tcp()
+        }
+    }
+
+    // tungstenite 0.20 fails a connection on any frame with RSV1 set, so permessage-deflate
+    // (RFC 7692) is undone below it: once the server accepted the extension, compressed messages
+    // are inflated and handed on as plain frames. Nothing is compressed on the way out, which the
+    // extension allows.
+    pub(crate) struct DeflateStream<S> {
+        inner: S,
+        // The extension was asked for; false passes every byte through.
+        requested: bool,
+        handshake_done: bool,
+        // Negotiated; None until then and when the server declined.
+        inflater: Option<Decompress>,
+        // The server resets its window after every message.
+        reset_per_message: bool,
+        // Bytes read but not parsed yet, and parsed bytes not handed out yet.
+        read_buf: Vec<u8>,
+        out: Vec<u8>,
+        out_pos: usize,
+        // Opcode and payload so far of a fragmented compressed message.
+        message: Option<(u8, Vec<u8>)>,
+    }
+
+    impl<S: Read + Write> DeflateStream<S> {
+        pub(crate) fn new(inner: S, requested: bool) -> Self {
+            DeflateStream {
+                inner: inner,
+                requested: requested,
+                handshake_done: false,
+                inflater: None,
+                reset_per_message: false,
+                read_buf: vec![],
+                out: vec![],
+                out_pos: 0,
+                message: None,
+            }
+        }
+
+        pub(crate) fn is_compressed(&self) -> bool {
+            self.inflater.is_some()
+        }
+
+        fn fill(&mut self) -> io::Result<usize> {
+            let mut chunk = [0u8; 16384];
+            let n = self.inner.read(&mut chunk)?;
+            self.read_buf.extend_from_slice(&chunk[..n]);
+            Ok(n)
+        }
+
+        // Hands out the HTTP response up to its blank line only, and looks for the extension in
+        // it; the frames after it are parsed.
+        fn read_handshake(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            loop {
+                if let Some(end) = self.read_buf.windows(4).position(|a| a == b"\r\n\r\n") {
+                    let response = String::from_utf8_lossy(&self.read_buf[..end]).to_lowercase();
+                    let accepted = response.lines().find(|a| {
+                        a.starts_with("sec-websocket-extensions:")
+                            && a.contains("permessage-deflate")
+                    });
+                    if let Some(line) = accepted {
+                        self.inflater = Some(Decompress::new(false));
+                        self.reset_per_message = line.contains("server_no_context_takeover");
+                    }
+                    self.handshake_done = true;
+                    self.out = self.read_buf.drain(..end + 4).collect();
+                    self.out_pos = 0;
+                    return Ok(self.copy_out(buf));
+                }
+                if self.fill()? == 0 {
+                    return Ok(self.copy_out_raw(buf));
+                }
+            }
+        }
+
+        fn copy_out(&mut self, buf: &mut [u8]) -> usize {
+            let n = buf.len().min(self.out.len() - self.out_pos);
+            buf[..n].copy_from_slice(&self.out[self.out_pos..self.out_pos + n]);
+            self.out_pos += n;
+            n
+        }
+
+        // At EOF whatever was read is handed on as is.
+        fn copy_out_raw(&mut self, buf: &mut [u8]) -> usize {
+            let n = buf.len().min(self.read_buf.len());
+            buf[..n].copy_from_slice(&self.read_buf[..n]);
+            self.read_buf.drain(..n);
+            n
+        }
+
+        // Turns the next complete frame in `read_buf` into `out`; false if it isn't all there.
+        fn next_frame(&mut self) -> io::Result<bool> {
+            let buf = &self.read_buf;
+            if buf.len() < 2 {
+                return Ok(false);
+            }
+            let (first, second) = (buf[0], buf[1]);
+            let masked = second & 0x80 != 0;
+            let (len, mut header_len) = match second & 0x7f {
+                126 if buf.len() >= 4 => (u16::from_be_bytes([buf[2], buf[3]]) as usize, 4),
+                127 if buf.len() >= 10 => {
+                    let mut len = [0u8; 8];
+                    len.copy_from_slice(&buf[2..10]);
+                    (u64::from_be_bytes(len) as usize, 10)
+                }
+                126 | 127 => return Ok(false),
+                len => (len as usize, 2),
+            };
+            if masked {
+                header_len += 4;
+            }
+            if buf.len() < header_len + len {
+                return Ok(false);
+            }
+            let frame: Vec<u8> = self.read_buf.drain(..header_len + len).collect();
+            let fin = first & 0x80 != 0;
+            let rsv1 = first & 0x40 != 0;
+            let opcode = first & 0x0f;
+            // Control frames, masked ones tungstenite rejects anyway and plain messages.
+            let compressed =
+                !masked && (rsv1 && opcode < 8 || opcode == 0 && self.message.is_some());
+            if !compressed {
+                self.emit(frame);
+                return Ok(true);
+            }
+            let payload = &frame[header_len..];
+            match &mut self.message {
+                Some((_, message)) => message.extend_from_slice(payload),
+                None => self.message = Some((opcode, payload.to_vec())),
+            }
+            if fin {
+                let (opcode, message) = self.message.take().unwrap_or_default();
+                let inflated = self.inflate(message)?;
+                self.emit(plain_frame(opcode, &inflated));
+            }
+            Ok(true)
+        }
+
+        fn emit(&mut self, frame: Vec<u8>) {
+            self.out = frame;
+            self.out_pos = 0;
+        }
+
+        fn inflate(&mut self, mut message: Vec<u8>) -> io::Result<Vec<u8>> {
+            let inflater = match &mut self.inflater {
+                Some(inflater) => inflater,
+                None => return Ok(message),
+            };
+            // The tail every compressed message was stripped of.
+            message.extend_from_slice(&[0x00, 0x00, 0xff, 0xff]);
+            let mut inflated = Vec::with_capacity(message.len() * 4);
+            let start = inflater.total_in();
+            loop {
+                let consumed = (inflater.total_in() - start) as usize;
+                if inflated.len() == inflated.capacity() {
+                    inflated.reserve(message.len() * 4);
+                }
+                let status = inflater
+                    .decompress_vec(&message[consumed..], &mut inflated, FlushDecompress::Sync)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                let consumed = (inflater.total_in() - start) as usize;
+                if status == Status::StreamEnd
+                    || consumed >= message.len() && inflated.len() < inflated.capacity()
+                {
+                    break;
+                }
+            }
+            if self.reset_per_message {
+                inflater.reset(false);
+            }
+            Ok(inflated)
+        }
+    }
+
+    // An unmasked, final frame as a server sends it.
+    fn plain_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            len if len < 126 => frame.push(len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    impl<S: Read + Write> Read for DeflateStream<S> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if !self.requested {
+                return self.inner.read(buf);
+            }
+            if self.out_pos < self.out.len() {
+                return Ok(self.copy_out(buf));
+            }
+            if !self.handshake_done {
+                return self.read_handshake(buf);
+            }
+            if self.inflater.is_none() {
+                if !self.read_buf.is_empty() {
+                    return Ok(self.copy_out_raw(buf));
+                }
+                return self.inner.read(buf);
+            }
+            loop {
+                if self.next_frame()? {
+                    if self.out_pos < self.out.len() {
+                        return Ok(self.copy_out(buf));
+                    }
+                    continue;
+                }
+                if self.fill()? == 0 {
+                    return Ok(self.copy_out_raw(buf));
+                }
+            }
+        }
+    }
+
+    impl<S: Write> Write for DeflateStream<S> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    // Connects like `tungstenite::connect`, asking for permessage-deflate if `compression`. The
+    // frames are inflated between TLS and tungstenite, so TLS is set up here rather than by
+    // tungstenite: `wss` needs the `native-tls` feature, whatever TLS tungstenite is built with.
+    pub(crate) fn connect_stream(
+        url: &str,
+        compression: bool,
+    ) -> Result<StreamSocket, Box<dyn Error>> {
+        let parsed = Url::parse(url)?;
+        let host = parsed.host_str().ok_or(format!("No host in `{url}`."))?;
+        let port = parsed
+            .port_or_known_default()
+            .ok_or(format!("No port in `{url}`."))?;
+        let tcp = TcpStream::connect((host, port))?;
+        tcp.set_nodelay(true)?;
+        let stream = match parsed.scheme() {
+            #[cfg(feature = "native-tls")]
+            "wss" => {
+                MaybeTlsStream::NativeTls(native_tls::TlsConnector::new()?.connect(host, tcp)?)
+            }
+            #[cfg(not(feature = "native-tls"))]
+            "wss" => {
+                return Err(format!("Streaming `{url}` needs the `native-tls` feature.").into())
+            }
+            _ => MaybeTlsStream::Plain(tcp),
+        };
+        let mut request = url.into_client_request()?;
+        if compression {
+            request.headers_mut().insert(
+                "Sec-WebSocket-Extensions",
+                HeaderValue::from_static("permessage-deflate"),
+            );
+        }
+        let stream = DeflateStream::new(stream, compression);
+        match tungstenite::client::client(request, stream) {
+            Ok((ws, _response)) => Ok(ws),
+            Err(HandshakeError::Failure(e)) => Err(e.into()),
+            Err(HandshakeError::Interrupted(_)) => Err("Websocket handshake interrupted.".into()),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use flate2::{Compress, Compression, FlushCompress};
+
+        const ACCEPTED: &[u8] = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nSec-WebSocket-Extensions: permessage-deflate\r\n\r\n";
+        const NO_CONTEXT_TAKEOVER: &[u8] = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nSec-WebSocket-Extensions: permessage-deflate; server_no_context_takeover\r\n\r\n";
+        const DECLINED: &[u8] = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n";
+        const MESSAGE: &[u8] = br#"{"e":"aggTrade","s":"BTCUSDT","p":"100.0","q":"1.0"}"#;
+
+        // The server side of a connection: reads come from `input`, writes are dropped.
+        struct Server {
+            input: io::Cursor<Vec<u8>>,
+        }
+
+        impl Read for Server {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.input.read(buf)
+            }
+        }
+
+        impl Write for Server {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        fn frame(fin: bool, rsv1: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+            let mut frame = plain_frame(opcode, payload);
+            frame[0] = (fin as u8) << 7 | (rsv1 as u8) << 6 | opcode;
+            frame
+        }
+
+        // A message as the server compresses it, without the 00 00 ff ff tail.
+        fn deflate(compress: &mut Compress, message: &[u8]) -> Vec<u8> {
+            let mut out = Vec::with_capacity(message.len() + 64);
+            compress
+                .compress_vec(message, &mut out, FlushCompress::Sync)
+                .unwrap();
+            assert!(out.ends_with(&[0x00, 0x00, 0xff, 0xff]));
+            out.truncate(out.len() - 4);
+            out
+        }
+
+        // Everything tungstenite would read from `input`, and whether it was compressed.
+        fn read_through(requested: bool, input: Vec<u8>) -> (Vec<u8>, bool) {
+            let mut stream = DeflateStream::new(
+                Server {
+                    input: io::Cursor::new(input),
+                },
+                requested,
+            );
+            let mut out = vec![];
+            stream.read_to_end(&mut out).unwrap();
+            (out, stream.is_compressed())
+        }
+
+        #[test]
+        fn inflates_a_single_frame_message() {
+            let compressed = deflate(&mut Compress::new(Compression::default(), false), MESSAGE);
+            let input = [ACCEPTED, &frame(true, true, 1, &compressed)].concat();
+            let (out, compressed) = read_through(true, input);
+            assert!(compressed);
+            assert_eq!(out, [ACCEPTED, &plain_frame(1, MESSAGE)].concat());
+        }
+
+        #[test]
+        fn inflates_a_fragmented_message() {
+            let compressed = deflate(&mut Compress::new(Compression::default(), false), MESSAGE);
+            let (head, tail) = compressed.split_at(compressed.len() / 2);
+            let input = [
+                ACCEPTED,
+                &frame(false, true, 1, head),
+                &frame(true, false, 0, tail),
+            ]
+            .concat();
+            let (out, _) = read_through(true, input);
+            assert_eq!(out, [ACCEPTED, &plain_frame(1, MESSAGE)].concat());
+        }
+
+        #[test]
+        fn passes_control_frames_between_fragments_through() {
+            let compressed = deflate(&mut Compress::new(Compression::default(), false), MESSAGE);
+            let (head, tail) = compressed.split_at(compressed.len() / 2);
+            let ping = frame(true, false, 9, b"ping");
+            let input = [
+                ACCEPTED,
+                &frame(false, true, 1, head),
+                &ping,
+                &frame(true, false, 0, tail),
+            ]
+            .concat();
+            let (out, _) = read_through(true, input);
+            assert_eq!(out, [ACCEPTED, &ping, &plain_frame(1, MESSAGE)].concat());
+        }
+
+        #[test]
+        fn keeps_the_window_across_messages_with_context_takeover() {
+            let mut compress = Compress::new(Compression::default(), false);
+            let first = deflate(&mut compress, MESSAGE);
+            // Mostly a back reference into the first message.
+            let second = deflate(&mut compress, MESSAGE);
+            assert!(second.len() < first.len());
+            let input = [
+                ACCEPTED,
+                &frame(true, true, 1, &first),
+                &frame(true, true, 1, &second),
+            ]
+            .concat();
+            let (out, _) = read_through(true, input);
+            let message = plain_frame(1, MESSAGE);
+            assert_eq!(out, [ACCEPTED, &message, &message].concat());
+        }
+
+        #[test]
+        fn resets_the_window_per_message_without_context_takeover() {
+            let first = deflate(&mut Compress::new(Compression::default(), false), MESSAGE);
+            let second = deflate(&mut Compress::new(Compression::default(), false), MESSAGE);
+            let input = [
+                NO_CONTEXT_TAKEOVER,
+                &frame(true, true, 1, &first),
+                &frame(true, true, 1, &second),
+            ]
+            .concat();
+            let (out, compressed) = read_through(true, input);
+            assert!(compressed);
+            let message = plain_frame(1, MESSAGE);
+            assert_eq!(out, [NO_CONTEXT_TAKEOVER, &message, &message].concat());
+        }
+
+        #[test]
+        fn passes_everything_through_when_declined_or_not_requested() {
+            let input = [
+                DECLINED,
+                &frame(false, false, 1, &MESSAGE[..10]),
+                &frame(true, false, 0, &MESSAGE[10..]),
+            ]
+            .concat();
+            assert_eq!(read_through(true, input.clone()), (input.clone(), false));
+            assert_eq!(read_through(false, input.clone()), (input, false));
+        }
+    }
+}
@@ -0,0 +1,338 @@
+pub mod ws_health {
+    use crate::binance_api::binance_api::BinanceAPI;
+    use crate::recorder::recorder::stream_name;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+        time::{Duration, Instant},
+    };
+    use tokio::sync::mpsc;
+
+    // How often a connection is pinged to measure the round trip.
+    const PING_INTERVAL: Duration = Duration::from_secs(30);
+    // The message rate is counted over windows this long.
+    const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+    // A snapshot of one stream connection, from `stream_health`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ConnectionHealth {
+        // e.g. `btcusdt@depth@100ms`, `userData` for a user data stream.
+        pub stream: String,
+        pub connected: bool,
+        // Since the last text message, or since connecting when there was none yet.
+        pub last_message_age: Option<Duration>,
+        pub messages: u64,
+        // Messages per second over the last 10s.
+        pub message_rate: f64,
+        // Of the last answered ping.
+        pub ping_rtt: Option<Duration>,
+        pub reconnects: u64,
+        // Reconnects forced by the watchdog, also counted in `reconnects`.
+        pub stale_reconnects: u64,
+        // Diff depth updates that didn't follow on from the previous one.
+        pub sequence_gaps: u64,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum HealthEvent {
+        // Nothing was read for `silent_for`, so the watchdog reconnects.
+        Stale {
+            stream: String,
+            silent_for: Duration,
+        },
+        SequenceGap {
+            stream: String,
+            symbol: String,
+            // The last update id seen and the first one of the update that followed.
+            last_update_id: i64,
+            first_update_id: i64,
+        },
+        Reconnected {
+            stream: String,
+            reconnects: u64,
+        },
+    }
+
+    #[derive(Debug)]
+    struct ConnectionStats {
+        stream: String,
+        connected: bool,
+        connected_at: Option<Instant>,
+        last_message: Option<Instant>,
+        messages: u64,
+        window_start: Instant,
+        window_messages: u64,
+        message_rate: f64,
+        // When the ping in flight was sent and its payload.
+        ping_sent: Option<(Instant, u64)>,
+        last_ping: Instant,
+        ping_rtt: Option<Duration>,
+        reconnects: u64,
+        stale_reconnects: u64,
+        sequence_gaps: u64,
+        // Symbol -> final update id of its last diff depth update on this connection.
+        depth_ids: HashMap<String, i64>,
+    }
+
+    // The health of every running stream, kept in `StreamTasks`.
+    #[derive(Debug, Default)]
+    pub(crate) struct StreamHealth {
+        next_id: AtomicU64,
+        connections: Mutex<HashMap<u64, Arc<Mutex<ConnectionStats>>>>,
+        // Set by `set_stream_watchdog`.
+        stale_after: Mutex<Option<Duration>>,
+        subscribers: Mutex<Vec<mpsc::Sender<HealthEvent>>>,
+    }
+
+    impl StreamHealth {
+        pub(crate) fn register(self: &Arc<Self>) -> ConnectionMonitor {
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+            let stats = Arc::new(Mutex::new(ConnectionStats {
+                stream: String::new(),
+                connected: false,
+                connected_at: None,
+                last_message: None,
+                messages: 0,
+                window_start: Instant::now(),
+                window_messages: 0,
+                message_rate: 0.0,
+                ping_sent: None,
+                last_ping: Instant::now(),
+                ping_rtt: None,
+                reconnects: 0,
+                stale_reconnects: 0,
+                sequence_gaps: 0,
+                depth_ids: HashMap::new(),
+            }));
+            self.connections.lock().unwrap().insert(id, stats.clone());
+            ConnectionMonitor {
+                id: id,
+                health: self.clone(),
+                stats: stats,
+            }
+        }
+
+        fn emit(&self, event: HealthEvent) {
+            let mut subscribers = self.subscribers.lock().unwrap();
+            // A slow reader misses events instead of stalling the stream.
+            subscribers.retain(|a| match a.try_send(event.clone()) {
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+                _ => true,
+            });
+        }
+    }
+
+    // The side of `StreamHealth` one stream thread updates; dropping it removes the connection.
+    pub(crate) struct ConnectionMonitor {
+        id: u64,
+        health: Arc<StreamHealth>,
+        stats: Arc<Mutex<ConnectionStats>>,
+    }
+
+    impl Drop for ConnectionMonitor {
+        fn drop(&mut self) {
+            self.health.connections.lock().unwrap().remove(&self.id);
+        }
+    }
+
+    impl ConnectionMonitor {
+        pub(crate) fn on_connect(&self, url: &str) {
+            let mut stats = self.stats.lock().unwrap();
+            let reconnected = stats.connected_at.is_some();
+            stats.stream = stream_name(url);
+            stats.connected = true;
+            stats.connected_at = Some(Instant::now());
+            stats.last_message = None;
+            stats.ping_sent = None;
+            stats.last_ping = Instant::now();
+            // Update ids start over on a new connection.
+            stats.depth_ids.clear();
+            if reconnected {
+                stats.reconnects += 1;
+                let event = HealthEvent::Reconnected {
+                    stream: stats.stream.clone(),
+                    reconnects: stats.reconnects,
+                };
+                drop(stats);
+                self.health.emit(event);
+            }
+        }
+
+        pub(crate) fn on_disconnect(&self) {
+            self.stats.lock().unwrap().connected = false;
+        }
+
+        pub(crate) fn on_text(&self, text: &str) {
+            let now = Instant::now();
+            let mut stats = self.stats.lock().unwrap();
+            stats.messages += 1;
+            stats.last_message = Some(now);
+            stats.window_messages += 1;
+            let window = now - stats.window_start;
+            if window >= RATE_WINDOW {
+                stats.message_rate = stats.window_messages as f64 / window.as_secs_f64();
+                stats.window_start = now;
+                stats.window_messages = 0;
+            }
+            if !text.contains("depthUpdate") {
+                return;
+            }
+            if let Some(event) = check_depth_sequence(&mut stats, text) {
+                stats.sequence_gaps += 1;
+                drop(stats);
+                tracing::warn!(event = ?event, "depth stream sequence gap");
+                self.health.emit(event);
+            }
+        }
+
+        // The payload of a ping to send now, if one is due.
+        pub(crate) fn ping_due(&self) -> Option<Vec<u8>> {
+            let mut stats = self.stats.lock().unwrap();
+            if stats.last_ping.elapsed() < PING_INTERVAL {
+                return None;
+            }
+            let payload = self.health.next_id.fetch_add(1, Ordering::SeqCst);
+            stats.last_ping = Instant::now();
+            stats.ping_sent = Some((stats.last_ping, payload));
+            Some(payload.to_be_bytes().to_vec())
+        }
+
+        pub(crate) fn on_pong(&self, payload: &[u8]) {
+            let mut stats = self.stats.lock().unwrap();
+            if let Some((sent, expected)) = stats.ping_sent {
+                if payload == expected.to_be_bytes() {
+                    stats.ping_rtt = Some(sent.elapsed());
+                    stats.ping_sent = None;
+                }
+            }
+        }
+
+        // Whether the watchdog wants the connection reopened. User data streams are quiet by
+        // design and never stale.
+        pub(crate) fn is_stale(&self) -> bool {
+            let stale_after = match *self.health.stale_after.lock().unwrap() {
+                Some(stale_after) => stale_after,
+                None => return false,
+            };
+            let mut stats = self.stats.lock().unwrap();
+            if stats.stream == "userData" {
+                return false;
+            }
+            let silent_for = match stats.last_message.or(stats.connected_at) {
+                Some(since) => since.elapsed(),
+                None => return false,
+            };
+            if silent_for < stale_after {
+                return false;
+            }
+            stats.stale_reconnects += 1;
+            let event = HealthEvent::Stale {
+                stream: stats.stream.clone(),
+                silent_for: silent_for,
+            };
+            drop(stats);
+            tracing::warn!(event = ?event, "stream stale, reconnecting");
+            self.health.emit(event);
+            true
+        }
+    }
+
+    // Spot updates follow on from the previous final id by one, futures ones name it in `pu`.
+    // Partial depth streams are skipped.
+    fn check_depth_sequence(stats: &mut ConnectionStats, text: &str) -> Option<HealthEvent> {
+        let parsed: serde_json::Value = serde_json::from_str(text).ok()?;
+        let stream = parsed["stream"]
+            .as_str()
+            .map(|a| a.to_string())
+            .unwrap_or(stats.stream.clone());
+        if ["@depth5", "@depth10", "@depth20"]
+            .iter()
+            .any(|a| stream.contains(a))
+        {
+            return None;
+        }
+        let data = parsed.get("data").unwrap_or(&parsed);
+        if data["e"].as_str() != Some("depthUpdate") {
+            return None;
+        }
+        let symbol = data["s"].as_str()?.to_string();
+        let first_update_id = data["U"].as_i64()?;
+        let final_update_id = data["u"].as_i64()?;
+        let last = stats.depth_ids.insert(symbol.clone(), final_update_id)?;
+        let gap = match data["pu"].as_i64() {
+            Some(prev) => prev != last,
+            None => first_update_id > last + 1,
+        };
+        if !gap {
+            return None;
+        }
+        Some(HealthEvent::SequenceGap {
+            stream: stream,
+            symbol: symbol,
+            last_update_id: last,
+            first_update_id: first_update_id,
+        })
+    }
+
+    impl<'a> BinanceAPI<'a> {
+        // Every stream connection this client runs, by stream name.
+        pub fn stream_health(&self) -> Vec<ConnectionHealth> {
+            let connections: Vec<_> = self
+                .streams
+                .health
+                .connections
+                .lock()
+                .unwrap()
+                .values()
+                .cloned()
+                .collect();
+            let mut health: Vec<ConnectionHealth> = connections
+                .iter()
+                .map(|a| {
+                    let stats = a.lock().unwrap();
+                    let window = stats.window_start.elapsed();
+                    ConnectionHealth {
+                        stream: stats.stream.clone(),
+                        connected: stats.connected,
+                        last_message_age: stats
+                            .last_message
+                            .or(stats.connected_at)
+                            .map(|a| a.elapsed()),
+                        messages: stats.messages,
+                        // The window in progress until a full one is counted.
+                        message_rate: if stats.message_rate == 0.0
+                            && window >= Duration::from_secs(1)
+                        {
+                            stats.window_messages as f64 / window.as_secs_f64()
+                        } else {
+                            stats.message_rate
+                        },
+                        ping_rtt: stats.ping_rtt,
+                        reconnects: stats.reconnects,
+                        stale_reconnects: stats.stale_reconnects,
+                        sequence_gaps: stats.sequence_gaps,
+                    }
+                })
+                .collect();
+            health.sort_by(|a, b| a.stream.cmp(&b.stream));
+            health
+        }
+
+        // Reconnects any stream, user data streams aside, that read nothing for `stale_after`;
+        // None turns the watchdog off, which is the default. Set it above the longest quiet spell
+        // of the streams used, e.g. a few seconds for depth and minutes for klines.
+        pub fn set_stream_watchdog(&self, stale_after: Option<Duration>) {
+            *self.streams.health.stale_after.lock().unwrap() = stale_after;
+        }
+
+        // Stale connections, depth sequence gaps and reconnects of every stream from now on.
+        pub fn stream_health_events(&self) -> mpsc::Receiver<HealthEvent> {
+            let (tx, rx) = mpsc::channel(1024);
+            self.streams.health.subscribers.lock().unwrap().push(tx);
+            rx
+        }
+    }
+}